@@ -0,0 +1,62 @@
+//! Side-by-side allocation against other AMM liquidity shapes
+
+use crate::curves::Grid;
+use std::str::FromStr;
+
+/// AMM shapes `--compare-amm` can line up against the DLMM schedule
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AmmKind {
+    /// Uniswap v3 concentrated position: constant L, x = L·(1/√P_a - 1/√P_b)
+    UniswapV3,
+}
+impl FromStr for AmmKind {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uniswap-v3" => Ok(AmmKind::UniswapV3),
+            _ => Err(format!("unknown AMM {:?} (expected uniswap-v3)", s)),
+        }
+    }
+}
+
+/// Token amount of a v3 position with liquidity `liquidity` over `[p_a, p_b]` when
+/// the price sits below the range (all base token)
+fn v3_tokens(liquidity: f64, p_a: f64, p_b: f64) -> f64 {
+    liquidity * (1.0 / p_a.sqrt() - 1.0 / p_b.sqrt())
+}
+
+/// Liquidity L for which a v3 position over `[p_lo, p_hi]` holds `tokens` base tokens
+pub fn uniswapv3_liquidity_for_tokens(p_lo: f64, p_hi: f64, tokens: f64) -> f64 {
+    tokens / v3_tokens(1.0, p_lo, p_hi)
+}
+
+/// Liquidity L for which selling a v3 position over `[p_lo, p_hi]` from bottom to
+/// top raises `quote`, L·(√p_hi - √p_lo): the position worth the same capital as a
+/// schedule raising `quote`
+pub fn uniswapv3_liquidity_for_quote(p_lo: f64, p_hi: f64, quote: f64) -> f64 {
+    quote / (p_hi.sqrt() - p_lo.sqrt())
+}
+
+/// Base tokens a Uniswap v3 position of liquidity `liquidity` over `[p_lo, p_hi]`
+/// places in each DLMM bin `[P_i, P_{i+1})` for `i` in `0..bins`.
+///
+/// Bins outside the range get 0; bins straddling an edge get the overlapping part.
+pub fn uniswapv3_allocation(
+    grid: &Grid,
+    p_lo: f64,
+    p_hi: f64,
+    liquidity: f64,
+    bins: i64,
+) -> Vec<f64> {
+    (0..bins)
+        .map(|i| {
+            let a = grid.price_of_bin(i).max(p_lo);
+            let b = grid.price_of_bin(i + 1).min(p_hi);
+            if a < b {
+                v3_tokens(liquidity, a, b)
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
@@ -1,8 +1,16 @@
 //! Bonding curve implementations for DLMM
 
+use crate::math;
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, vec::Vec};
+use anyhow::{anyhow, Result};
+use core::ops::Range;
 use serde::{Deserialize, Serialize};
 
 /// Generic interface for bonding curves on a DLMM price grid
+///
+/// The trait is object-safe and a boxed curve is itself a `Curve`. Cloning a boxed
+/// curve needs the opt-in [`CurveClone`]; use `Box<dyn CurveClone>` for that.
 pub trait Curve {
     /// Returns the name/type of this curve implementation
     fn name(&self) -> &'static str;
@@ -13,37 +21,613 @@ pub trait Curve {
     /// Returns the token allocation for bin i
     fn delta_x_of_bin(&self, i: i64) -> f64;
 
-    /// Computes the cumulative supply from bin 0 to n-1
+    /// Lowest bin index of the schedule; 0 unless the curve extends below P_0
+    fn first_bin(&self) -> i64 {
+        0
+    }
+
+    /// Computes the cumulative supply from `first_bin` to n-1, compensated with
+    /// [`CompensatedSum`] so it matches the schedule's `supply_cum`
+    fn cumulative_supply(&self, n: i64) -> f64 {
+        (self.first_bin()..n)
+            .map(|i| self.delta_x_of_bin(i))
+            .sum::<CompensatedSum>()
+            .value()
+    }
+
+    /// Yields `(i, P_i, ΔX_i)` for each bin in `range`.
+    ///
+    /// Requires `Self: Sized` to keep the trait object-safe; call it on a `Box<dyn Curve>`
+    /// rather than a bare `dyn Curve`.
+    fn bins_iter(&self, range: Range<i64>) -> impl Iterator<Item = (i64, f64, f64)> + '_
+    where
+        Self: Sized,
+    {
+        range.map(|i| (i, self.price_of_bin(i), self.delta_x_of_bin(i)))
+    }
+
+    /// Total ΔX of the bins in `[first_bin, bins)` priced within `[p_lo, p_hi]`.
+    ///
+    /// Prices must increase with the bin index; the bounds are found by bisection.
+    /// Errors unless both prices are finite and > 0 with `p_lo < p_hi`.
+    fn supply_between_prices(&self, p_lo: f64, p_hi: f64, bins: i64) -> Result<f64> {
+        check_price_band(p_lo, p_hi)?;
+        let lo = partition_bins(self, bins, |p| p < p_lo);
+        let hi = partition_bins(self, bins, |p| p <= p_hi);
+        Ok((lo..hi).map(|i| self.delta_x_of_bin(i)).sum())
+    }
+
+    /// Bin holding price `p`: the largest bin in `[first_bin, bins)` priced at or
+    /// below `p`, by bisection over the increasing prices. `None` when `p` is NaN or
+    /// below the first bin's price.
+    fn bin_of_price(&self, p: f64, bins: i64) -> Option<i64> {
+        let end = partition_bins(self, bins, |q| q <= p);
+        (end > self.first_bin()).then(|| end - 1)
+    }
+
+    /// Price of the bin holding the token after `s` tokens have been sold from
+    /// `first_bin`: the DLMM marginal price, constant within a bin.
+    ///
+    /// Walks the compensated cumulative supply over `[first_bin, bins)`, so a bin
+    /// starts exactly where the schedule's `supply_cum` of the previous row ends;
+    /// empty bins are passed over. `s ≤ 0` maps to the first bin and `s` at or past
+    /// the total supply to the last; NaN for a non-finite `s`.
+    ///
+    /// This is the price a buyer pays, stepping bin to bin; for a smooth price
+    /// between bins use [`Curve::price_of_supply`].
+    fn price_at_supply(&self, s: f64, bins: i64) -> f64 {
+        if !s.is_finite() {
+            return f64::NAN;
+        }
+        let lo = self.first_bin();
+        let mut sold = CompensatedSum::default();
+        for i in lo..bins {
+            sold.add(self.delta_x_of_bin(i));
+            if sold.value() > s {
+                return self.price_of_bin(i);
+            }
+        }
+        self.price_of_bin((bins - 1).max(lo))
+    }
+
+    /// Continuous inverse of [`Curve::cumulative_supply`]: the price reached once
+    /// `s` tokens have been sold from `first_bin`.
+    ///
+    /// Bisects over bins `[first_bin, bins)` for the bin whose supply brackets `s`,
+    /// then interpolates geometrically from P_i at S_i towards P_{i+1} at S_{i+1}.
+    /// Clamps to the first bin's price for `s ≤ 0` and to the last bin's price once
+    /// `s` reaches that bin's start; NaN for a NaN `s`. Each probe calls `cumulative_supply`, so curves
+    /// without a closed form pay O(n) per step.
+    ///
+    /// Unlike [`Curve::price_at_supply`], which returns the stepped price of the bin
+    /// holding `s`, this moves continuously through each bin, e.g. for charting.
+    fn price_of_supply(&self, s: f64, bins: i64) -> f64 {
+        if s.is_nan() {
+            return f64::NAN;
+        }
+        let first = self.first_bin();
+        let last = (bins - 1).max(first);
+        if s <= 0.0 {
+            return self.price_of_bin(first);
+        }
+        // Largest i in [first, last] with S_i ≤ s
+        let (mut lo, mut hi) = (first, last);
+        while lo < hi {
+            let mid = lo + (hi - lo + 1) / 2;
+            if self.cumulative_supply(mid) <= s {
+                lo = mid;
+            } else {
+                hi = mid - 1;
+            }
+        }
+        if lo == last {
+            return self.price_of_bin(last);
+        }
+        let dx = self.delta_x_of_bin(lo);
+        let frac = if dx > 0.0 {
+            ((s - self.cumulative_supply(lo)) / dx).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let p = self.price_of_bin(lo);
+        p * math::powf(self.price_of_bin(lo + 1) / p, frac)
+    }
+
+    /// Tokens received for spending `quote_in` from `start_bin` upward, and the
+    /// bin the fill ends in: `(tokens_out, end_bin)`.
+    ///
+    /// Buys each bin in `[start_bin, bins)` whole for `P_i·ΔX_i` of quote until the
+    /// budget no longer covers one, then fills that bin partially with
+    /// `remaining / P_i` tokens, so `end_bin` is the first bin not bought out. A
+    /// budget past the rest of the curve returns every remaining token with
+    /// `end_bin = bins`. A non-positive or NaN `quote_in` buys nothing. `start_bin`
+    /// below [`Curve::first_bin`] starts there.
+    ///
+    /// A single [`execute_buy`] from a fresh [`BuyCursor`] at `start_bin`.
+    fn tokens_for_quote(&self, start_bin: i64, quote_in: f64, bins: i64) -> (f64, i64) {
+        let mut cursor = BuyCursor {
+            bin: start_bin.max(self.first_bin()),
+            ..BuyCursor::default()
+        };
+        let fill = execute_buy(self, bins, &mut cursor, quote_in);
+        (fill.tokens, cursor.bin)
+    }
+
+    /// Quote needed to buy out bins `[start_bin, end_bin)`: Σ P_i·ΔX_i, summed with
+    /// [`CompensatedSum`] like the schedule's `revenue_cum`.
+    ///
+    /// An empty range costs 0; errors when `start_bin > end_bin`.
+    fn quote_to_buy_range(&self, start_bin: i64, end_bin: i64) -> Result<f64> {
+        check_bin_range(start_bin, end_bin)?;
+        Ok((start_bin..end_bin)
+            .map(|i| self.price_of_bin(i) * self.delta_x_of_bin(i))
+            .sum::<CompensatedSum>()
+            .value())
+    }
+}
+
+/// Position of the next buy: the active bin and tokens already sold out of it
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BuyCursor {
+    /// Bin the next token is bought from
+    pub bin: i64,
+    /// Tokens of `bin`'s ΔX already sold
+    pub filled: f64,
+}
+impl BuyCursor {
+    /// A cursor at the curve's [`Curve::first_bin`], with nothing sold yet
+    pub fn at_first_bin<C: Curve + ?Sized>(c: &C) -> Self {
+        BuyCursor {
+            bin: c.first_bin(),
+            filled: 0.0,
+        }
+    }
+}
+
+/// Result of one [`execute_buy`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fill {
+    /// Tokens received
+    pub tokens: f64,
+    /// Quote consumed
+    pub quote_spent: f64,
+    /// Quote left over when the schedule ran out of bins
+    pub quote_unspent: f64,
+}
+
+/// Spends `quote` buying bins in order from `cursor` up to `bins`, advancing the
+/// cursor past every bin it empties. Tokens and quote spent are summed with
+/// [`CompensatedSum`]; a non-positive or NaN `quote` buys nothing.
+pub fn execute_buy<C: Curve + ?Sized>(
+    c: &C,
+    bins: i64,
+    cursor: &mut BuyCursor,
+    quote: f64,
+) -> Fill {
+    let quote = quote.max(0.0);
+    let mut tokens = CompensatedSum::default();
+    let mut spent = CompensatedSum::default();
+    while cursor.bin < bins {
+        let left = quote - spent.value();
+        if left <= 0.0 {
+            break;
+        }
+        let p = c.price_of_bin(cursor.bin);
+        let avail = c.delta_x_of_bin(cursor.bin) - cursor.filled;
+        if avail * p <= left {
+            tokens.add(avail);
+            spent.add(avail * p);
+            cursor.bin += 1;
+            cursor.filled = 0.0;
+        } else {
+            // p > 0 here, and left / p < avail since left < p·avail
+            let t = (left / p).min(avail);
+            tokens.add(t);
+            spent.add(left);
+            cursor.filled += t;
+            break;
+        }
+    }
+    let quote_spent = spent.value().min(quote);
+    Fill {
+        tokens: tokens.value(),
+        quote_spent,
+        quote_unspent: quote - quote_spent,
+    }
+}
+
+fn check_bin_range(start_bin: i64, end_bin: i64) -> Result<()> {
+    if start_bin > end_bin {
+        return Err(anyhow!(
+            "quote_to_buy_range: need start_bin ≤ end_bin (got {}, {})",
+            start_bin,
+            end_bin
+        ));
+    }
+    Ok(())
+}
+
+fn check_price_band(p_lo: f64, p_hi: f64) -> Result<()> {
+    for (name, p) in [("p_lo", p_lo), ("p_hi", p_hi)] {
+        if !(p.is_finite() && p > 0.0) {
+            return Err(anyhow!(
+                "supply_between_prices: {} must be finite and > 0 (got {})",
+                name,
+                p
+            ));
+        }
+    }
+    if p_lo >= p_hi {
+        return Err(anyhow!(
+            "supply_between_prices: need p_lo < p_hi (got {}, {})",
+            p_lo,
+            p_hi
+        ));
+    }
+    Ok(())
+}
+
+/// First bin in `[first_bin, bins)` whose price fails `pred`, or `bins` if none does;
+/// `pred` must hold on a prefix of the (increasing) prices.
+fn partition_bins<C: Curve + ?Sized>(c: &C, bins: i64, pred: impl Fn(f64) -> bool) -> i64 {
+    let (mut lo, mut hi) = (c.first_bin(), bins.max(c.first_bin()));
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(c.price_of_bin(mid)) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+/// Neumaier compensated running sum: error stays O(ε) independent of the number
+/// of terms, where a plain `+=` loop drifts by O(n·ε)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CompensatedSum {
+    sum: f64,
+    comp: f64,
+}
+impl CompensatedSum {
+    /// Starts the sum at `value` with no carried error
+    pub fn starting_at(value: f64) -> Self {
+        CompensatedSum {
+            sum: value,
+            comp: 0.0,
+        }
+    }
+    /// Adds `x`, folding the rounding error into the compensation term
+    pub fn add(&mut self, x: f64) {
+        let t = self.sum + x;
+        if self.sum.abs() >= x.abs() {
+            self.comp += (self.sum - t) + x;
+        } else {
+            self.comp += (x - t) + self.sum;
+        }
+        self.sum = t;
+    }
+    /// Compensated total so far
+    pub fn value(&self) -> f64 {
+        self.sum + self.comp
+    }
+}
+impl core::iter::Sum<f64> for CompensatedSum {
+    fn sum<I: Iterator<Item = f64>>(iter: I) -> Self {
+        let mut acc = CompensatedSum::default();
+        iter.for_each(|x| acc.add(x));
+        acc
+    }
+}
+
+/// Bins of `range` to draw: all of them when they fit in `max_points` (at least
+/// 2), otherwise a fixed stride that keeps the first and last bin and at most
+/// `max_points` in total. Lazy, so huge ranges allocate nothing.
+pub fn sample_bins(range: Range<i64>, max_points: usize) -> impl Iterator<Item = i64> {
+    let n = range.end.saturating_sub(range.start).max(0);
+    let max_points = i64::try_from(max_points).unwrap_or(i64::MAX).max(2);
+    let stride = if n <= max_points {
+        1
+    } else {
+        // ⌈(n-1)/(m-1)⌉: m-1 strides reach the last bin
+        (n - 1 + max_points - 2) / (max_points - 1)
+    };
+    let last = range.end - 1;
+    let strided = range.clone().step_by(stride as usize);
+    // The stride may step over the last bin; add it back
+    let tail = (n > 0 && (last - range.start) % stride != 0).then_some(last);
+    strided.chain(tail)
+}
+
+/// A [`Curve`] that can be cloned into a trait object, implemented for every
+/// `Clone + 'static` curve; `Box<dyn CurveClone>` is `Clone`
+pub trait CurveClone: Curve {
+    /// Returns a boxed copy of this curve
+    fn clone_boxed(&self) -> Box<dyn CurveClone>;
+}
+impl<T: Curve + Clone + 'static> CurveClone for T {
+    fn clone_boxed(&self) -> Box<dyn CurveClone> {
+        Box::new(self.clone())
+    }
+}
+impl Clone for Box<dyn CurveClone> {
+    fn clone(&self) -> Self {
+        (**self).clone_boxed()
+    }
+}
+impl<C: Curve + ?Sized> Curve for Box<C> {
+    fn name(&self) -> &'static str {
+        (**self).name()
+    }
+    fn price_of_bin(&self, i: i64) -> f64 {
+        (**self).price_of_bin(i)
+    }
+    fn delta_x_of_bin(&self, i: i64) -> f64 {
+        (**self).delta_x_of_bin(i)
+    }
+    fn first_bin(&self) -> i64 {
+        (**self).first_bin()
+    }
     fn cumulative_supply(&self, n: i64) -> f64 {
-        let mut s = 0.0;
-        for i in 0..n {
-            s += self.delta_x_of_bin(i);
+        (**self).cumulative_supply(n)
+    }
+    fn supply_between_prices(&self, p_lo: f64, p_hi: f64, bins: i64) -> Result<f64> {
+        (**self).supply_between_prices(p_lo, p_hi, bins)
+    }
+    fn bin_of_price(&self, p: f64, bins: i64) -> Option<i64> {
+        (**self).bin_of_price(p, bins)
+    }
+    fn price_at_supply(&self, s: f64, bins: i64) -> f64 {
+        (**self).price_at_supply(s, bins)
+    }
+    fn price_of_supply(&self, s: f64, bins: i64) -> f64 {
+        (**self).price_of_supply(s, bins)
+    }
+    fn tokens_for_quote(&self, start_bin: i64, quote_in: f64, bins: i64) -> (f64, i64) {
+        (**self).tokens_for_quote(start_bin, quote_in, bins)
+    }
+    fn quote_to_buy_range(&self, start_bin: i64, end_bin: i64) -> Result<f64> {
+        (**self).quote_to_buy_range(start_bin, end_bin)
+    }
+}
+
+/// Schedule shapes selectable with the CLI's `--mode`, in the order `--list-modes`
+/// prints them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CurveKind {
+    /// [`Geometric`], or [`TwoSided`] with `--below-bins`
+    Geometric,
+    /// [`LogisticS`], or [`AdaptiveLogistic`] with `--adaptive-bins`
+    Logistic,
+    /// [`StepCurve`]
+    Step,
+    /// [`Exponential`]
+    Exponential,
+    /// [`Linear`]
+    Linear,
+}
+impl CurveKind {
+    /// Every kind, in registry order
+    pub const fn all() -> &'static [CurveKind] {
+        &[
+            CurveKind::Geometric,
+            CurveKind::Logistic,
+            CurveKind::Step,
+            CurveKind::Exponential,
+            CurveKind::Linear,
+        ]
+    }
+    /// `--mode` value selecting this kind
+    pub const fn name(self) -> &'static str {
+        match self {
+            CurveKind::Geometric => "geometric",
+            CurveKind::Logistic => "logistic",
+            CurveKind::Step => "step",
+            CurveKind::Exponential => "exponential",
+            CurveKind::Linear => "linear",
+        }
+    }
+    /// One-line description of the allocation shape
+    pub const fn summary(self) -> &'static str {
+        match self {
+            CurveKind::Geometric => "ΔX_i = (R_0/P_0)·r^i with r = q^(θ-1), on the p0·q^i grid",
+            CurveKind::Logistic => {
+                "logistic P(S) between p_min and p_max, discretized onto the grid"
+            }
+            CurveKind::Step => "flat ΔX tiers on the p0·q^i grid",
+            CurveKind::Exponential => "ΔX_i = a·e^(b·i) on the p0·q^i grid",
+            CurveKind::Linear => "ΔX_i = base + slope·i on the p0·q^i grid",
+        }
+    }
+}
+impl core::fmt::Display for CurveKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+impl core::str::FromStr for CurveKind {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        CurveKind::all()
+            .iter()
+            .copied()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = CurveKind::all().iter().map(|k| k.name()).collect();
+                anyhow!("unknown mode {:?} (expected {})", s, names.join("|"))
+            })
+    }
+}
+
+/// What a [`Grid`]'s bin step is applied to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GridSpacing {
+    /// P_i = P_0·q^i with q = 1 + bin_step_bps/10,000 (the DLMM grid)
+    #[default]
+    Price,
+    /// √P_i = √P_0·q_√^i with q_√ = 1 + bin_step_bps/10,000, like Uniswap v3
+    /// ticks; prices grow by q = q_√² per bin
+    SqrtPrice,
+}
+impl core::str::FromStr for GridSpacing {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "price" => Ok(GridSpacing::Price),
+            "sqrt-price" => Ok(GridSpacing::SqrtPrice),
+            _ => Err(anyhow!(
+                "unknown grid spacing {:?} (expected price|sqrt-price)",
+                s
+            )),
         }
-        s
     }
 }
 
 /// DLMM price grid parameters
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+///
+/// `PartialEq` is exact float equality, intended for config identity (round-trips,
+/// dedup) rather than numerical comparison.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Grid {
     /// Initial price at bin 0
     pub p0: f64,
-    /// Bin step size in basis points (e.g., 10 = 0.10%)
+    /// Bin step size in basis points (e.g., 10 = 0.10%), of the price or, under
+    /// [`GridSpacing::SqrtPrice`], of its square root
     pub bin_step_bps: f64,
+    /// Whether `bin_step_bps` steps the price (default) or its square root
+    #[serde(default)]
+    pub spacing: GridSpacing,
 }
 impl Grid {
-    /// Returns the growth factor q = 1 + bin_step_bps/10,000
+    /// Builds a price-spaced grid; see [`Grid::with_spacing`] for square-root spacing
+    pub fn new(p0: f64, bin_step_bps: f64) -> Self {
+        Grid {
+            p0,
+            bin_step_bps,
+            spacing: GridSpacing::Price,
+        }
+    }
+    /// This grid with `bin_step_bps` stepping per `spacing`
+    pub fn with_spacing(self, spacing: GridSpacing) -> Self {
+        Grid { spacing, ..self }
+    }
+    /// Builds a price-spaced grid with `n` bins per 10× price move: q = 10^(1/n)
+    pub fn from_bins_per_decade(p0: f64, n: f64) -> Self {
+        let q = math::powf(10.0, 1.0 / n);
+        Grid::new(p0, (q - 1.0) * 10_000.0)
+    }
+    /// Same bin step and spacing, with P_0 back-computed so that bin `bin` lands
+    /// exactly on `price`
+    pub fn anchored_at(&self, price: f64, bin: i64) -> Self {
+        let p0 = match self.spacing {
+            GridSpacing::Price => price / math::powi_i64(self.q(), bin),
+            GridSpacing::SqrtPrice => {
+                let growth = math::powi_i64(self.q_sqrt(), bin);
+                price / (growth * growth)
+            }
+        };
+        Grid { p0, ..*self }
+    }
+    /// Returns the price growth factor per bin: q = 1 + bin_step_bps/10,000, or
+    /// its square under [`GridSpacing::SqrtPrice`]
     pub fn q(&self) -> f64 {
-        1.0 + self.bin_step_bps / 10_000.0
+        let step = 1.0 + self.bin_step_bps / 10_000.0;
+        match self.spacing {
+            GridSpacing::Price => step,
+            GridSpacing::SqrtPrice => step * step,
+        }
+    }
+    /// Returns the √price growth factor per bin: 1 + bin_step_bps/10,000 under
+    /// [`GridSpacing::SqrtPrice`], √q under [`GridSpacing::Price`]
+    pub fn q_sqrt(&self) -> f64 {
+        match self.spacing {
+            GridSpacing::Price => math::sqrt(self.q()),
+            GridSpacing::SqrtPrice => 1.0 + self.bin_step_bps / 10_000.0,
+        }
     }
-    /// Returns the price at bin i: P_i = P_0 * q^i
+    /// Price step per bin in bps, (q - 1)·10,000: `bin_step_bps` itself on a
+    /// price-spaced grid
+    pub fn price_step_bps(&self) -> f64 {
+        match self.spacing {
+            GridSpacing::Price => self.bin_step_bps,
+            GridSpacing::SqrtPrice => (self.q() - 1.0) * 10_000.0,
+        }
+    }
+    /// Returns the price at bin i: P_i = P_0 * q^i, or (√P_0·q_√^i)² under
+    /// [`GridSpacing::SqrtPrice`], evaluated as P_0·(q_√^i)² so P_0 stays exact
     pub fn price_of_bin(&self, i: i64) -> f64 {
-        self.p0 * self.q().powi(i as i32)
+        match self.spacing {
+            GridSpacing::Price => self.p0 * math::powi_i64(self.q(), i),
+            GridSpacing::SqrtPrice => {
+                let growth = math::powi_i64(self.q_sqrt(), i);
+                self.p0 * (growth * growth)
+            }
+        }
+    }
+    /// Smallest bin index n ≥ 0 with P_n ≥ `p_target` (0 when `p_target` ≤ P_0).
+    /// Saturates at `i64::MAX` for non-finite ratios.
+    pub fn bins_for_price_range(&self, p_target: f64) -> i64 {
+        if p_target <= self.p0 {
+            return 0;
+        }
+        let mut n = math::ceil(math::ln(p_target / self.p0) / math::ln(self.q())) as i64;
+        // Correct for rounding in the logarithm
+        while n > 0 && self.price_of_bin(n - 1) >= p_target {
+            n -= 1;
+        }
+        while n < i64::MAX && self.price_of_bin(n) < p_target {
+            n += 1;
+        }
+        n
+    }
+    /// Bin holding price `p`: the largest i with P_i ≤ p, i.e. ⌊ln(p/P_0)/ln q⌋
+    /// corrected for rounding in the logarithm. Negative below P_0. Closed-form
+    /// counterpart of [`Curve::bin_of_price`] on the unbounded grid.
+    ///
+    /// Errors on a non-finite or non-positive `p`, or a grid that does not increase.
+    pub fn bin_of_price(&self, p: f64) -> Result<i64> {
+        let ratio = p / self.p0;
+        if !(p.is_finite() && p > 0.0 && ratio.is_finite() && ratio > 0.0) {
+            return Err(anyhow!(
+                "bin_of_price: need a finite price > 0 on a grid with P_0 > 0 (got p={}, p0={})",
+                p,
+                self.p0
+            ));
+        }
+        if self.bin_step_bps.is_nan() || self.bin_step_bps <= 0.0 {
+            return Err(anyhow!(
+                "bin_of_price: need bin_step_bps > 0 (got {})",
+                self.bin_step_bps
+            ));
+        }
+        let mut i = math::floor(math::ln(ratio) / math::ln(self.q())) as i64;
+        // Correct for rounding in the logarithm
+        while self.price_of_bin(i) > p {
+            i -= 1;
+        }
+        while self.price_of_bin(i + 1) <= p {
+            i += 1;
+        }
+        Ok(i)
+    }
+}
+
+/// Σ_{i<n} ratio^i as expm1(n·ln ratio)/expm1(ln ratio), see `Geometric::series_sum`
+fn geometric_series(ratio: f64, n: i64) -> f64 {
+    let log_ratio = math::ln_1p(ratio - 1.0);
+    if log_ratio == 0.0 {
+        n as f64
+    } else {
+        math::exp_m1(n as f64 * log_ratio) / math::exp_m1(log_ratio)
     }
 }
 
 /// Geometric bonding curve: ΔX_i = (R_0/P_0) * r^i where r = q^(θ-1)
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+///
+/// `PartialEq` is exact float equality (config identity, see [`Grid`]).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Geometric {
     /// DLMM price grid configuration
     pub grid: Grid,
@@ -53,38 +637,71 @@ pub struct Geometric {
     pub r0_quote: f64,
 }
 impl Geometric {
+    /// Builds the curve running from `p0` at bin 0 to `p_end` at bin `bins`, with
+    /// q = (p_end/p0)^(1/bins) and R_0 solved so that S_bins = `target_supply`.
+    pub fn from_endpoints(
+        p0: f64,
+        p_end: f64,
+        bins: i64,
+        theta: f64,
+        target_supply: f64,
+    ) -> Result<Self> {
+        if !(p0 > 0.0 && p_end > p0 && p_end.is_finite()) {
+            return Err(anyhow!(
+                "from_endpoints: need 0 < p0 < p_end (got p0={}, p_end={})",
+                p0,
+                p_end
+            ));
+        }
+        if bins < 1 || target_supply.is_nan() || target_supply <= 0.0 {
+            return Err(anyhow!(
+                "from_endpoints: need bins ≥ 1 and target_supply > 0 (got {}, {})",
+                bins,
+                target_supply
+            ));
+        }
+        let q = math::powf(p_end / p0, 1.0 / bins as f64);
+        let mut curve = Geometric {
+            grid: Grid::new(p0, (q - 1.0) * 10_000.0),
+            theta,
+            r0_quote: 0.0,
+        };
+        curve.r0_quote = curve.solve_r0_from_supply(target_supply, bins);
+        Ok(curve)
+    }
     /// Returns the decay factor r = q^(θ-1)
     pub fn r(&self) -> f64 {
-        self.grid.q().powf(self.theta - 1.0)
+        math::powf(self.grid.q(), self.theta - 1.0)
     }
     /// Returns the growth factor g = q^θ
     pub fn g(&self) -> f64 {
-        self.grid.q().powf(self.theta)
+        math::powf(self.grid.q(), self.theta)
     }
     /// Returns the initial token allocation ΔX_0 = R_0/P_0
     pub fn delta_x0(&self) -> f64 {
         self.r0_quote / self.grid.p0
     }
-    /// Computes the closed-form cumulative supply S_n
+    /// Geometric series Σ_{i<n} r^i = (r^n - 1)/(r - 1).
+    ///
+    /// Evaluated as expm1(n·ln r)/expm1(ln r), which stays accurate as r → 1
+    /// (θ → 1) and reduces to `n` at r = 1 exactly; the textbook ratio loses
+    /// digits to cancellation there, and a linear cutoff errs by ≈ n·|r-1|/2.
+    fn series_sum(&self, n: i64) -> f64 {
+        geometric_series(self.r(), n)
+    }
+    /// Computes the closed-form cumulative supply S_n = ΔX_0·Σ_{i<n} r^i
     pub fn s_n_closed(&self, n: i64) -> f64 {
-        let r = self.r();
-        if (r - 1.0).abs() < 1e-12 {
-            self.delta_x0() * n as f64
-        } else {
-            self.delta_x0() * (1.0 - r.powi(n as i32)) / (1.0 - r)
-        }
+        self.delta_x0() * self.series_sum(n)
+    }
+    /// Closed-form cumulative revenue Σ_{i<n} P_i·ΔX_i = R_0·Σ_{i<n} g^i, the
+    /// schedule's `revenue_cum` after n bins. Summed like [`Geometric::s_n_closed`],
+    /// so it reduces to R_0·n at g = 1 without a separate linear branch.
+    pub fn revenue_closed(&self, n: i64) -> f64 {
+        self.r0_quote * geometric_series(self.g(), n)
     }
     /// Solves for R_0 given a target total supply S_n
     pub fn solve_r0_from_supply(&self, target_s: f64, n: i64) -> f64 {
-        let r = self.r();
-        if (r - 1.0).abs() < 1e-12 {
-            // In the r→1 limit, S_n = ΔX_0 * n  ⇒  ΔX_0 = target_s / n
-            (target_s / (n as f64)) * self.grid.p0
-        } else {
-            let denom = 1.0 - r.powi(n as i32);
-            let a = target_s * (1.0 - r) / denom;
-            a * self.grid.p0
-        }
+        target_s / self.series_sum(n) * self.grid.p0
     }
 }
 impl Curve for Geometric {
@@ -95,12 +712,167 @@ impl Curve for Geometric {
         self.grid.price_of_bin(i)
     }
     fn delta_x_of_bin(&self, i: i64) -> f64 {
-        self.delta_x0() * self.r().powi(i as i32)
+        self.delta_x0() * math::powi_i64(self.r(), i)
+    }
+    /// O(1) through [`Geometric::s_n_closed`]; `verify_geometric` keeps the bin-by-bin
+    /// sum as its cross-check
+    fn cumulative_supply(&self, n: i64) -> f64 {
+        self.s_n_closed(n.max(0))
+    }
+    /// O(1): R_i = R_0·g^i, so the range costs R_0·g^start·Σ_{k<end-start} g^k
+    fn quote_to_buy_range(&self, start_bin: i64, end_bin: i64) -> Result<f64> {
+        check_bin_range(start_bin, end_bin)?;
+        let g = self.g();
+        Ok(self.r0_quote * math::powi_i64(g, start_bin) * geometric_series(g, end_bin - start_bin))
+    }
+    /// Bounds from [`Grid::bins_for_price_range`], sum from [`Geometric::s_n_closed`]
+    fn supply_between_prices(&self, p_lo: f64, p_hi: f64, bins: i64) -> Result<f64> {
+        check_price_band(p_lo, p_hi)?;
+        let lo = self.grid.bins_for_price_range(p_lo).min(bins);
+        let mut hi = self.grid.bins_for_price_range(p_hi).min(bins);
+        if hi < bins && self.price_of_bin(hi) <= p_hi {
+            hi += 1;
+        }
+        if hi <= lo {
+            return Ok(0.0);
+        }
+        Ok(self.s_n_closed(hi) - self.s_n_closed(lo))
+    }
+    /// Inverts [`Geometric::s_n_closed`] over a continuous bin index:
+    /// x = ln(1 + (s/ΔX_0)·(r - 1))/ln r, so P = P_0·q^x, with the same clamps as
+    /// the default.
+    fn price_of_supply(&self, s: f64, bins: i64) -> f64 {
+        if s.is_nan() {
+            return f64::NAN;
+        }
+        let last = (bins - 1).max(0);
+        if s <= 0.0 {
+            return self.price_of_bin(0);
+        }
+        let log_r = math::ln_1p(self.r() - 1.0);
+        let x = if log_r == 0.0 {
+            s / self.delta_x0()
+        } else {
+            let y = s / self.delta_x0() * math::exp_m1(log_r);
+            // Past the supply an r < 1 series can ever reach
+            if y <= -1.0 {
+                return self.price_of_bin(last);
+            }
+            math::ln_1p(y) / log_r
+        };
+        if x.is_nan() || x >= last as f64 {
+            return self.price_of_bin(last);
+        }
+        self.grid.p0 * math::powf(self.grid.q(), x)
+    }
+}
+
+/// Exponential allocation ΔX_i = a·e^(b·i) on the DLMM grid.
+///
+/// The same family as [`Geometric`] with r = e^b, but parameterized directly by the
+/// per-bin decay rate `b` instead of through the bin step and θ.
+///
+/// `PartialEq` is exact float equality (config identity, see [`Grid`]).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Exponential {
+    /// DLMM price grid configuration
+    pub grid: Grid,
+    /// Allocation of bin 0
+    pub a: f64,
+    /// Growth rate per bin (negative = decaying allocation)
+    pub b: f64,
+}
+impl Exponential {
+    /// Σ_{i<n} e^(b·i) = expm1(n·b)/expm1(b), exactly `n` at b = 0 (see
+    /// [`Geometric::s_n_closed`] for why expm1 rather than a cutoff)
+    fn series_sum(&self, n: i64) -> f64 {
+        if self.b == 0.0 {
+            n as f64
+        } else {
+            math::exp_m1(n as f64 * self.b) / math::exp_m1(self.b)
+        }
+    }
+    /// Solves for `a` given a target total supply over `n` bins
+    pub fn solve_a_from_supply(&self, target_s: f64, n: i64) -> f64 {
+        target_s / self.series_sum(n)
+    }
+}
+impl Curve for Exponential {
+    fn name(&self) -> &'static str {
+        "Exponential(a·e^(b·i))"
+    }
+    fn price_of_bin(&self, i: i64) -> f64 {
+        self.grid.price_of_bin(i)
+    }
+    fn delta_x_of_bin(&self, i: i64) -> f64 {
+        self.a * math::exp(self.b * i as f64)
+    }
+    /// Closed form a·Σ_{i<n} e^(b·i)
+    fn cumulative_supply(&self, n: i64) -> f64 {
+        self.a * self.series_sum(n.max(0))
+    }
+}
+
+/// Linear allocation ΔX_i = base + slope·i on the DLMM grid, for simple fundraising
+/// schedules.
+///
+/// `PartialEq` is exact float equality (config identity, see [`Grid`]).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Linear {
+    /// DLMM price grid configuration
+    pub grid: Grid,
+    /// Allocation of bin 0
+    pub base: f64,
+    /// Change in allocation per bin
+    pub slope: f64,
+}
+impl Linear {
+    /// Checks that `base` and `slope` are finite, `base ≥ 0`, and that the last
+    /// bin's allocation `base + slope·(bins-1)` is still ≥ 0.
+    pub fn new(grid: Grid, base: f64, slope: f64, bins: i64) -> Result<Self> {
+        if !base.is_finite() || base < 0.0 {
+            return Err(anyhow!(
+                "linear: base must be finite and ≥ 0 (got {})",
+                base
+            ));
+        }
+        if !slope.is_finite() {
+            return Err(anyhow!("linear: slope must be finite (got {})", slope));
+        }
+        let curve = Linear { grid, base, slope };
+        let last = curve.delta_x_of_bin(bins - 1);
+        if last < 0.0 {
+            return Err(anyhow!(
+                "linear: ΔX_{} = base + slope·{} = {} < 0; raise base, flatten slope or use fewer bins",
+                bins - 1,
+                bins - 1,
+                last
+            ));
+        }
+        Ok(curve)
+    }
+}
+impl Curve for Linear {
+    fn name(&self) -> &'static str {
+        "Linear(base + slope·i)"
+    }
+    fn price_of_bin(&self, i: i64) -> f64 {
+        self.grid.price_of_bin(i)
+    }
+    fn delta_x_of_bin(&self, i: i64) -> f64 {
+        self.base + self.slope * i as f64
+    }
+    /// Arithmetic series n·base + slope·n(n-1)/2
+    fn cumulative_supply(&self, n: i64) -> f64 {
+        let n = n.max(0) as f64;
+        n * self.base + self.slope * n * (n - 1.0) / 2.0
     }
 }
 
 /// Logistic target P(S) discretized onto the DLMM grid via ΔX_i = S(P_{i+1}) - S(P_i)
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+///
+/// `PartialEq` is exact float equality (config identity, see [`Grid`]).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct LogisticS {
     /// DLMM price grid configuration
     pub grid: Grid,
@@ -116,16 +888,25 @@ pub struct LogisticS {
     pub bins: i64,
 }
 impl LogisticS {
-    fn s_of_p(&self, p: f64) -> f64 {
+    pub(crate) fn s_of_p(&self, p: f64) -> f64 {
         let eps = (self.p_max - self.p_min) * 1e-12;
         let p = p.clamp(self.p_min + eps, self.p_max - eps);
         let num = self.p_max - p;
         let den = p - self.p_min;
-        self.s_mid - (num / den).ln() / self.k
+        self.s_mid - math::ln(num / den) / self.k
     }
     fn s_i(&self, i: i64) -> f64 {
         self.s_of_p(self.grid.price_of_bin(i))
     }
+    fn p_of_s(&self, s: f64) -> f64 {
+        self.p_min + (self.p_max - self.p_min) / (1.0 + math::exp(-self.k * (s - self.s_mid)))
+    }
+    /// Returns `(s_mid, P(s_mid))`, the supply and price at the inflection point where
+    /// dP/dS is steepest. With this parameterization P(s_mid) = (p_min + p_max)/2
+    /// wherever `s_mid` is placed.
+    pub fn inflection(&self) -> (f64, f64) {
+        (self.s_mid, self.p_of_s(self.s_mid))
+    }
 }
 impl Curve for LogisticS {
     fn name(&self) -> &'static str {
@@ -143,3 +924,221 @@ impl Curve for LogisticS {
         (s_ip1 - s_i).max(0.0)
     }
 }
+
+/// Logistic target on an explicit, non-uniform price ladder instead of `p0·q^i`.
+///
+/// Bin prices are spaced uniformly in asin((2P - p_max - p_min)/(p_max - p_min)), so
+/// ΔP ∝ (dS/dP)^(-1/2) and each bin's supply ΔS ∝ (dS/dP)^(1/2): bins are finest in
+/// supply around `s_mid` and coarsest in the flat tails. As with [`LogisticS`],
+/// ΔX_i = S(P_{i+1}) - S(P_i) and the last bin is empty.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AdaptiveLogistic {
+    /// Logistic target; its `grid` and `bins` are not used
+    pub target: LogisticS,
+    /// Increasing bin prices, one per bin
+    pub prices: Vec<f64>,
+}
+impl AdaptiveLogistic {
+    /// Places `bins` prices from `p_start` to `p_end` (both inclusive), which must lie
+    /// strictly between the asymptotes.
+    pub fn new(target: LogisticS, p_start: f64, p_end: f64, bins: i64) -> Result<Self> {
+        let (lo, hi) = (target.p_min, target.p_max);
+        if !(lo < p_start && p_start < p_end && p_end < hi) {
+            return Err(anyhow!(
+                "adaptive bins: need p_min < start < end < p_max (got {} < {} < {} < {})",
+                lo,
+                p_start,
+                p_end,
+                hi
+            ));
+        }
+        if bins < 2 {
+            return Err(anyhow!("adaptive bins: need ≥2 bins (got {})", bins));
+        }
+        let mid = 0.5 * (hi + lo);
+        let half = 0.5 * (hi - lo);
+        let t0 = math::asin((p_start - mid) / half);
+        let t1 = math::asin((p_end - mid) / half);
+        let last = (bins - 1) as f64;
+        let mut prices: Vec<f64> = (0..bins)
+            .map(|j| mid + half * math::sin(t0 + (t1 - t0) * j as f64 / last))
+            .collect();
+        // Pin the endpoints against asin/sin round-off
+        prices[0] = p_start;
+        prices[bins as usize - 1] = p_end;
+        Ok(AdaptiveLogistic { target, prices })
+    }
+
+    /// Price of bin `i`; errors outside the ladder `[0, prices.len())`
+    pub fn checked_price_of_bin(&self, i: i64) -> Result<f64> {
+        usize::try_from(i)
+            .ok()
+            .and_then(|j| self.prices.get(j).copied())
+            .ok_or_else(|| {
+                anyhow!(
+                    "adaptive bins: bin {} is outside the ladder [0, {})",
+                    i,
+                    self.prices.len()
+                )
+            })
+    }
+}
+impl Curve for AdaptiveLogistic {
+    fn name(&self) -> &'static str {
+        "Logistic-S(adaptive bins)"
+    }
+    /// NaN outside the ladder, see [`AdaptiveLogistic::checked_price_of_bin`]
+    fn price_of_bin(&self, i: i64) -> f64 {
+        self.checked_price_of_bin(i).unwrap_or(f64::NAN)
+    }
+    fn delta_x_of_bin(&self, i: i64) -> f64 {
+        if i < 0 || i + 1 >= self.prices.len() as i64 {
+            return 0.0;
+        }
+        let i = i as usize;
+        (self.target.s_of_p(self.prices[i + 1]) - self.target.s_of_p(self.prices[i])).max(0.0)
+    }
+}
+
+/// Piecewise-constant allocation: flat ΔX tiers on the `p0·q^i` grid.
+///
+/// `tiers` holds `(start_bin, delta_x)` sorted by start, the first at bin 0. Tier k
+/// covers `[start_k, start_{k+1})` and the last tier runs to the end of the schedule.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StepCurve {
+    /// Price lattice
+    pub grid: Grid,
+    /// `(start_bin, delta_x)` per tier, strictly increasing in `start_bin`
+    pub tiers: Vec<(i64, f64)>,
+}
+impl StepCurve {
+    /// Checks that the tiers start at bin 0, strictly increase, each begin below
+    /// `bins` (so none is empty) and carry finite ΔX ≥ 0.
+    pub fn new(grid: Grid, tiers: Vec<(i64, f64)>, bins: i64) -> Result<Self> {
+        match tiers.first() {
+            None => return Err(anyhow!("step curve: need at least one tier")),
+            Some(&(start, _)) if start != 0 => {
+                return Err(anyhow!(
+                    "step curve: first tier must start at bin 0 (got {})",
+                    start
+                ))
+            }
+            _ => {}
+        }
+        if let Some(w) = tiers.windows(2).find(|w| w[1].0 <= w[0].0) {
+            return Err(anyhow!(
+                "step curve: tier starts must strictly increase (got {} then {})",
+                w[0].0,
+                w[1].0
+            ));
+        }
+        if let Some(&(start, _)) = tiers.iter().find(|&&(start, _)| start >= bins) {
+            return Err(anyhow!(
+                "step curve: tier at bin {} starts past the last bin {}",
+                start,
+                bins - 1
+            ));
+        }
+        if let Some(&(start, dx)) = tiers.iter().find(|&&(_, dx)| !dx.is_finite() || dx < 0.0) {
+            return Err(anyhow!(
+                "step curve: tier at bin {} needs finite ΔX ≥ 0 (got {})",
+                start,
+                dx
+            ));
+        }
+        Ok(StepCurve { grid, tiers })
+    }
+}
+impl Curve for StepCurve {
+    fn name(&self) -> &'static str {
+        "Step(tiers)"
+    }
+    fn price_of_bin(&self, i: i64) -> f64 {
+        self.grid.price_of_bin(i)
+    }
+    fn delta_x_of_bin(&self, i: i64) -> f64 {
+        // Tiers starting at or before i; the last of them contains i
+        match self.tiers.partition_point(|&(start, _)| start <= i) {
+            0 => 0.0,
+            k => self.tiers[k - 1].1,
+        }
+    }
+    /// Σ over tiers of ΔX_k times the tier's bins below `n`
+    fn cumulative_supply(&self, n: i64) -> f64 {
+        let ends = self.tiers.iter().skip(1).map(|&(start, _)| start);
+        self.tiers
+            .iter()
+            .zip(ends.chain(core::iter::once(i64::MAX)))
+            .map(|(&(start, dx), end)| dx * (end.min(n) - start).max(0) as f64)
+            .sum()
+    }
+}
+
+/// Wraps a curve and zeroes the allocation of bins in `excluded`, leaving prices intact.
+/// Models intentional liquidity gaps in a price band.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaskedCurve<C> {
+    /// Underlying curve supplying prices and allocations
+    pub inner: C,
+    /// Half-open bin range `[start, end)` carrying no liquidity
+    pub excluded: Range<i64>,
+}
+impl<C: Curve> Curve for MaskedCurve<C> {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+    fn price_of_bin(&self, i: i64) -> f64 {
+        self.inner.price_of_bin(i)
+    }
+    fn first_bin(&self) -> i64 {
+        self.inner.first_bin()
+    }
+    fn delta_x_of_bin(&self, i: i64) -> f64 {
+        if self.excluded.contains(&i) {
+            0.0
+        } else {
+            self.inner.delta_x_of_bin(i)
+        }
+    }
+}
+
+/// Geometric schedule mirrored below P_0 for a two-sided initial LP position.
+/// Bin -1-k carries the same ΔX as bin k, so the schedule spans `[-below_bins, bins)`
+/// with bin 0 at P_0.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TwoSided {
+    /// Upside curve, reflected for negative bins
+    pub core: Geometric,
+    /// Number of bins below P_0
+    pub below_bins: i64,
+}
+impl TwoSided {
+    /// Total allocation over bins `[-below_bins, 0)`
+    pub fn supply_below(&self) -> f64 {
+        self.core.s_n_closed(self.below_bins)
+    }
+    /// Total allocation over bins `[0, bins)`
+    pub fn supply_above(&self, bins: i64) -> f64 {
+        self.core.s_n_closed(bins)
+    }
+}
+impl Curve for TwoSided {
+    fn name(&self) -> &'static str {
+        "DLMM-Geometric(θ), two-sided"
+    }
+    fn price_of_bin(&self, i: i64) -> f64 {
+        self.core.price_of_bin(i)
+    }
+    fn delta_x_of_bin(&self, i: i64) -> f64 {
+        if i >= 0 {
+            self.core.delta_x_of_bin(i)
+        } else if i >= -self.below_bins {
+            self.core.delta_x_of_bin(-1 - i)
+        } else {
+            0.0
+        }
+    }
+    fn first_bin(&self) -> i64 {
+        -self.below_bins
+    }
+}
@@ -0,0 +1,159 @@
+//! Per-bin comparison of two schedules, aligned on the `bin` column
+
+use crate::curves::{CompensatedSum, Curve};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The columns of one schedule row that [`diff_schedules`] compares
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BinValues {
+    /// Bin index
+    pub bin: i64,
+    /// P_i
+    pub price: f64,
+    /// ΔX_i
+    pub delta_x: f64,
+    /// P_i·ΔX_i
+    pub revenue_bin: f64,
+}
+
+/// Reads the compared columns of a schedule CSV by header name, skipping the `#`
+/// metadata lines and any other columns
+pub fn read_schedule_values(path: &str) -> Result<Vec<BinValues>> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .comment(Some(b'#'))
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .with_context(|| format!("failed to read {}", path))?;
+    let mut rows = Vec::new();
+    for rec in rdr.deserialize() {
+        rows.push(rec.with_context(|| format!("failed to parse {}", path))?);
+    }
+    Ok(rows)
+}
+
+/// The compared columns of `c`'s schedule over `[first_bin, bins)`, as schedule.csv
+/// would hold them
+pub fn schedule_values<C: Curve + ?Sized>(c: &C, bins: i64) -> Vec<BinValues> {
+    (c.first_bin()..bins)
+        .map(|bin| {
+            let (price, delta_x) = (c.price_of_bin(bin), c.delta_x_of_bin(bin));
+            BinValues {
+                bin,
+                price,
+                delta_x,
+                revenue_bin: price * delta_x,
+            }
+        })
+        .collect()
+}
+
+/// One bin of a schedule diff; a side is empty where that schedule has no such bin
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct BinDiff {
+    /// Bin index
+    pub bin: i64,
+    /// Old P_i
+    pub price_old: Option<f64>,
+    /// New P_i
+    pub price_new: Option<f64>,
+    /// New − old P_i
+    pub price_change: Option<f64>,
+    /// Old ΔX_i
+    pub delta_x_old: Option<f64>,
+    /// New ΔX_i
+    pub delta_x_new: Option<f64>,
+    /// New − old ΔX_i
+    pub delta_x_change: Option<f64>,
+    /// Old P_i·ΔX_i
+    pub revenue_old: Option<f64>,
+    /// New P_i·ΔX_i
+    pub revenue_new: Option<f64>,
+    /// New − old P_i·ΔX_i
+    pub revenue_change: Option<f64>,
+}
+impl BinDiff {
+    /// Whether the bin is missing from one side or any compared value moved
+    pub fn is_change(&self) -> bool {
+        [self.price_change, self.delta_x_change, self.revenue_change]
+            .iter()
+            .any(|d| *d != Some(0.0))
+    }
+}
+
+/// Summary of a [`diff_schedules`] comparison
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DiffSummary {
+    /// Bins in both schedules
+    pub matched: usize,
+    /// Bins only in the old schedule
+    pub only_old: usize,
+    /// Bins only in the new schedule
+    pub only_new: usize,
+    /// Bins with [`BinDiff::is_change`]
+    pub changed: usize,
+    /// Largest |price change| over matched bins
+    pub max_abs_price_change: f64,
+    /// Largest |ΔX change| over matched bins
+    pub max_abs_delta_x_change: f64,
+    /// Largest |revenue change| over matched bins
+    pub max_abs_revenue_change: f64,
+    /// New total supply − old total supply, over all bins of each
+    pub supply_change: f64,
+}
+
+/// Aligns `old` and `new` on `bin` (a later duplicate bin wins) and returns the
+/// per-bin differences in bin order with their summary
+pub fn diff_schedules(old: &[BinValues], new: &[BinValues]) -> (Vec<BinDiff>, DiffSummary) {
+    let mut aligned: BTreeMap<i64, (Option<BinValues>, Option<BinValues>)> = BTreeMap::new();
+    for row in old {
+        aligned.entry(row.bin).or_default().0 = Some(*row);
+    }
+    for row in new {
+        aligned.entry(row.bin).or_default().1 = Some(*row);
+    }
+    let mut summary = DiffSummary::default();
+    let mut supply_change = CompensatedSum::default();
+    let diffs: Vec<BinDiff> = aligned
+        .into_iter()
+        .map(|(bin, (o, n))| {
+            let change = |f: fn(&BinValues) -> f64| Some(f(&n?) - f(&o?));
+            let diff = BinDiff {
+                bin,
+                price_old: o.map(|r| r.price),
+                price_new: n.map(|r| r.price),
+                price_change: change(|r| r.price),
+                delta_x_old: o.map(|r| r.delta_x),
+                delta_x_new: n.map(|r| r.delta_x),
+                delta_x_change: change(|r| r.delta_x),
+                revenue_old: o.map(|r| r.revenue_bin),
+                revenue_new: n.map(|r| r.revenue_bin),
+                revenue_change: change(|r| r.revenue_bin),
+            };
+            match (o, n) {
+                (Some(_), Some(_)) => {
+                    summary.matched += 1;
+                    let max = |m: &mut f64, d: Option<f64>| *m = m.max(d.unwrap_or(0.0).abs());
+                    max(&mut summary.max_abs_price_change, diff.price_change);
+                    max(&mut summary.max_abs_delta_x_change, diff.delta_x_change);
+                    max(&mut summary.max_abs_revenue_change, diff.revenue_change);
+                }
+                (Some(_), None) => summary.only_old += 1,
+                _ => summary.only_new += 1,
+            }
+            if let Some(o) = o {
+                supply_change.add(-o.delta_x);
+            }
+            if let Some(n) = n {
+                supply_change.add(n.delta_x);
+            }
+            if diff.is_change() {
+                summary.changed += 1;
+            }
+            diff
+        })
+        .collect();
+    summary.supply_change = supply_change.value();
+    (diffs, summary)
+}
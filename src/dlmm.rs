@@ -1,11 +1,93 @@
 //! DLMM fee schedule and launch-phase launch policy (allowlist + time-decay surcharge)
+//!
+//! The fee math is `no_std`; the launch policy and allowlist loading need `std`.
 
+use crate::curves::{sample_bins, Curve};
+use crate::math;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::fmt;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+#[cfg(feature = "std")]
+use sha3::{Digest, Keccak256};
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+#[cfg(feature = "std")]
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+#[cfg(feature = "std")]
+use std::str::FromStr;
+
+/// Pluggable fee formula. All rates are decimal (0.01 = 1%) and `va` is the
+/// volatility accumulator in bins crossed.
+pub trait FeeModel {
+    /// Total fee at volatility `va`
+    fn total_fee_rate(&self, va: f64) -> f64;
+
+    /// Volatility-independent component; defaults to the total at `va = 0`
+    fn base_fee_rate(&self) -> f64 {
+        self.total_fee_rate(0.0)
+    }
+
+    /// Volatility-driven component; defaults to total minus base
+    fn variable_fee_rate(&self, va: f64) -> f64 {
+        self.total_fee_rate(va) - self.base_fee_rate()
+    }
+
+    /// Base component for a bin `bins_from_active` away from the active bin;
+    /// defaults to the flat [`FeeModel::base_fee_rate`]
+    fn base_fee_rate_at_bin(&self, _bins_from_active: i64) -> f64 {
+        self.base_fee_rate()
+    }
+
+    /// Total fee for a bin `bins_from_active` away from the active bin; defaults
+    /// to the flat [`FeeModel::total_fee_rate`]
+    fn total_fee_rate_at_bin(&self, va: f64, _bins_from_active: i64) -> f64 {
+        self.total_fee_rate(va)
+    }
+
+    /// Fraction of every fee paid to the protocol rather than LPs, see
+    /// [`split_fee`]; defaults to 0
+    fn protocol_share(&self) -> f64 {
+        0.0
+    }
+}
+
+/// Splits a fee rate into `(protocol, lp)` with `protocol ≈ total·share` (share
+/// clamped to [0, 1]) and `protocol + lp == total` exactly in f64.
+///
+/// Whichever part is at most half of `total` is computed by subtraction from
+/// the other, so the final subtraction is exact (Sterbenz) and the two parts
+/// add back to `total` without rounding drift.
+pub fn split_fee(total: f64, share: f64) -> (f64, f64) {
+    let share = if share.is_nan() {
+        0.0
+    } else {
+        share.clamp(0.0, 1.0)
+    };
+    let protocol = total * share;
+    if protocol <= total * 0.5 {
+        let lp = total - protocol;
+        (total - lp, lp)
+    } else {
+        (protocol, total - protocol)
+    }
+}
+
+/// Decimal fee rate as integer basis points, rounded to nearest (halves up) and
+/// saturating to `[0, 10_000]`; NaN maps to 0.
+pub fn rate_to_bps(rate: f64) -> u32 {
+    // Non-negative after the clamp, so truncating rate·10⁴ + ½ rounds to nearest
+    ((rate * 10_000.0).clamp(0.0, 10_000.0) + 0.5) as u32
+}
 
 /// DLMM fee schedule in decimal space.
-/// f = f_b + f_v, with f_b = B·s and f_v = A·(va·s)^2, capped at `max_fee_rate` (decimal, e.g. 0.05 = 5%).
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+/// f = f_b + f_v, with f_b = B·s and f_v = A·(va·s)^2, clamped into
+/// `[min_fee_rate, max_fee_rate]` (decimal, e.g. 0.05 = 5%).
+///
+/// Fields stay public, but prefer [`DlmmFeeParams::new`] (or call
+/// [`DlmmFeeParams::validate`] after building a literal) so a misconfigured
+/// schedule is rejected up front instead of silently clamped.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct DlmmFeeParams {
     /// Base factor B (dimensionless)
     pub base_factor: f64,
@@ -15,9 +97,146 @@ pub struct DlmmFeeParams {
     pub variable_fee_control: f64,
     /// Max total fee (decimal, e.g., 0.05 = 5%).
     pub max_fee_rate: f64,
+    /// Min total fee (decimal) charged even at zero volatility; 0 = no floor.
+    #[serde(default)]
+    pub min_fee_rate: f64,
+    /// Fraction of the cap in [0, 1) over which the fee ramps into it instead of
+    /// clipping; 0 keeps the hard cap.
+    #[serde(default)]
+    pub cap_softness: f64,
+    /// Per-bin growth δ of the base fee away from the active bin,
+    /// f_b(d) = B·s·(1 + δ·|d|); 0 keeps the base flat.
+    #[serde(default)]
+    pub distance_fee_delta: f64,
+    /// Fraction of the (capped) total fee in [0, 1] that goes to the protocol
+    /// treasury instead of LPs; 0 leaves it all to LPs.
+    #[serde(default)]
+    pub protocol_share: f64,
+}
+
+/// Why [`DlmmFeeParams::validate`] rejected a fee schedule
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FeeParamsError {
+    /// `max_fee_rate` outside [0, 1] decimal
+    MaxFeeRate(f64),
+    /// `min_fee_rate` negative, non-finite or above `max_fee_rate`
+    MinFeeRate(f64),
+    /// `base_factor` negative or non-finite
+    BaseFactor(f64),
+    /// `variable_fee_control` negative or non-finite
+    VariableFeeControl(f64),
+    /// `bin_step_bps` not positive and finite
+    BinStep(f64),
+    /// `cap_softness` outside [0, 1)
+    CapSoftness(f64),
+    /// `distance_fee_delta` negative or non-finite
+    DistanceFeeDelta(f64),
+    /// `protocol_share` outside [0, 1]
+    ProtocolShare(f64),
+}
+
+impl fmt::Display for FeeParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::MaxFeeRate(v) => write!(
+                f,
+                "max_fee_rate must be in [0,1] decimal (got {}; 0.05 = 5%)",
+                v
+            ),
+            Self::MinFeeRate(v) => write!(
+                f,
+                "min_fee_rate must be finite, ≥ 0 and ≤ max_fee_rate (got {})",
+                v
+            ),
+            Self::BaseFactor(v) => write!(f, "base_factor must be finite and ≥ 0 (got {})", v),
+            Self::VariableFeeControl(v) => {
+                write!(f, "variable_fee_control must be finite and ≥ 0 (got {})", v)
+            }
+            Self::BinStep(v) => write!(f, "bin_step_bps must be finite and > 0 (got {})", v),
+            Self::CapSoftness(v) => write!(f, "cap_softness must be in [0, 1) (got {})", v),
+            Self::DistanceFeeDelta(v) => {
+                write!(f, "distance_fee_delta must be finite and ≥ 0 (got {})", v)
+            }
+            Self::ProtocolShare(v) => write!(f, "protocol_share must be in [0, 1] (got {})", v),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FeeParamsError {}
+
+/// True for finite `v ≥ 0` (NaN fails)
+fn non_negative(v: f64) -> bool {
+    v.is_finite() && v >= 0.0
 }
 
 impl DlmmFeeParams {
+    /// Validated constructor with no floor (`min_fee_rate = 0`), a hard cap
+    /// (`cap_softness = 0`), a flat base (`distance_fee_delta = 0`) and no
+    /// protocol fee; see [`Self::validate`] for the checks.
+    pub fn new(
+        base_factor: f64,
+        bin_step_bps: f64,
+        variable_fee_control: f64,
+        max_fee_rate: f64,
+    ) -> Result<Self, FeeParamsError> {
+        let params = Self {
+            base_factor,
+            bin_step_bps,
+            variable_fee_control,
+            max_fee_rate,
+            min_fee_rate: 0.0,
+            cap_softness: 0.0,
+            distance_fee_delta: 0.0,
+            protocol_share: 0.0,
+        };
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// `self` with a fee floor, rejected if `min_fee_rate > max_fee_rate`
+    pub fn with_min_fee_rate(self, min_fee_rate: f64) -> Result<Self, FeeParamsError> {
+        let params = Self {
+            min_fee_rate,
+            ..self
+        };
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Checks `0 ≤ min_fee_rate ≤ max_fee_rate ≤ 1`, `base_factor, variable_fee_control ≥ 0`,
+    /// `bin_step_bps > 0`, `cap_softness ∈ [0, 1)`, `distance_fee_delta ≥ 0` and
+    /// `protocol_share ∈ [0, 1]`.
+    pub fn validate(&self) -> Result<(), FeeParamsError> {
+        if !(0.0..=1.0).contains(&self.max_fee_rate) {
+            return Err(FeeParamsError::MaxFeeRate(self.max_fee_rate));
+        }
+        if !non_negative(self.min_fee_rate) || self.min_fee_rate > self.max_fee_rate {
+            return Err(FeeParamsError::MinFeeRate(self.min_fee_rate));
+        }
+        if !non_negative(self.base_factor) {
+            return Err(FeeParamsError::BaseFactor(self.base_factor));
+        }
+        if !non_negative(self.variable_fee_control) {
+            return Err(FeeParamsError::VariableFeeControl(
+                self.variable_fee_control,
+            ));
+        }
+        if !self.bin_step_bps.is_finite() || self.bin_step_bps <= 0.0 {
+            return Err(FeeParamsError::BinStep(self.bin_step_bps));
+        }
+        if !(0.0..1.0).contains(&self.cap_softness) {
+            return Err(FeeParamsError::CapSoftness(self.cap_softness));
+        }
+        if !non_negative(self.distance_fee_delta) {
+            return Err(FeeParamsError::DistanceFeeDelta(self.distance_fee_delta));
+        }
+        if !(0.0..=1.0).contains(&self.protocol_share) {
+            return Err(FeeParamsError::ProtocolShare(self.protocol_share));
+        }
+        Ok(())
+    }
+
     #[inline]
     fn s_dec(&self) -> f64 {
         self.bin_step_bps / 10_000.0
@@ -29,19 +248,96 @@ impl DlmmFeeParams {
     }
 
     /// Variable fee f_v = A * (va * s)^2 (decimal).
+    ///
+    /// `volatility_accumulator` is measured in bins crossed (dimensionless, not
+    /// scaled by 10,000). Very large values may overflow to `+Inf`.
     pub fn variable_fee_rate(&self, volatility_accumulator: f64) -> f64 {
         let s = self.s_dec();
-        self.variable_fee_control * (volatility_accumulator * s).powi(2)
+        self.variable_fee_control * math::powi(volatility_accumulator * s, 2)
     }
 
-    /// Total fee (decimal), capped at `max_fee_rate` (must be ≤ 1.0).
+    /// Total fee (decimal), clamped into `[min_fee_rate, max_fee_rate]` (cap
+    /// must be ≤ 1.0).
+    ///
+    /// `va` is in bins crossed, as for [`Self::variable_fee_rate`]. A non-finite
+    /// intermediate (e.g. `(va·s)^2` overflowing) yields the cap.
+    ///
+    /// With `cap_softness = w > 0` the fee follows the raw rate up to the knee
+    /// `cap·(1 - w)`, then rises at half slope until it meets the cap at raw
+    /// `cap·(1 + w)`: continuous and monotone, with no jump at the cap.
     pub fn total_fee_rate(&self, va: f64) -> f64 {
+        self.cap_fee(self.base_fee_rate() + self.variable_fee_rate(va))
+    }
+
+    /// Protocol part of [`Self::total_fee_rate`]: the capped total times
+    /// `protocol_share`, see [`split_fee`]
+    pub fn protocol_fee_rate(&self, va: f64) -> f64 {
+        split_fee(self.total_fee_rate(va), self.protocol_share).0
+    }
+
+    /// LP part of [`Self::total_fee_rate`]; adds to [`Self::protocol_fee_rate`]
+    /// exactly
+    pub fn lp_fee_rate(&self, va: f64) -> f64 {
+        split_fee(self.total_fee_rate(va), self.protocol_share).1
+    }
+
+    /// [`Self::base_fee_rate`] in integer bps, see [`rate_to_bps`]
+    pub fn base_fee_bps(&self) -> u32 {
+        rate_to_bps(self.base_fee_rate())
+    }
+
+    /// [`Self::variable_fee_rate`] in integer bps, see [`rate_to_bps`]
+    pub fn variable_fee_bps(&self, va: f64) -> u32 {
+        rate_to_bps(self.variable_fee_rate(va))
+    }
+
+    /// [`Self::total_fee_rate`] in integer bps as contracts take it: rounded to
+    /// nearest and saturating at 10,000. Rounded separately, base and variable bps
+    /// need not add up to this.
+    pub fn total_fee_bps(&self, va: f64) -> u32 {
+        rate_to_bps(self.total_fee_rate(va))
+    }
+
+    /// Base fee for a bin `bins_from_active` away from the active bin:
+    /// f_b·(1 + δ·|bins_from_active|), equal to [`Self::base_fee_rate`] when δ = 0.
+    pub fn base_fee_rate_at_bin(&self, bins_from_active: i64) -> f64 {
+        let d = bins_from_active.unsigned_abs() as f64;
+        self.base_fee_rate() * (1.0 + self.distance_fee_delta * d)
+    }
+
+    /// [`Self::total_fee_rate`] with the base from [`Self::base_fee_rate_at_bin`]
+    pub fn total_fee_rate_at_bin(&self, va: f64, bins_from_active: i64) -> f64 {
+        self.cap_fee(self.base_fee_rate_at_bin(bins_from_active) + self.variable_fee_rate(va))
+    }
+
+    /// `(floor, cap)` of the total fee; an unvalidated floor above the cap
+    /// yields to it
+    fn fee_bounds(&self) -> (f64, f64) {
         let cap = self.max_fee_rate.max(0.0);
-        (self.base_fee_rate() + self.variable_fee_rate(va)).min(cap)
+        (self.min_fee_rate.max(0.0).min(cap), cap)
+    }
+
+    /// Applies `max_fee_rate`, `cap_softness` and then `min_fee_rate` to an
+    /// uncapped fee
+    fn cap_fee(&self, raw: f64) -> f64 {
+        let (floor, cap) = self.fee_bounds();
+        if !raw.is_finite() {
+            return cap;
+        }
+        let knee = cap * (1.0 - self.cap_softness.clamp(0.0, 1.0));
+        let capped = if raw <= knee {
+            raw
+        } else {
+            (knee + 0.5 * (raw - knee)).min(cap)
+        };
+        capped.max(floor)
     }
 
     /// Price impact guards (per docs).
     /// Selling X for Y: min_price = spot * 10000 / (10000 - impact_bps)
+    ///
+    /// Brackets spot from above together with [`Self::min_price_sell_y_for_x`];
+    /// grows without bound as `impact_bps` → 10,000 and is `+∞` at 10,000.
     pub fn min_price_sell_x_for_y(spot_price: f64, max_price_impact_bps: f64) -> f64 {
         spot_price * 10_000.0 / (10_000.0 - max_price_impact_bps)
     }
@@ -51,55 +347,837 @@ impl DlmmFeeParams {
     }
 }
 
+/// Number of fee multiplier steps swept by [`fee_revenue_tradeoff`].
+pub const FEE_TRADEOFF_STEPS: usize = 40;
+/// Largest fee multiplier swept by [`fee_revenue_tradeoff`].
+pub const FEE_TRADEOFF_MAX_MULTIPLIER: f64 = 4.0;
+
+/// Toy fee/volume trade-off: sweeps a multiplier m over the base fee rate and
+/// projects volume as `base_volume * (1 - fee * elasticity)` (floored at zero).
+///
+/// Returns `(fee_rate, projected_fee_revenue)` pairs with `fee_rate` clamped into
+/// `[min_fee_rate, max_fee_rate]`. This is a planning aid, not a market model.
+pub fn fee_revenue_tradeoff(
+    fees: &DlmmFeeParams,
+    elasticity: f64,
+    base_volume: f64,
+) -> Vec<(f64, f64)> {
+    let base = fees.base_fee_rate();
+    let (floor, cap) = fees.fee_bounds();
+    (0..=FEE_TRADEOFF_STEPS)
+        .map(|k| {
+            let m = FEE_TRADEOFF_MAX_MULTIPLIER * k as f64 / FEE_TRADEOFF_STEPS as f64;
+            let fee = (base * m).clamp(floor, cap);
+            let volume = (base_volume * (1.0 - fee * elasticity)).max(0.0);
+            (fee, fee * volume)
+        })
+        .collect()
+}
+
+/// Days per year used to annualize [`estimate_bin_apr`]
+pub const DAYS_PER_YEAR: f64 = 365.0;
+
+/// Rough LP yield per bin: `daily_volume_per_bin · fee(va) · 365 / (P_i · ΔX_i)`.
+///
+/// Returns `(bin, apr)` for every bin from [`Curve::first_bin`] up to `bins`, with
+/// `apr = None` where the bin holds no value (zero or non-finite TVL). Like
+/// [`fee_revenue_tradeoff`] this is a planning aid; it assumes every bin sees the
+/// same quote volume and a constant volatility accumulator.
+pub fn estimate_bin_apr<C: Curve>(
+    c: &C,
+    bins: i64,
+    fees: &dyn FeeModel,
+    va: f64,
+    daily_volume_per_bin: f64,
+) -> Vec<(i64, Option<f64>)> {
+    let income = daily_volume_per_bin * fees.total_fee_rate(va) * DAYS_PER_YEAR;
+    (c.first_bin()..bins)
+        .map(|i| {
+            let tvl = c.price_of_bin(i) * c.delta_x_of_bin(i);
+            let apr = (tvl.is_finite() && tvl > 0.0).then(|| income / tvl);
+            (i, apr)
+        })
+        .collect()
+}
+
+/// Order-book style depth: cumulative token liquidity `(price, depth)` walking
+/// away from `active_bin`, sorted by ascending price.
+///
+/// Ask depth sums ΔX over `[active_bin, bins)`. Bins below the active bin (only a
+/// two-sided curve has any) hold quote instead; their depth is that reserve in
+/// token terms, ΔX_i = Y_i / P_i, summed downward from `active_bin - 1`, so both
+/// sides share one axis. Points priced below `c.price_of_bin(active_bin)` are bids.
+pub fn depth_chart<C: Curve>(c: &C, active_bin: i64, bins: i64) -> Vec<(f64, f64)> {
+    depth_chart_sampled(c, active_bin, bins, usize::MAX)
+}
+
+/// [`depth_chart`] keeping only the [`sample_bins`] of each side, at most
+/// `max_points / 2` (and at least 2) points per side. Depth still accumulates
+/// over every bin, so the kept points are exact, but nothing is allocated per
+/// unsampled bin.
+pub fn depth_chart_sampled<C: Curve>(
+    c: &C,
+    active_bin: i64,
+    bins: i64,
+    max_points: usize,
+) -> Vec<(f64, f64)> {
+    let active = active_bin.clamp(c.first_bin(), bins.max(c.first_bin()));
+    let per_side = max_points / 2;
+    // Bids accumulate downward from the active bin, so walk the samples backwards
+    let mut bid_bins: Vec<i64> = sample_bins(c.first_bin()..active, per_side).collect();
+    let asks = usize::try_from(bins - active)
+        .unwrap_or(0)
+        .min(per_side.max(2));
+    let mut out = Vec::with_capacity(bid_bins.len() + asks);
+    let mut depth = 0.0;
+    for i in (c.first_bin()..active).rev() {
+        depth += c.delta_x_of_bin(i);
+        if bid_bins.last() == Some(&i) {
+            bid_bins.pop();
+            out.push((c.price_of_bin(i), depth));
+        }
+    }
+    out.reverse();
+    let mut ask_bins = sample_bins(active..bins, per_side).peekable();
+    depth = 0.0;
+    for i in active..bins {
+        depth += c.delta_x_of_bin(i);
+        if ask_bins.next_if_eq(&i).is_some() {
+            out.push((c.price_of_bin(i), depth));
+        }
+    }
+    out
+}
+
+/// Fee at an arbitrary `price`, linearly interpolating the per-bin
+/// [`FeeModel::total_fee_rate_at_bin`] between the bracketing bins
+/// P_i ≤ price < P_{i+1} of `[first_bin, bins)`, found with [`Curve::bin_of_price`]
+/// (bin 0 is the active bin, as in the schedule).
+///
+/// The interpolation weight is the fractional bin index ln(price/P_i)/ln(P_{i+1}/P_i),
+/// so a distance fee that is linear in bins stays linear. Where the fee does not
+/// vary per bin the flat fee comes back exactly. Prices at or below the first bin
+/// take its fee, prices at or past the last bin (or where prices stop rising) take
+/// the bracketing bin's fee, and a non-finite price yields NaN.
+pub fn fee_rate_at_price<C: Curve, F: FeeModel + ?Sized>(
+    c: &C,
+    price: f64,
+    bins: i64,
+    fees: &F,
+    va: f64,
+) -> f64 {
+    if !price.is_finite() {
+        return f64::NAN;
+    }
+    let fee = |i: i64| fees.total_fee_rate_at_bin(va, i);
+    let Some(lo) = c.bin_of_price(price, bins) else {
+        return fee(c.first_bin());
+    };
+    let hi = lo + 1;
+    let (p_lo, p_hi) = (c.price_of_bin(lo), c.price_of_bin(hi));
+    if hi >= bins || price <= p_lo || p_hi <= p_lo {
+        return fee(lo);
+    }
+    let t = math::ln(price / p_lo) / math::ln(p_hi / p_lo);
+    let (f_lo, f_hi) = (fee(lo), fee(hi));
+    f_lo + t * (f_hi - f_lo)
+}
+
+impl FeeModel for DlmmFeeParams {
+    fn total_fee_rate(&self, va: f64) -> f64 {
+        DlmmFeeParams::total_fee_rate(self, va)
+    }
+    fn base_fee_rate(&self) -> f64 {
+        DlmmFeeParams::base_fee_rate(self)
+    }
+    fn variable_fee_rate(&self, va: f64) -> f64 {
+        DlmmFeeParams::variable_fee_rate(self, va)
+    }
+    fn base_fee_rate_at_bin(&self, bins_from_active: i64) -> f64 {
+        DlmmFeeParams::base_fee_rate_at_bin(self, bins_from_active)
+    }
+    fn total_fee_rate_at_bin(&self, va: f64, bins_from_active: i64) -> f64 {
+        DlmmFeeParams::total_fee_rate_at_bin(self, va, bins_from_active)
+    }
+    fn protocol_share(&self) -> f64 {
+        self.protocol_share
+    }
+}
+
+/// Example alternate [`FeeModel`]: f = base + slope·va, capped at `max_fee_rate`.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct LinearFee {
+    /// Fee at zero volatility (decimal)
+    pub base: f64,
+    /// Fee added per unit of volatility accumulator (decimal)
+    pub slope: f64,
+    /// Max total fee (decimal)
+    pub max_fee_rate: f64,
+}
+impl FeeModel for LinearFee {
+    fn total_fee_rate(&self, va: f64) -> f64 {
+        let cap = self.max_fee_rate.max(0.0);
+        let raw = self.base + self.slope * va;
+        if !raw.is_finite() {
+            return cap;
+        }
+        raw.min(cap)
+    }
+}
+
+/// How the launch surcharge τ(t) falls from `tau_start_pct` to `tau_end_pct`
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TauDecay {
+    /// Straight line to `tau_end_pct` at `ramp_secs`
+    #[default]
+    Linear,
+    /// τ_end + (τ_start - τ_end)·e^(-t/λ) with λ = `half_life_secs` / ln 2; never
+    /// quite reaches `tau_end_pct` and ignores `ramp_secs`
+    Exponential {
+        /// Seconds for the excess over `tau_end_pct` to halve
+        half_life_secs: f64,
+    },
+}
+#[cfg(feature = "std")]
+impl FromStr for TauDecay {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "linear" {
+            return Ok(TauDecay::Linear);
+        }
+        let half_life = s
+            .strip_prefix("exponential:")
+            .and_then(|h| h.parse::<f64>().ok())
+            .ok_or_else(|| {
+                format!(
+                    "unknown tau decay {:?} (expected linear|exponential:<half_life_secs>)",
+                    s
+                )
+            })?;
+        if !(half_life.is_finite() && half_life > 0.0) {
+            return Err(format!(
+                "half-life must be positive and finite (got {})",
+                half_life
+            ));
+        }
+        Ok(TauDecay::Exponential {
+            half_life_secs: half_life,
+        })
+    }
+}
+
 /// Launch-phase policy: allowlist + time-decaying surcharge τ(t) from τ0 to τ1 over [0, T].
-#[derive(Default, Clone, Debug)]
+#[cfg(feature = "std")]
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct LaunchPhasePolicy {
     /// Addresses exempt from the surcharge
-    pub allowlist: HashSet<String>,
+    #[serde(flatten)]
+    pub allowlist: Allowlist,
     /// Initial surcharge percentage at launch (t=0)
     pub tau_start_pct: f64,
     /// Final surcharge percentage after ramp period
     pub tau_end_pct: f64,
     /// Duration of the ramp period in seconds
     pub ramp_secs: f64,
+    /// Shape of the fall from `tau_start_pct` to `tau_end_pct`
+    #[serde(default)]
+    pub tau_decay: TauDecay,
 }
+#[cfg(feature = "std")]
 impl LaunchPhasePolicy {
-    /// Checks if an address is exempt from launch phase surcharges.
+    /// Policy whose allowlist is [`Allowlist::with_bloom`] over `addresses`.
+    /// The surcharge ramp starts zeroed; set it with struct update syntax.
+    #[cfg(feature = "bloom")]
+    pub fn with_bloom<I, S>(addresses: I, fp_rate: f64) -> anyhow::Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        I::IntoIter: ExactSizeIterator,
+        S: AsRef<str>,
+    {
+        Ok(LaunchPhasePolicy {
+            allowlist: Allowlist::with_bloom(addresses, fp_rate)?,
+            ..LaunchPhasePolicy::default()
+        })
+    }
+
+    /// Checks if an address is exempt from launch phase surcharges at launch
+    /// (t = 0), ignoring ASCII case unless the allowlist is [`AddressMatch::Exact`].
     ///
     /// This is a core API method for integrators implementing launch phase policies.
     /// Addresses on the allowlist can trade without paying the time-decaying surcharge.
     ///
     /// # Example
     /// ```
-    /// use bcurve::dlmm::LaunchPhasePolicy;
-    /// use std::collections::HashSet;
+    /// use bcurve::dlmm::{Allowlist, LaunchPhasePolicy};
     ///
-    /// let mut allowlist = HashSet::new();
-    /// allowlist.insert("whitelisted_user_123".to_string());
+    /// let mut allowlist = Allowlist::default();
+    /// allowlist.insert("Whitelisted_User_123");
     ///
     /// let policy = LaunchPhasePolicy {
     ///     allowlist,
     ///     tau_start_pct: 50.0,
     ///     tau_end_pct: 3.0,
     ///     ramp_secs: 60.0,
+    ///     ..Default::default()
     /// };
     ///
     /// assert!(policy.is_allowed("whitelisted_user_123"));
+    /// assert!(policy.is_allowed("Whitelisted_User_123"));
     /// assert!(!policy.is_allowed("regular_user_456"));
     /// ```
-    #[allow(dead_code)] // Public API for library integrators, not used by CLI
+    ///
+    /// Same as [`LaunchPhasePolicy::is_allowed_at`] with `now = 0`; use that
+    /// when the trade time is known.
     pub fn is_allowed(&self, addr: &str) -> bool {
-        self.allowlist.contains(addr)
+        self.is_allowed_at(addr, 0.0)
+    }
+    /// Whether `addr` pays no surcharge at time `now`, in the clock of the
+    /// entries' `expires_at`: false once its entry has expired, and false for an
+    /// entry with a partial `discount` (see [`LaunchPhasePolicy::effective_tau`]).
+    pub fn is_allowed_at(&self, addr: &str, now: f64) -> bool {
+        self.allowlist.surcharge_share_at(addr, now) == 0.0
+    }
+    /// Distinct addresses on the allowlist, plain or with entries
+    pub fn allowlist_len(&self) -> usize {
+        self.allowlist.len()
     }
-    /// Calculates the surcharge percentage at a given time since launch
+    /// Calculates the surcharge percentage at a given time since launch, following
+    /// [`TauDecay`]
     pub fn tau(&self, seconds_since_launch: f64) -> f64 {
         if seconds_since_launch <= 0.0 {
             return self.tau_start_pct.max(self.tau_end_pct);
         }
+        if let TauDecay::Exponential { half_life_secs } = self.tau_decay {
+            let excess = self.tau_start_pct - self.tau_end_pct;
+            return self.tau_end_pct + excess * (-seconds_since_launch / half_life_secs).exp2();
+        }
         if seconds_since_launch >= self.ramp_secs {
             return self.tau_end_pct;
         }
         let t = seconds_since_launch / self.ramp_secs;
         self.tau_start_pct + t * (self.tau_end_pct - self.tau_start_pct)
     }
+    /// Surcharge percentage `addr` pays at `seconds_since_launch`: τ(t) scaled by
+    /// [`Allowlist::surcharge_share_at`].
+    pub fn effective_tau(&self, addr: &str, seconds_since_launch: f64) -> f64 {
+        self.allowlist
+            .surcharge_share_at(addr, seconds_since_launch)
+            * self.tau(seconds_since_launch)
+    }
+    /// Surcharge percentage gated on cumulative traded volume instead of time:
+    /// ramps from `tau_start_pct` to `tau_end_pct` as volume goes 0 → `target_volume`.
+    pub fn tau_by_volume(&self, cumulative_volume: f64, target_volume: f64) -> f64 {
+        if cumulative_volume <= 0.0 {
+            return self.tau_start_pct.max(self.tau_end_pct);
+        }
+        if cumulative_volume >= target_volume {
+            return self.tau_end_pct;
+        }
+        let v = cumulative_volume / target_volume;
+        self.tau_start_pct + v * (self.tau_end_pct - self.tau_start_pct)
+    }
+}
+
+/// Bloom filter over allowlisted addresses: no false negatives, false positives
+/// at about the rate it was sized for.
+///
+/// Hashing is FNV-1a with double hashing, fixed by this crate rather than the
+/// standard library, so a serialized filter answers the same on any platform.
+#[cfg(feature = "bloom")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BloomAllowlist {
+    bits: Vec<u64>,
+    num_bits: u64,
+    hashes: u32,
+    len: usize,
+}
+#[cfg(feature = "bloom")]
+impl BloomAllowlist {
+    /// Sizes the filter for `addresses` at false-positive rate `fp_rate` in (0, 1):
+    /// m = -n·ln(p)/ln²2 bits and k = (m/n)·ln 2 probes
+    pub fn new<I, S>(addresses: I, fp_rate: f64) -> anyhow::Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        I::IntoIter: ExactSizeIterator,
+        S: AsRef<str>,
+    {
+        let addresses = addresses.into_iter();
+        if !(fp_rate > 0.0 && fp_rate < 1.0) {
+            return Err(anyhow::anyhow!(
+                "bloom fp_rate must be in (0, 1) (got {})",
+                fp_rate
+            ));
+        }
+        let n = addresses.len().max(1) as f64;
+        let ln2 = core::f64::consts::LN_2;
+        let num_bits = ((-n * fp_rate.ln() / (ln2 * ln2)).ceil() as u64).max(64);
+        let hashes = ((num_bits as f64 / n * ln2).round() as u32).max(1);
+        let mut filter = BloomAllowlist {
+            bits: vec![0; num_bits.div_ceil(64) as usize],
+            num_bits,
+            hashes,
+            len: addresses.len(),
+        };
+        for addr in addresses {
+            for bit in filter.probes(addr.as_ref()) {
+                filter.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+            }
+        }
+        Ok(filter)
+    }
+    /// Whether `addr` may be listed: always true for listed addresses
+    pub fn contains(&self, addr: &str) -> bool {
+        self.probes(addr)
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+    /// Addresses the filter was built from
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Whether it was built from no addresses
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Filter size in bytes
+    pub fn size_bytes(&self) -> usize {
+        self.bits.len() * 8
+    }
+    /// Bit positions h1 + i·h2 (mod m) for the k probes of `addr`
+    fn probes(&self, addr: &str) -> impl Iterator<Item = u64> {
+        let h1 = addr.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |h, b| {
+            (h ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+        // SplitMix64 finalizer of h1 as the second, odd hash
+        let mut h2 = h1.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        h2 = (h2 ^ (h2 >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        h2 = (h2 ^ (h2 >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        h2 = (h2 ^ (h2 >> 31)) | 1;
+        let m = self.num_bits;
+        (0..u64::from(self.hashes)).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % m)
+    }
+}
+
+/// Addresses exempt from the launch surcharge, plain or with an [`AllowlistEntry`].
+///
+/// Addresses are stored as their [`AddressMatch::key`], on insertion and when
+/// deserialized, so under the default [`AddressMatch::CaseInsensitive`] an
+/// address matches whatever case either side is written in.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "AllowlistFields", into = "AllowlistFields")]
+pub struct Allowlist {
+    addresses: HashSet<String>,
+    entries: HashMap<String, AllowlistEntry>,
+    address_match: AddressMatch,
+    #[cfg(feature = "bloom")]
+    bloom: Option<BloomAllowlist>,
+}
+#[cfg(feature = "std")]
+impl Allowlist {
+    /// An empty allowlist comparing addresses by `address_match`
+    pub fn new(address_match: AddressMatch) -> Self {
+        Allowlist {
+            address_match,
+            ..Allowlist::default()
+        }
+    }
+    /// A [`AddressMatch::CaseInsensitive`] allowlist whose plain list is a Bloom
+    /// filter over `addresses`, for lists too large to hold exactly (~1.8 MB
+    /// instead of tens of MB for 500k addresses at `fp_rate = 1e-6`). The filter
+    /// is built as `addresses` is iterated, without collecting them.
+    ///
+    /// Listed addresses are always allowed; an unlisted one is wrongly exempted
+    /// from the surcharge with probability about `fp_rate`. That only forgoes a
+    /// little surcharge revenue, which is acceptable for exemption checks but not
+    /// for anything that grants access.
+    #[cfg(feature = "bloom")]
+    pub fn with_bloom<I, S>(addresses: I, fp_rate: f64) -> anyhow::Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        I::IntoIter: ExactSizeIterator,
+        S: AsRef<str>,
+    {
+        let keys = addresses
+            .into_iter()
+            .map(|a| AddressMatch::CaseInsensitive.key(a.as_ref()).into_owned());
+        Ok(Allowlist {
+            bloom: Some(BloomAllowlist::new(keys, fp_rate)?),
+            ..Allowlist::default()
+        })
+    }
+    /// The Bloom filter from [`Allowlist::with_bloom`], if any
+    #[cfg(feature = "bloom")]
+    pub fn bloom(&self) -> Option<&BloomAllowlist> {
+        self.bloom.as_ref()
+    }
+    /// How addresses are compared
+    pub fn address_match(&self) -> AddressMatch {
+        self.address_match
+    }
+    /// Adds `addr` to the plain list; false if it was already there
+    pub fn insert(&mut self, addr: &str) -> bool {
+        self.addresses
+            .insert(self.address_match.key(addr).into_owned())
+    }
+    /// Adds `entry` under its address, returning the entry it replaces. An entry
+    /// past its `expires_at` is no longer exempt (see
+    /// [`LaunchPhasePolicy::is_allowed_at`]).
+    pub fn insert_entry(&mut self, entry: AllowlistEntry) -> Option<AllowlistEntry> {
+        let key = self.address_match.key(&entry.address).into_owned();
+        self.entries.insert(key, entry)
+    }
+    /// The entry for `addr`, if it has one
+    pub fn entry(&self, addr: &str) -> Option<&AllowlistEntry> {
+        self.entries.get(self.address_match.key(addr).as_ref())
+    }
+    /// Share of τ(t) `addr` pays at `now`: 0 when fully exempt, 1 when unlisted
+    /// or its entry has expired, and [`AllowlistEntry::surcharge_share`] for an
+    /// active entry. An address with an entry follows the entry even if it is
+    /// also on the plain list.
+    pub fn surcharge_share_at(&self, addr: &str, now: f64) -> f64 {
+        let key = self.address_match.key(addr);
+        match self.entries.get(key.as_ref()) {
+            Some(entry) if entry.is_active_at(now) => entry.surcharge_share(),
+            Some(_) => 1.0,
+            None if self.is_listed(&key) => 0.0,
+            None => 1.0,
+        }
+    }
+    /// Distinct listed addresses, as their [`AddressMatch::key`]; addresses only
+    /// in the Bloom filter cannot be listed
+    pub fn addresses(&self) -> impl Iterator<Item = &str> {
+        let plain_only = self
+            .addresses
+            .iter()
+            .filter(|a| !self.entries.contains_key(*a));
+        plain_only.chain(self.entries.keys()).map(String::as_str)
+    }
+    /// Distinct addresses on the plain list (or in its Bloom filter) and with
+    /// entries
+    pub fn len(&self) -> usize {
+        #[cfg(feature = "bloom")]
+        let filtered = self.bloom.as_ref().map_or(0, BloomAllowlist::len);
+        #[cfg(not(feature = "bloom"))]
+        let filtered = 0;
+        filtered + self.addresses().count()
+    }
+    /// Whether nothing is listed
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Whether `key` is on the plain list or in its Bloom filter
+    fn is_listed(&self, key: &str) -> bool {
+        #[cfg(feature = "bloom")]
+        if self.bloom.as_ref().is_some_and(|b| b.contains(key)) {
+            return true;
+        }
+        self.addresses.contains(key)
+    }
+}
+#[cfg(feature = "std")]
+impl<S: AsRef<str>> Extend<S> for Allowlist {
+    fn extend<I: IntoIterator<Item = S>>(&mut self, addresses: I) {
+        for addr in addresses {
+            self.insert(addr.as_ref());
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl<S: AsRef<str>> FromIterator<S> for Allowlist {
+    /// A [`AddressMatch::CaseInsensitive`] allowlist of `addresses`
+    fn from_iter<I: IntoIterator<Item = S>>(addresses: I) -> Self {
+        let mut allowlist = Allowlist::default();
+        allowlist.extend(addresses);
+        allowlist
+    }
+}
+
+/// [`Allowlist`] as serialized, before its addresses are re-keyed; sorted so
+/// sidecars list addresses in the same order on every run
+#[cfg(feature = "std")]
+#[derive(Serialize, Deserialize)]
+struct AllowlistFields {
+    #[serde(default)]
+    allowlist: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    entries: BTreeMap<String, AllowlistEntry>,
+    #[serde(default)]
+    address_match: AddressMatch,
+    #[cfg(feature = "bloom")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bloom: Option<BloomAllowlist>,
+}
+#[cfg(feature = "std")]
+impl From<AllowlistFields> for Allowlist {
+    fn from(fields: AllowlistFields) -> Self {
+        let mut allowlist = Allowlist {
+            address_match: fields.address_match,
+            #[cfg(feature = "bloom")]
+            bloom: fields.bloom,
+            ..Allowlist::default()
+        };
+        allowlist.extend(fields.allowlist);
+        for entry in fields.entries.into_values() {
+            allowlist.insert_entry(entry);
+        }
+        allowlist
+    }
+}
+#[cfg(feature = "std")]
+impl From<Allowlist> for AllowlistFields {
+    fn from(allowlist: Allowlist) -> Self {
+        AllowlistFields {
+            allowlist: allowlist.addresses.into_iter().collect(),
+            entries: allowlist.entries.into_iter().collect(),
+            address_match: allowlist.address_match,
+            #[cfg(feature = "bloom")]
+            bloom: allowlist.bloom,
+        }
+    }
+}
+
+/// How allowlisted addresses are compared
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressMatch {
+    /// ASCII case is ignored, so EVM addresses match checksummed or not
+    #[default]
+    CaseInsensitive,
+    /// Byte-for-byte
+    Exact,
+}
+#[cfg(feature = "std")]
+impl AddressMatch {
+    /// The form of `addr` stored in and looked up from the allowlist
+    pub fn key<'a>(&self, addr: &'a str) -> Cow<'a, str> {
+        match self {
+            AddressMatch::CaseInsensitive if addr.bytes().any(|b| b.is_ascii_uppercase()) => {
+                Cow::Owned(addr.to_ascii_lowercase())
+            }
+            _ => Cow::Borrowed(addr),
+        }
+    }
+}
+
+/// Address syntax enforced when loading an allowlist
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AllowlistFormat {
+    /// Any single token without internal whitespace
+    #[default]
+    Any,
+    /// `0x` followed by 40 hex digits
+    Evm,
+    /// [`AllowlistFormat::Evm`] whose mixed-case addresses must carry a valid
+    /// EIP-55 checksum; all-lowercase and all-uppercase ones carry none and pass
+    EvmChecksum,
+}
+#[cfg(feature = "std")]
+impl AllowlistFormat {
+    /// Whether `addr` (already trimmed, non-empty) is well-formed
+    pub fn is_valid(&self, addr: &str) -> bool {
+        let evm_hex = addr
+            .strip_prefix("0x")
+            .filter(|h| h.len() == 40 && h.bytes().all(|b| b.is_ascii_hexdigit()));
+        match self {
+            AllowlistFormat::Any => !addr.contains(char::is_whitespace),
+            AllowlistFormat::Evm => evm_hex.is_some(),
+            AllowlistFormat::EvmChecksum => evm_hex.is_some_and(|h| {
+                let lower = h.bytes().all(|b| !b.is_ascii_uppercase());
+                let upper = h.bytes().all(|b| !b.is_ascii_lowercase());
+                lower || upper || eip55_checksum(addr).as_deref() == Some(addr)
+            }),
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl FromStr for AllowlistFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "any" => Ok(AllowlistFormat::Any),
+            "evm" => Ok(AllowlistFormat::Evm),
+            "evm-checksum" => Ok(AllowlistFormat::EvmChecksum),
+            _ => Err(format!(
+                "unknown allowlist format {:?} (expected any|evm|evm-checksum)",
+                s
+            )),
+        }
+    }
+}
+
+/// EIP-55 checksummed form of a `0x` + 40 hex digit address, in any case; `None`
+/// for anything else.
+///
+/// Each letter is uppercased where the matching nibble of keccak256 over the
+/// lowercase hex digits is ≥ 8.
+#[cfg(feature = "std")]
+pub fn eip55_checksum(addr: &str) -> Option<String> {
+    let hex = addr
+        .strip_prefix("0x")
+        .filter(|h| h.len() == 40 && h.bytes().all(|b| b.is_ascii_hexdigit()))?
+        .to_ascii_lowercase();
+    let hash = Keccak256::digest(hex.as_bytes());
+    let digits = hex.chars().enumerate().map(|(i, ch)| {
+        let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0xf;
+        if nibble >= 8 {
+            ch.to_ascii_uppercase()
+        } else {
+            ch
+        }
+    });
+    Some("0x".chars().chain(digits).collect())
+}
+
+/// An allowlisted address with its metadata, as loaded from a JSON or CSV allowlist
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AllowlistEntry {
+    /// The address as written in the file
+    pub address: String,
+    /// Free-form tier label (e.g. `team`, `seed`)
+    #[serde(default)]
+    pub tier: Option<String>,
+    /// Time from which the address pays the surcharge again, in the clock of
+    /// trade timestamps (seconds since launch for the simulator); `None` never expires
+    #[serde(default)]
+    pub expires_at: Option<f64>,
+    /// Fraction of τ(t) waived, clamped to [0, 1]; `None` waives all of it
+    #[serde(default)]
+    pub discount: Option<f64>,
+}
+#[cfg(feature = "std")]
+impl AllowlistEntry {
+    /// Whether the exemption still holds at `now`
+    pub fn is_active_at(&self, now: f64) -> bool {
+        self.expires_at.is_none_or(|t| now < t)
+    }
+    /// Share of τ(t) this address still pays while active: 1 - `discount`,
+    /// clamped to [0, 1]. A NaN discount waives nothing.
+    pub fn surcharge_share(&self) -> f64 {
+        match self.discount {
+            None => 0.0,
+            Some(d) if d.is_nan() => 1.0,
+            Some(d) => 1.0 - d.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// Counts gathered while loading an allowlist
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AllowlistStats {
+    /// Lines read, including blanks
+    pub total_lines: usize,
+    /// Distinct well-formed addresses inserted
+    pub valid: usize,
+    /// Well-formed addresses seen more than once
+    pub duplicates: usize,
+    /// 1-based line numbers rejected by the format check
+    pub malformed: Vec<usize>,
+}
+
+/// Parses a newline-separated allowlist, skipping blank lines and rejecting
+/// addresses that fail `format` instead of inserting them. Addresses are stored
+/// as their [`AddressMatch::key`], so case variants of one address under
+/// [`AddressMatch::CaseInsensitive`] count as duplicates.
+#[cfg(feature = "std")]
+pub fn load_allowlist(
+    text: &str,
+    format: AllowlistFormat,
+    matching: AddressMatch,
+) -> (HashSet<String>, AllowlistStats) {
+    let mut set = HashSet::new();
+    let mut stats = AllowlistStats::default();
+    for (n, line) in text.lines().enumerate() {
+        stats.total_lines += 1;
+        let addr = line.trim();
+        if addr.is_empty() {
+            continue;
+        }
+        if !format.is_valid(addr) {
+            stats.malformed.push(n + 1);
+        } else if !set.insert(matching.key(addr).into_owned()) {
+            stats.duplicates += 1;
+        }
+    }
+    stats.valid = set.len();
+    (set, stats)
+}
+
+/// An allowlist file parsed by [`parse_allowlist`]
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParsedAllowlist {
+    /// Addresses from a plain newline-separated file
+    pub addresses: HashSet<String>,
+    /// Entries from a JSON or CSV file, keyed by [`AddressMatch::key`]
+    pub entries: HashMap<String, AllowlistEntry>,
+    /// Counts; for CSV, `malformed` holds 1-based line numbers counting the header
+    /// and, for JSON, 1-based positions in the array
+    pub stats: AllowlistStats,
+}
+
+/// Parses an allowlist in whichever format `text` is in: a JSON array of
+/// [`AllowlistEntry`] objects, a CSV whose header names an `address` column
+/// (optionally `tier` and `expires_at`), or else the plain newline-separated
+/// format of [`load_allowlist`].
+///
+/// Addresses failing `format` are skipped and counted as malformed, as for plain
+/// text; a JSON or CSV file that does not parse is an error. A later duplicate
+/// entry replaces the earlier one.
+#[cfg(feature = "std")]
+pub fn parse_allowlist(
+    text: &str,
+    format: AllowlistFormat,
+    matching: AddressMatch,
+) -> anyhow::Result<ParsedAllowlist> {
+    let raw: Vec<(usize, AllowlistEntry)> = if text.trim_start().starts_with('[') {
+        let entries: Vec<AllowlistEntry> = serde_json::from_str(text)?;
+        entries
+            .into_iter()
+            .enumerate()
+            .map(|(n, e)| (n + 1, e))
+            .collect()
+    } else {
+        let header = text.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+        if !header.split(',').any(|col| col.trim() == "address") {
+            let (addresses, stats) = load_allowlist(text, format, matching);
+            return Ok(ParsedAllowlist {
+                addresses,
+                stats,
+                ..ParsedAllowlist::default()
+            });
+        }
+        let mut rdr = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(text.as_bytes());
+        let headers = rdr.headers()?.clone();
+        let mut rec = csv::StringRecord::new();
+        let mut rows = Vec::new();
+        while rdr.read_record(&mut rec)? {
+            let line = rec.position().map_or(0, |p| p.line() as usize);
+            rows.push((line, rec.deserialize(Some(&headers))?));
+        }
+        rows
+    };
+
+    let mut parsed = ParsedAllowlist::default();
+    parsed.stats.total_lines = raw.len();
+    for (n, mut entry) in raw {
+        entry.address = entry.address.trim().to_string();
+        if entry.address.is_empty() || !format.is_valid(&entry.address) {
+            parsed.stats.malformed.push(n);
+            continue;
+        }
+        let key = matching.key(&entry.address).into_owned();
+        if parsed.entries.insert(key, entry).is_some() {
+            parsed.stats.duplicates += 1;
+        }
+    }
+    parsed.stats.valid = parsed.entries.len();
+    Ok(parsed)
 }
@@ -0,0 +1,391 @@
+//! Calibrating curve parameters to observed (supply, price) points
+
+use crate::curves::{Curve, Geometric, Grid, LogisticS, StepCurve};
+use anyhow::{anyhow, Result};
+
+const MAX_ITERS: usize = 500;
+
+/// Outcome of a least-squares logistic fit
+#[derive(Clone, Copy, Debug)]
+pub struct LogisticFit {
+    /// Fitted curve on the requested grid
+    pub curve: LogisticS,
+    /// Root-mean-square price residual over the observations
+    pub rms_residual: f64,
+    /// Levenberg-Marquardt iterations taken
+    pub iterations: usize,
+}
+
+/// Outcome of [`Geometric::best_fit_to`]
+#[derive(Clone, Copy, Debug)]
+pub struct GeometricFit {
+    /// Best geometric approximation on the target's grid
+    pub curve: Geometric,
+    /// Root-mean-square ΔX_i residual over the fitted bins
+    pub rms_residual: f64,
+    /// Largest |ΔX_i(fit) - ΔX_i(target)| over the fitted bins
+    pub max_bin_error: f64,
+}
+
+/// θ interval searched by [`Geometric::best_fit_to`]
+pub const BEST_FIT_THETA_RANGE: (f64, f64) = (-2.0, 2.0);
+
+/// Coarse θ samples before [`Geometric::best_fit_to`] refines the best bracket
+const THETA_SCAN_STEPS: usize = 200;
+
+/// Outcome of [`curve_for_fdv_path`]
+#[derive(Clone, Debug)]
+pub struct FdvPathFit {
+    /// Flat tier per FDV segment on the requested grid
+    pub curve: StepCurve,
+    /// Bins spanned by the tiers; the last is the final point's bin
+    pub bins: i64,
+    /// Largest |P_bin / (fdv / supply) - 1| over the points, from snapping to the grid
+    pub max_snap_error: f64,
+}
+
+/// Back-solves a bin schedule from `(cumulative_supply, target_fdv)` points.
+///
+/// Each point asks for price `fdv / cumulative_supply` once that much supply is
+/// sold; the price is snapped to the nearest bin of `grid`. Segment k, the supply
+/// between points k-1 and k (from 0 for the first), is spread evenly over the bins
+/// after point k-1's bin up to and including point k's (from bin 0 for the first),
+/// so cumulative supply hits every target exactly and only prices are off by
+/// the snapping error. Points must have increasing supply and snap to strictly
+/// increasing bins at or above bin 0.
+pub fn curve_for_fdv_path(grid: Grid, points: &[(f64, f64)]) -> Result<FdvPathFit> {
+    if points.is_empty() {
+        return Err(anyhow!("fdv path: need at least one point"));
+    }
+    let mut tiers = Vec::with_capacity(points.len());
+    let mut max_snap_error = 0.0_f64;
+    let (mut prev_supply, mut prev_bin) = (0.0, -1_i64);
+    for &(supply, fdv) in points {
+        let ok = |v: f64| v.is_finite() && v > 0.0;
+        if !ok(supply) || !ok(fdv) {
+            return Err(anyhow!(
+                "fdv path: need finite supply > 0 and fdv > 0 (got ({}, {}))",
+                supply,
+                fdv
+            ));
+        }
+        if supply <= prev_supply {
+            return Err(anyhow!(
+                "fdv path: cumulative supply must increase (got {} after {})",
+                supply,
+                prev_supply
+            ));
+        }
+        let price = fdv / supply;
+        // Nearest bin in log-price: the first bin at or above `price`, or the one below
+        let above = grid.bins_for_price_range(price);
+        let bin = if above > 0
+            && (price / grid.price_of_bin(above - 1)).ln() < (grid.price_of_bin(above) / price).ln()
+        {
+            above - 1
+        } else {
+            above
+        };
+        if bin <= prev_bin {
+            return Err(anyhow!(
+                "fdv path: price {} at supply {} snaps to bin {}, not above the previous point's bin {}; \
+                 prices must rise by at least one bin step between points",
+                price,
+                supply,
+                bin,
+                prev_bin
+            ));
+        }
+        max_snap_error = max_snap_error.max((grid.price_of_bin(bin) / price - 1.0).abs());
+        let width = (bin - prev_bin) as f64;
+        tiers.push((prev_bin + 1, (supply - prev_supply) / width));
+        (prev_supply, prev_bin) = (supply, bin);
+    }
+    let bins = prev_bin + 1;
+    Ok(FdvPathFit {
+        curve: StepCurve::new(grid, tiers, bins)?,
+        bins,
+        max_snap_error,
+    })
+}
+
+/// P(x) = a + (b - a) / (1 + e^(-kk(x - m))) in normalized coordinates
+fn model(t: &[f64; 4], x: f64) -> f64 {
+    let [a, b, kk, m] = *t;
+    a + (b - a) / (1.0 + (-kk * (x - m)).exp())
+}
+
+/// ∂P/∂(a, b, kk, m)
+fn gradient(t: &[f64; 4], x: f64) -> [f64; 4] {
+    let [a, b, kk, m] = *t;
+    let sig = 1.0 / (1.0 + (-kk * (x - m)).exp());
+    let ds = (b - a) * sig * (1.0 - sig);
+    [1.0 - sig, sig, ds * (x - m), -ds * kk]
+}
+
+fn cost(t: &[f64; 4], pts: &[(f64, f64)]) -> f64 {
+    pts.iter().map(|&(x, y)| (model(t, x) - y).powi(2)).sum()
+}
+
+/// Gaussian elimination with partial pivoting; `None` if singular.
+fn solve4(mut a: [[f64; 4]; 4], mut b: [f64; 4]) -> Option<[f64; 4]> {
+    for col in 0..4 {
+        let piv = (col..4).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[piv][col].abs() < 1e-300 {
+            return None;
+        }
+        a.swap(col, piv);
+        b.swap(col, piv);
+        let pivot = a[col];
+        for row in col + 1..4 {
+            let f = a[row][col] / pivot[col];
+            for (dst, src) in a[row][col..].iter_mut().zip(&pivot[col..]) {
+                *dst -= f * src;
+            }
+            b[row] -= f * b[col];
+        }
+    }
+    let mut x = [0.0; 4];
+    for row in (0..4).rev() {
+        let tail: f64 = (row + 1..4).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - tail) / a[row][row];
+    }
+    x.iter().all(|v| v.is_finite()).then_some(x)
+}
+
+impl LogisticS {
+    /// Fits `p_min`, `p_max`, `k` and `s_mid` to `(supply, price)` observations by
+    /// Levenberg-Marquardt least squares, returning a curve on `grid` with `bins` bins.
+    ///
+    /// Needs at least 4 finite points with non-constant prices. Supply and price are
+    /// normalized internally so that `k ~ 1e-8` style scales stay well-conditioned.
+    pub fn fit(points: &[(f64, f64)], grid: Grid, bins: i64) -> Result<LogisticFit> {
+        if points.len() < 4 {
+            return Err(anyhow!("fit: need at least 4 points, got {}", points.len()));
+        }
+        if let Some((s, p)) = points
+            .iter()
+            .find(|(s, p)| !s.is_finite() || !p.is_finite())
+        {
+            return Err(anyhow!("fit: non-finite point ({}, {})", s, p));
+        }
+        let sx = points.iter().map(|p| p.0.abs()).fold(0.0, f64::max);
+        let sx = if sx > 0.0 { sx } else { 1.0 };
+        let py = points.iter().map(|p| p.1.abs()).fold(0.0, f64::max);
+        let pts: Vec<(f64, f64)> = points.iter().map(|&(s, p)| (s / sx, p / py)).collect();
+
+        let y_lo = pts.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+        let y_hi = pts.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+        let x_lo = pts.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+        let x_hi = pts.iter().map(|p| p.0).fold(f64::NEG_INFINITY, f64::max);
+        let span = y_hi - y_lo;
+        if span.is_nan() || span <= 0.0 || x_hi <= x_lo {
+            return Err(anyhow!("fit: supply and price must both vary"));
+        }
+        let y_mid = 0.5 * (y_lo + y_hi);
+        let m0 = pts
+            .iter()
+            .min_by(|a, b| (a.1 - y_mid).abs().total_cmp(&(b.1 - y_mid).abs()))
+            .map(|p| p.0)
+            .unwrap_or(0.5 * (x_lo + x_hi));
+        let mut t = [
+            y_lo - 0.1 * span,
+            y_hi + 0.1 * span,
+            8.0 / (x_hi - x_lo),
+            m0,
+        ];
+
+        let mut c = cost(&t, &pts);
+        let mut lambda = 1e-3;
+        let mut iterations = 0;
+        while iterations < MAX_ITERS && lambda < 1e12 {
+            iterations += 1;
+            let mut jtj = [[0.0; 4]; 4];
+            let mut jtr = [0.0; 4];
+            for &(x, y) in &pts {
+                let g = gradient(&t, x);
+                let r = model(&t, x) - y;
+                for i in 0..4 {
+                    jtr[i] -= g[i] * r;
+                    for j in 0..4 {
+                        jtj[i][j] += g[i] * g[j];
+                    }
+                }
+            }
+            let mut damped = jtj;
+            for (i, row) in damped.iter_mut().enumerate() {
+                row[i] += lambda * jtj[i][i].max(1e-12);
+            }
+            let Some(delta) = solve4(damped, jtr) else {
+                lambda *= 10.0;
+                continue;
+            };
+            let cand = [
+                t[0] + delta[0],
+                t[1] + delta[1],
+                t[2] + delta[2],
+                t[3] + delta[3],
+            ];
+            let c_new = cost(&cand, &pts);
+            if c_new.is_finite() && c_new < c {
+                let improvement = (c - c_new) / c.max(1e-300);
+                t = cand;
+                c = c_new;
+                lambda = (lambda / 10.0).max(1e-12);
+                if improvement < 1e-14 {
+                    break;
+                }
+            } else {
+                lambda *= 10.0;
+            }
+        }
+
+        // kk < 0 describes the same curve with a and b swapped
+        if t[2] < 0.0 {
+            t = [t[1], t[0], -t[2], t[3]];
+        }
+        let curve = LogisticS {
+            grid,
+            p_min: t[0] * py,
+            p_max: t[1] * py,
+            k: t[2] / sx,
+            s_mid: t[3] * sx,
+            bins,
+        };
+        if curve.p_min >= curve.p_max || !curve.k.is_finite() || curve.k <= 0.0 {
+            return Err(anyhow!(
+                "fit: did not converge to an increasing logistic (p_min={}, p_max={}, k={})",
+                curve.p_min,
+                curve.p_max,
+                curve.k
+            ));
+        }
+        Ok(LogisticFit {
+            curve,
+            rms_residual: (c / pts.len() as f64).sqrt() * py,
+            iterations,
+        })
+    }
+}
+
+/// Optimal ΔX_0 for `theta` and the squared error it leaves against `target`:
+/// with u_i = r^i, ΔX_0 = Σ y_i·u_i / Σ u_i²
+fn geometric_profile(grid: Grid, theta: f64, target: &[f64]) -> (f64, f64) {
+    let unit = Geometric {
+        grid,
+        theta,
+        r0_quote: grid.p0,
+    };
+    let (mut yu, mut uu) = (0.0, 0.0);
+    for (i, &y) in target.iter().enumerate() {
+        let u = unit.delta_x_of_bin(i as i64);
+        yu += y * u;
+        uu += u * u;
+    }
+    let dx0 = if uu > 0.0 { (yu / uu).max(0.0) } else { 0.0 };
+    let sse: f64 = target
+        .iter()
+        .enumerate()
+        .map(|(i, &y)| {
+            let d = dx0 * unit.delta_x_of_bin(i as i64) - y;
+            d * d
+        })
+        .sum();
+    if dx0.is_finite() && sse.is_finite() {
+        (dx0, sse)
+    } else {
+        (0.0, f64::INFINITY)
+    }
+}
+
+impl Geometric {
+    /// Least-squares geometric approximation of `target` over bins `[0, bins)`:
+    /// θ and R_0 minimizing Σ (ΔX_i(fit) - ΔX_i(target))².
+    ///
+    /// The grid keeps the target's P_0 and its mean step (P_{bins-1}/P_0)^(1/(bins-1)),
+    /// which is the target's own step on a `p0·q^i` grid. For each θ the best R_0 is
+    /// closed-form, so only θ is searched: a scan of [`BEST_FIT_THETA_RANGE`], then
+    /// golden-section refinement around the best sample.
+    pub fn best_fit_to<C: Curve>(target: &C, bins: i64) -> Result<GeometricFit> {
+        if bins < 2 {
+            return Err(anyhow!("best_fit_to: need ≥ 2 bins (got {})", bins));
+        }
+        let (p0, p_last) = (target.price_of_bin(0), target.price_of_bin(bins - 1));
+        if !(p0 > 0.0 && p_last > p0 && p_last.is_finite()) {
+            return Err(anyhow!(
+                "best_fit_to: need increasing positive prices (P_0={}, P_{}={})",
+                p0,
+                bins - 1,
+                p_last
+            ));
+        }
+        let q = (p_last / p0).powf(1.0 / (bins - 1) as f64);
+        let grid = Grid::new(p0, (q - 1.0) * 10_000.0);
+        let y: Vec<f64> = (0..bins).map(|i| target.delta_x_of_bin(i)).collect();
+        if y.iter().any(|v| !v.is_finite()) || y.iter().all(|&v| v == 0.0) {
+            return Err(anyhow!(
+                "best_fit_to: target allocations must be finite and not all zero"
+            ));
+        }
+
+        let (lo, hi) = BEST_FIT_THETA_RANGE;
+        let step = (hi - lo) / THETA_SCAN_STEPS as f64;
+        let best = (0..=THETA_SCAN_STEPS)
+            .map(|k| (k, geometric_profile(grid, lo + k as f64 * step, &y).1))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(0, |(k, _)| k);
+        // Golden-section search on the bracket around the best sample
+        let (mut a, mut b) = (
+            (lo + (best as f64 - 1.0) * step).max(lo),
+            (lo + (best as f64 + 1.0) * step).min(hi),
+        );
+        let inv_phi = (5.0_f64.sqrt() - 1.0) / 2.0;
+        let mut c = b - inv_phi * (b - a);
+        let mut d = a + inv_phi * (b - a);
+        let (mut fc, mut fd) = (
+            geometric_profile(grid, c, &y).1,
+            geometric_profile(grid, d, &y).1,
+        );
+        for _ in 0..MAX_ITERS {
+            if b - a <= 1e-12 * (1.0 + a.abs()) {
+                break;
+            }
+            if fc <= fd {
+                b = d;
+                d = c;
+                fd = fc;
+                c = b - inv_phi * (b - a);
+                fc = geometric_profile(grid, c, &y).1;
+            } else {
+                a = c;
+                c = d;
+                fc = fd;
+                d = a + inv_phi * (b - a);
+                fd = geometric_profile(grid, d, &y).1;
+            }
+        }
+        let theta = 0.5 * (a + b);
+        let (dx0, sse) = geometric_profile(grid, theta, &y);
+        if !sse.is_finite() {
+            return Err(anyhow!(
+                "best_fit_to: no finite geometric fit over {} bins",
+                bins
+            ));
+        }
+        let curve = Geometric {
+            grid,
+            theta,
+            r0_quote: dx0 * p0,
+        };
+        let max_bin_error = y
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| (curve.delta_x_of_bin(i as i64) - v).abs())
+            .fold(0.0, f64::max);
+        Ok(GeometricFit {
+            curve,
+            rms_residual: (sse / bins as f64).sqrt(),
+            max_bin_error,
+        })
+    }
+}
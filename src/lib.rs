@@ -1,3 +1,4 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![forbid(unsafe_code)]
 #![warn(rust_2018_idioms)]
 #![warn(missing_docs)]
@@ -10,8 +11,25 @@
 //! # Modules
 //! - [`curves`][]: Price lattice & allocation mechanisms
 //! - [`dlmm`][]: Fee schedule and launch-phase surcharge
+//! - [`fit`][]: Calibrating curve parameters to observed prices
 //! - [`verifier`][]: Analytic vs numeric checks
 //! - [`plot`][]: Visualization (optional in binaries)
+//! - [`repl`][]: Interactive price/bin queries
+//! - [`sim`][]: Trade-sequence launch simulation
+//! - [`montecarlo`][]: Seeded random-flow launch statistics
+//! - [`amm_compare`][]: Allocation versus other AMM liquidity shapes
+//! - [`solidity`][]: Schedule constants for EVM contracts
+//! - [`diff`][]: Per-bin comparison of two schedules
+//!
+//! # `no_std`
+//! With `default-features = false` only [`curves`][] and the fee math in
+//! [`dlmm`][] are built, against `core` + `alloc` with float functions from
+//! `libm`. Everything else needs the default `std` feature.
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+mod math;
 
 /// Price lattice and allocation mechanisms for bonding curves
 pub mod curves;
@@ -19,8 +37,38 @@ pub mod curves;
 /// DLMM fee schedule and launch-phase surcharge policies
 pub mod dlmm;
 
+/// Least-squares calibration of curve parameters to observations
+#[cfg(feature = "std")]
+pub mod fit;
+
 /// Verification tools for curve properties and numerical accuracy
+#[cfg(feature = "std")]
 pub mod verifier;
 
 /// Visualization utilities for generating charts
+#[cfg(feature = "std")]
 pub mod plot;
+
+/// Interactive stdin queries against a built curve
+#[cfg(feature = "std")]
+pub mod repl;
+
+/// Simulating launch trades against a curve
+#[cfg(feature = "std")]
+pub mod sim;
+
+/// Seeded Monte Carlo launch simulation
+#[cfg(feature = "std")]
+pub mod montecarlo;
+
+/// Comparing the DLMM allocation with other AMMs
+#[cfg(feature = "std")]
+pub mod amm_compare;
+
+/// Solidity constant generation for on-chain schedules
+#[cfg(feature = "std")]
+pub mod solidity;
+
+/// Comparing a schedule against an earlier one
+#[cfg(feature = "std")]
+pub mod diff;
@@ -1,20 +1,66 @@
-mod curves;
-mod dlmm;
-mod plot;
-mod verifier;
-
-use crate::curves::{Curve, Geometric, Grid, LogisticS};
-use crate::dlmm::{DlmmFeeParams, LaunchPhasePolicy};
-use crate::plot::{plot_fee_vs_vol, plot_price_vs_supply, plot_tokens_per_bin};
-use crate::verifier::verify_geometric;
-
-use anyhow::{anyhow, Result};
-use clap::Parser;
+use bcurve::amm_compare::{uniswapv3_allocation, uniswapv3_liquidity_for_quote, AmmKind};
+use bcurve::curves::{
+    AdaptiveLogistic, CompensatedSum, Curve, CurveClone, CurveKind, Exponential, Geometric, Grid,
+    GridSpacing, Linear, LogisticS, MaskedCurve, StepCurve, TwoSided,
+};
+use bcurve::diff::{diff_schedules, read_schedule_values, schedule_values, BinValues};
+use bcurve::dlmm::{
+    depth_chart_sampled, estimate_bin_apr, fee_revenue_tradeoff, parse_allowlist, rate_to_bps,
+    split_fee, AddressMatch, Allowlist, AllowlistFormat, DlmmFeeParams, FeeModel,
+    LaunchPhasePolicy, TauDecay,
+};
+use bcurve::fit::curve_for_fdv_path;
+use bcurve::montecarlo::{simulate, MonteCarloConfig};
+use bcurve::plot::{
+    plot_depth_chart, plot_fee_components, plot_price_vs_supply_multi, plot_price_vs_supply_with,
+    plot_tokens_per_bin, ChartFormat, PriceChartOptions, MAX_PLOT_BINS, SERIES_COLORS,
+};
+use bcurve::repl::run_repl;
+use bcurve::sim::simulate_trades;
+use bcurve::solidity::{write_solidity, SolidityOptions, CONTRACT_SIZE_LIMIT, DEFAULT_CHUNK_BINS};
+use bcurve::verifier::{
+    accuracy_sweep, allocation_stats, binned_integration_error, check_allocation_shape,
+    check_monotone, check_revenue_monotone, count_dust_bins, min_price_gap, reconcile_schedule,
+    supply_integration_error, verify_geometric, verify_linear, verify_logistic, AllocationShape,
+    PriceGap, Report, ACCURACY_SWEEP_STEPS_BPS, DEFAULT_MAX_ALLOCATION_RATIO,
+    DEFAULT_MAX_INTEGRATION_ERROR,
+};
+
+use anyhow::{anyhow, Context, Result};
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fmt;
 use std::fs::{create_dir_all, File};
-use std::io::Write;
+use std::io::{BufRead, BufWriter, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::panic::AssertUnwindSafe;
 use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// `println!` for human-readable output: stdout normally, stderr when stdout
+/// carries data (`--output-stdout`, `--print-config`) so the two never mix
+macro_rules! info {
+    ($args:expr, $($fmt:tt)*) => {
+        if $args.stdout_is_data() {
+            eprintln!($($fmt)*)
+        } else {
+            println!($($fmt)*)
+        }
+    };
+}
+
+/// One `[[tiers]]` table of a step-mode config: ΔX per bin from `start_bin` on.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Tier {
+    start_bin: i64,
+    delta_x: f64,
+}
 
 #[derive(Parser, Debug)]
 #[command(
@@ -23,12 +69,40 @@ use std::path::Path;
     about = "DLMM bonding curve simulator + verifier"
 )]
 struct Args {
+    /// Curve family (see --list-modes)
     #[arg(long, default_value = "geometric")]
-    mode: String,
+    mode: CurveKind,
+    /// Print every --mode with the parameters it requires, then exit
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    list_modes: bool,
     #[arg(long, default_value_t = 0.01)]
     p0: f64,
+    /// Derive p0 so that bin --anchor-bin sits at this price (replaces --p0)
+    #[arg(long, requires = "anchor_bin", conflicts_with = "p0")]
+    anchor_price: Option<f64>,
+    /// Bin index that --anchor-price pins
+    #[arg(long, requires = "anchor_price")]
+    anchor_bin: Option<i64>,
     #[arg(long, default_value_t = 10.0)]
     bin_step_bps: f64,
+    /// Step `price` (default) or `sqrt-price` by --bin-step-bps, the latter like
+    /// Uniswap v3 ticks (prices then grow by (1 + step)² per bin)
+    #[arg(
+        long,
+        default_value = "price",
+        conflicts_with_all = ["bins_per_decade", "step_from_endpoints"]
+    )]
+    grid_spacing: GridSpacing,
+    /// Set resolution as bins per 10× price move instead of --bin-step-bps
+    #[arg(long, conflicts_with = "bin_step_bps")]
+    bins_per_decade: Option<f64>,
+    /// Geometric: derive the bin step from --p0, --end-price and --bins so bin `bins` lands on --end-price
+    #[arg(
+        long,
+        requires_all = ["bins", "end_price", "target_supply"],
+        conflicts_with_all = ["bin_step_bps", "bins_per_decade", "anchor_price"]
+    )]
+    step_from_endpoints: bool,
 
     /// θ (prefer 0<θ<1). θ>1 makes ΔX grow with i.
     #[arg(long, default_value_t = 0.6)]
@@ -40,8 +114,20 @@ struct Args {
     bins: Option<i64>,
     #[arg(long)]
     end_price: Option<f64>,
+    /// Logistic: size bins so the last bin's price is within e·(p_max - p_min) of p_max
+    #[arg(long, conflicts_with_all = ["bins", "end_price", "fit_csv"])]
+    until_pmax_eps: Option<f64>,
     #[arg(long)]
     r0: Option<f64>,
+    /// Refuse schedules with more bins than this (guards against typos in --bins)
+    #[arg(long, default_value_t = 10_000_000)]
+    max_bins: i64,
+    /// Geometric: mirror the schedule over this many bins below P_0 (two-sided position)
+    #[arg(long, default_value_t = 0)]
+    below_bins: i64,
+    /// Zero the allocation of bins in `start..end` (e.g. 100..150) while keeping prices
+    #[arg(long, value_parser = parse_bin_range)]
+    exclude_bins: Option<Range<i64>>,
 
     #[arg(long, default_value_t = 0.0)]
     p_min: f64,
@@ -51,6 +137,29 @@ struct Args {
     k: f64,
     #[arg(long, default_value_t = 0.0)]
     s_mid: f64,
+    /// Fit p_min/p_max/k/s_mid to `supply,price` observations in this CSV (logistic)
+    #[arg(long)]
+    fit_csv: Option<String>,
+    /// Exponential: allocation of bin 0 (instead of --target-supply)
+    #[arg(long)]
+    exp_a: Option<f64>,
+    /// Exponential: growth rate per bin, ΔX_i = a·e^(b·i) (negative = decaying)
+    #[arg(long, allow_negative_numbers = true)]
+    exp_b: Option<f64>,
+    /// Linear: allocation of bin 0, ΔX_i = base + slope·i
+    #[arg(long)]
+    base: Option<f64>,
+    /// Linear: change in allocation per bin (default 0)
+    #[arg(long, allow_negative_numbers = true)]
+    slope: Option<f64>,
+    /// Step mode: flat allocation tiers, set by the `[[tiers]]` tables of the
+    /// `--config` file
+    #[arg(skip)]
+    tiers: Option<Vec<Tier>>,
+    /// Step mode: derive the tiers from a headered `supply,fdv` CSV of target FDV
+    /// (price = fdv / supply) at each cumulative supply; sets the bin count
+    #[arg(long, conflicts_with_all = ["bins", "end_price", "until_pmax_eps"])]
+    fdv_csv: Option<String>,
 
     #[arg(long, default_value_t = 0.0)]
     base_factor: f64,
@@ -60,6 +169,19 @@ struct Args {
     vol_accum: f64,
     #[arg(long, default_value_t = 0.10)]
     max_fee_rate: f64, // decimal default 10%
+    /// Fee floor charged even at zero volatility (decimal, ≤ --max-fee-rate)
+    #[arg(long, default_value_t = 0.0)]
+    min_fee_rate: f64,
+    /// Ramp into the fee cap over this fraction of it instead of clipping (0 = hard cap)
+    #[arg(long, default_value_t = 0.0)]
+    cap_softness: f64,
+    /// Grow the base fee by this fraction per bin away from the active bin (0 = flat)
+    #[arg(long, default_value_t = 0.0)]
+    distance_fee: f64,
+    /// Send this fraction in [0, 1] of the capped fee to the protocol and add
+    /// `fee_protocol`/`fee_lp` columns splitting `fee_total`
+    #[arg(long)]
+    protocol_share: Option<f64>,
 
     // Launch-phase policy
     #[arg(long, default_value_t = 50.0)]
@@ -68,20 +190,416 @@ struct Args {
     tau_end_pct: f64,
     #[arg(long, default_value_t = 30.0)]
     tau_ramp_secs: f64,
-    /// Path to a newline-separated allowlist; addresses here are exempt from τ(t)
+    /// Surcharge decay: `linear` over --tau-ramp-secs, or `exponential:<half_life_secs>`
+    /// toward --tau-end-pct
+    #[arg(long, default_value = "linear")]
+    tau_decay: TauDecay,
+    /// Path to an allowlist whose addresses are exempt from τ(t): newline-separated,
+    /// a CSV with an `address,tier,expires_at,discount` header, or a JSON array of such objects
     #[arg(long, alias = "whitelist-path")]
     allowlist_path: Option<String>,
+    /// Address syntax to enforce on allowlist lines: `any`, `evm` (0x + 40 hex) or
+    /// `evm-checksum` (evm, rejecting mixed-case addresses with a bad EIP-55 checksum)
+    #[arg(long, default_value = "any")]
+    allowlist_format: AllowlistFormat,
+    /// Match allowlisted addresses byte-for-byte instead of ignoring case
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    allowlist_exact: bool,
+    /// Add `effective_ask` and `fee_total_with_surcharge` columns with the surcharge
+    /// τ(t) applied at this many seconds after launch (bare flag = 0s, the worst case)
+    #[arg(long, num_args = 0..=1, default_missing_value = "0")]
+    surcharge_at_secs: Option<f64>,
+    /// Price the --surcharge-at-secs columns for this trader, honoring their
+    /// allowlist exemption or discount (default: a non-allowlisted trader)
+    #[arg(long, requires = "surcharge_at_secs")]
+    surcharge_address: Option<String>,
+    /// Add `surcharge_revenue_bin`/`surcharge_revenue_cum` columns: revenue_bin·τ(0)/100,
+    /// assuming every buy is non-allowlisted and lands at launch
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    account_surcharge: bool,
 
     /// Optional: if provided, include price-guard metadata using this impact (bps)
     #[arg(long)]
     price_guard_bps: Option<f64>,
 
+    /// Write fee_tradeoff.csv: projected fee revenue across a fee multiplier sweep
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    fee_tradeoff: bool,
+    /// Volume elasticity to the fee rate (volume = base_volume·(1 - fee·elasticity))
+    #[arg(long, default_value_t = 10.0)]
+    elasticity: f64,
+    /// Quote volume at zero fee for --fee-tradeoff
+    #[arg(long, default_value_t = 1_000_000.0)]
+    base_volume: f64,
+    /// Logistic: write accuracy.csv, discretization error across bin steps over the same price range
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    accuracy_sweep: bool,
+    /// Logistic (experimental): spread the same price range over N non-uniform bins,
+    /// finest around the inflection, instead of the p0·q^i grid
+    #[arg(long)]
+    adaptive_bins: Option<i64>,
+    /// Logistic: write the least-squares geometric approximation of the logistic's
+    /// allocations instead, reporting the RMS and max per-bin ΔX error
+    #[arg(long, action = clap::ArgAction::SetTrue, conflicts_with = "adaptive_bins")]
+    geometric_from_logistic: bool,
+    /// Skip the curve and write only fees.csv (va, fee_base, fee_var, fee_total) and the fee chart
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    fee_only: bool,
+    /// Upper end of the volatility-accumulator sweep for --fee-only
+    #[arg(long, default_value_t = 50.0)]
+    va_max: f64,
+    /// Number of va steps for --fee-only
+    #[arg(long, default_value_t = 500)]
+    va_steps: u32,
+
     #[arg(long, default_value = "out")]
     out_dir: String,
+    /// Write the schedule (as --format) to stdout instead of --out-dir, skip the charts
+    /// and send all other console output to stderr, for piping into other tools
+    #[arg(
+        long,
+        action = clap::ArgAction::SetTrue,
+        conflicts_with_all = ["split_output", "resume", "verify_output", "round_trip_test", "repl", "batch"]
+    )]
+    output_stdout: bool,
+    /// Schedule output: `csv` (schedule.csv), `json` (schedule.rows.json with `metadata`
+    /// and `rows`), `both`, `solidity` (Schedule.sol constant arrays) or `parquet`
+    /// (schedule.parquet; needs the `parquet` feature)
+    #[arg(long, default_value = "csv")]
+    format: OutputFormat,
+    /// Shard the csv schedule into schedule_0.csv, schedule_1.csv, … of at most this many
+    /// rows each, cumulative columns continuing across files, listed in schedule_index.json
+    #[arg(long)]
+    split_output: Option<i64>,
+    /// Fixed-point decimals for --format solidity: values are written as round(x·10^decimals)
+    #[arg(long, default_value_t = 18)]
+    decimals: u32,
+    /// Bins per generated library for --format solidity, to stay under the contract size limit
+    #[arg(long, default_value_t = DEFAULT_CHUNK_BINS)]
+    solidity_chunk_bins: i64,
     #[arg(long = "no-draw", action = clap::ArgAction::SetFalse, default_value_t = true)]
     draw: bool,
+    /// Image format of the charts: `png` or `svg` (scalable, for embedding in reports)
+    #[arg(long, default_value = "png")]
+    chart_format: ChartFormat,
     #[arg(long, action = clap::ArgAction::SetTrue)]
     verbose: bool,
+    /// Exit nonzero, listing every warning, if any warning fired during the run
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    fail_on_warning: bool,
+    /// Geometric: warn when ΔX_0/ΔX_{bins-1} exceeds this (allocation concentrated in the head bins)
+    #[arg(long, default_value_t = DEFAULT_MAX_ALLOCATION_RATIO)]
+    max_allocation_ratio: f64,
+    /// Logistic: warn when Σ P·ΔX misses the exact ∫ P dS by more than this relative error (bins too coarse)
+    #[arg(long, default_value_t = DEFAULT_MAX_INTEGRATION_ERROR)]
+    max_integration_error: f64,
+    /// Count bins whose ΔX falls below this many tokens (dead on-chain liquidity)
+    #[arg(long)]
+    dust_threshold: Option<f64>,
+    /// With --dust-threshold, fail when more than this percentage of bins are dust
+    #[arg(long, requires = "dust_threshold")]
+    max_dust_pct: Option<f64>,
+    /// Add a `price_inv = 1/price` column and plot 1/P (token-per-quote orientation)
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    invert_prices: bool,
+    /// Add `log_price` and `log_return_from_prev` columns (ln P_i, ln(P_i/P_{i-1}))
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    log_columns: bool,
+    /// Add a `bin_id = base + i` column using the on-chain id scheme (e.g. 8388608 = 2^23)
+    #[arg(long)]
+    bin_id_base: Option<i64>,
+    /// Signed integer width the exported bin ids must fit in
+    #[arg(long, default_value_t = 32, requires = "bin_id_base")]
+    bin_id_bits: u32,
+    /// Plot price against supply as a fraction of the total (x-axis 0..1)
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    normalize_supply: bool,
+    /// Draw price_vs_supply on a log-scale price axis (needs every price > 0)
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    log_price: bool,
+    /// Most bins drawn per chart line; longer schedules are decimated, keeping the first and last bin
+    #[arg(long, default_value_t = MAX_PLOT_BINS)]
+    max_plot_points: usize,
+    /// Report schedule-generation progress on stderr
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    progress: bool,
+    /// Print the first and last N schedule rows as an aligned table on stdout
+    #[arg(long)]
+    preview: Option<usize>,
+    /// After writing outputs, answer `price`/`bin-of`/`supply`/`buy` queries from stdin
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    repl: bool,
+    /// Replay `timestamp,address,quote_in` buys from this CSV and write simulation.csv
+    #[arg(long)]
+    trades: Option<String>,
+    /// Simulate random launches over the surcharge ramp and write fee/surcharge
+    /// revenue, supply sold and final bin percentiles to montecarlo.csv
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    montecarlo: bool,
+    /// RNG seed for --montecarlo; a seed always reproduces the same montecarlo.csv
+    #[arg(long, default_value_t = 0, requires = "montecarlo")]
+    seed: u64,
+    /// Launches simulated by --montecarlo
+    #[arg(long, default_value_t = 1000, requires = "montecarlo")]
+    mc_runs: usize,
+    /// Mean buys per second for --montecarlo (Poisson arrivals)
+    #[arg(long, default_value_t = 1.0, requires = "montecarlo")]
+    mc_arrival_rate: f64,
+    /// Median gross quote per --montecarlo buy (log-normal sizes)
+    #[arg(long, default_value_t = 100.0, requires = "montecarlo")]
+    mc_median_quote: f64,
+    /// σ of ln(quote) for --montecarlo buy sizes
+    #[arg(long, default_value_t = 1.0, requires = "montecarlo")]
+    mc_size_sigma: f64,
+    /// Fraction of --montecarlo buys from an allowlisted (surcharge-exempt) address
+    #[arg(long, default_value_t = 0.0, requires = "montecarlo")]
+    mc_allowlisted_share: f64,
+    /// Write apr.csv: estimated LP APR per bin given --daily-volume per bin at --vol-accum
+    #[arg(long, action = clap::ArgAction::SetTrue, requires = "daily_volume")]
+    apr: bool,
+    /// Assumed daily quote volume through each bin, for --apr
+    #[arg(long)]
+    daily_volume: Option<f64>,
+    /// Also draw depth_chart.png (or .svg): cumulative bid/ask liquidity around the launch bin 0
+    #[arg(long, action = clap::ArgAction::SetTrue, conflicts_with = "draw")]
+    depth_chart: bool,
+    /// Write the verifier report to verify.json
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    verify_json: bool,
+    /// Re-read schedule.csv and check its final revenue_cum against the curve
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    verify_output: bool,
+    /// Compare the new schedule bin by bin with this earlier schedule.csv: print a
+    /// summary and the first changed bins, and write the full diff to diff.csv
+    #[arg(long, conflicts_with_all = ["fee_only", "print_config"])]
+    diff: Option<String>,
+    /// Geometric: write amm_compare.csv, the per-bin allocation next to `uniswap-v3` holding
+    /// the same tokens over the same price range
+    #[arg(long)]
+    compare_amm: Option<AmmKind>,
+    /// Geometric: draw compare_theta.png (or .svg) overlaying price vs supply for each
+    /// of these θ (comma-separated) on the run's grid and bins, at the run's total
+    /// supply unless --r0 fixes R₀
+    #[arg(long, value_delimiter = ',', num_args = 1..)]
+    compare_theta: Vec<f64>,
+    /// Continue a partial schedule.csv from its last complete row instead of starting over
+    /// (same arguments as the interrupted run; starts fresh if the file is missing)
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    resume: bool,
+    /// Write schedule.json with every parameter needed to regenerate schedule.csv
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    sidecar: bool,
+    /// Write schedule.json, regenerate the schedule from it and fail unless every cell matches schedule.csv
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    round_trip_test: bool,
+    /// Print the resolved run (solved R₀, derived s_mid, final bin count, fees, launch
+    /// policy) as a scenario JSON on stdout instead of writing the schedule and charts
+    #[arg(long, action = clap::ArgAction::SetTrue, conflicts_with_all = ["output_stdout", "replay"])]
+    print_config: bool,
+    /// Rewrite the schedule from a --print-config scenario; the curve, bin, fee,
+    /// launch-policy and schedule-column flags are ignored
+    #[arg(long)]
+    replay: Option<String>,
+    /// Run each non-empty, non-`#` line of this file as its own arguments into
+    /// <out-dir>/run_<line>/, then write <out-dir>/batch_summary.csv; other flags are ignored
+    #[arg(long)]
+    batch: Option<String>,
+    /// Number of --batch configs run at once, each on its own worker thread
+    #[arg(long, default_value_t = 1, requires = "batch")]
+    concurrency: usize,
+    /// Read options from this TOML file, keyed by flag name in snake_case
+    /// (`bin_step_bps = 25`); flags given on the command line override it
+    #[arg(long)]
+    config: Option<String>,
+}
+
+impl Args {
+    /// Whether stdout carries data rather than human-readable output
+    fn stdout_is_data(&self) -> bool {
+        self.output_stdout || self.print_config
+    }
+}
+
+/// File the schedule is written to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    Csv,
+    Json,
+    /// schedule.csv and schedule.rows.json
+    Both,
+    Solidity,
+    /// schedule.parquet (`parquet` feature)
+    Parquet,
+}
+impl OutputFormat {
+    fn writes_csv(self) -> bool {
+        matches!(self, OutputFormat::Csv | OutputFormat::Both)
+    }
+}
+impl FromStr for OutputFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(OutputFormat::Csv),
+            "json" => Ok(OutputFormat::Json),
+            "both" => Ok(OutputFormat::Both),
+            "solidity" => Ok(OutputFormat::Solidity),
+            "parquet" => Ok(OutputFormat::Parquet),
+            _ => Err(format!(
+                "unknown format {:?} (expected csv|json|both|solidity|parquet)",
+                s
+            )),
+        }
+    }
+}
+
+/// Curve parameters as recorded in the schedule.json sidecar
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum CurveConfig {
+    Geometric(Geometric),
+    TwoSided(TwoSided),
+    Logistic(LogisticS),
+    AdaptiveLogistic(AdaptiveLogistic),
+    Step(StepCurve),
+    Exponential(Exponential),
+    Linear(Linear),
+}
+impl CurveConfig {
+    fn boxed(&self) -> Box<dyn CurveClone> {
+        match self {
+            CurveConfig::Geometric(c) => Box::new(*c),
+            CurveConfig::TwoSided(c) => Box::new(*c),
+            CurveConfig::Logistic(c) => Box::new(*c),
+            CurveConfig::AdaptiveLogistic(c) => Box::new(c.clone()),
+            CurveConfig::Step(c) => Box::new(c.clone()),
+            CurveConfig::Exponential(c) => Box::new(*c),
+            CurveConfig::Linear(c) => Box::new(*c),
+        }
+    }
+}
+
+/// Options that shape schedule.csv beyond the curve, fees and launch policy
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct ScheduleFormat {
+    /// Run description written as `# ` lines above the header
+    header_lines: Vec<String>,
+    vol_accum: f64,
+    surcharge_at_secs: Option<f64>,
+    #[serde(default)]
+    surcharge_address: Option<String>,
+    account_surcharge: bool,
+    price_guard_bps: Option<f64>,
+    invert_prices: bool,
+    log_columns: bool,
+    bin_id_base: Option<i64>,
+    #[serde(default)]
+    protocol_fee_columns: bool,
+    /// Write guards as `Guard @ bin N`, the format geometric schedules have always used
+    #[serde(default)]
+    guard_by_bin: bool,
+}
+impl ScheduleFormat {
+    fn new(args: &Args, header_lines: Vec<String>) -> Self {
+        ScheduleFormat {
+            header_lines,
+            vol_accum: args.vol_accum,
+            surcharge_at_secs: args.surcharge_at_secs,
+            surcharge_address: args.surcharge_address.clone(),
+            account_surcharge: args.account_surcharge,
+            price_guard_bps: args.price_guard_bps,
+            invert_prices: args.invert_prices,
+            log_columns: args.log_columns,
+            bin_id_base: args.bin_id_base,
+            protocol_fee_columns: args.protocol_share.is_some(),
+            guard_by_bin: false,
+        }
+    }
+}
+
+/// Everything needed to regenerate schedule.csv; written as schedule.json
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Sidecar {
+    curve: CurveConfig,
+    bins: i64,
+    /// Option that fixed the bin count (`explicit`, `end_price`, …); empty in
+    /// sidecars written before it was recorded
+    #[serde(default)]
+    bin_source: String,
+    exclude_bins: Option<Range<i64>>,
+    fees: DlmmFeeParams,
+    policy: LaunchPhasePolicy,
+    format: ScheduleFormat,
+}
+impl Sidecar {
+    /// Rebuilds the schedule.csv contents from the recorded parameters
+    fn regenerate(&self) -> Result<String> {
+        let curve = self.curve.boxed();
+        let mut buf = Vec::new();
+        match &self.exclude_bins {
+            Some(excluded) => {
+                let masked = MaskedCurve {
+                    inner: curve,
+                    excluded: excluded.clone(),
+                };
+                write_schedule_csv(
+                    &mut buf,
+                    &self.format,
+                    &masked,
+                    self.bins,
+                    &self.fees,
+                    &self.policy,
+                    RowLoop::default(),
+                )?
+            }
+            None => write_schedule_csv(
+                &mut buf,
+                &self.format,
+                &curve,
+                self.bins,
+                &self.fees,
+                &self.policy,
+                RowLoop::default(),
+            )?,
+        };
+        Ok(String::from_utf8(buf)?)
+    }
+}
+
+/// A fully resolved run, written by `--print-config` and rerun by `--replay`.
+///
+/// Unlike the CLI flags it records solved and derived values (R₀, s_mid, the
+/// final bin count), so a replay does not depend on the defaults of the bcurve
+/// version that reads it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Scenario {
+    /// bcurve version that resolved the run
+    version: String,
+    /// Curve, bins and their source, fees, launch policy and schedule options
+    schedule: Sidecar,
+}
+
+/// Outcome of a schedule run, for callers that inspect totals without parsing files
+#[derive(Debug)]
+struct RunResult {
+    /// [`Curve::name`] of the curve written
+    curve_name: String,
+    /// Number of bins written
+    bins: i64,
+    /// Compensated Σ ΔX_i as written to `supply_cum`
+    total_supply: f64,
+    /// Compensated Σ P_i·ΔX_i as written to `revenue_cum`
+    total_revenue: f64,
+    /// Verification checks on the curve
+    report: Report,
+    /// Directory holding the run's outputs
+    out_dir: String,
+}
+
+/// The curve a run resolved to and which option set its bin count
+struct Resolved {
+    config: CurveConfig,
+    bin_source: &'static str,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -95,9 +613,215 @@ struct Row {
     fee_base: f64,
     fee_var: f64,
     fee_total: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    price_inv: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    effective_ask: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    surcharge_revenue_bin: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    surcharge_revenue_cum: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    log_price: Option<f64>,
+    /// Empty on the first bin of the schedule, which has no previous price
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    log_return_from_prev: Option<Option<f64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bin_id: Option<i64>,
+    fee_total_bps: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fee_protocol: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fee_lp: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fee_total_with_surcharge: Option<f64>,
+}
+
+/// A non-fatal finding, printed when it fires and fatal under `--fail-on-warning`
+#[derive(Clone, Debug)]
+enum Warning {
+    /// Allowlist lines that failed to parse (1-based)
+    MalformedAllowlist { path: String, lines: Vec<usize> },
+    /// Adjacent bin prices nearly collide at f64 resolution
+    DegeneratePriceGap(PriceGap),
+    /// Bins holding less than `threshold` tokens
+    DustBins {
+        dust: i64,
+        bins: i64,
+        threshold: f64,
+    },
+    /// A Schedule.sol library likely too large to deploy
+    ContractSize { chunk: usize, bytes: usize },
+    /// Logistic grid prices reach `p_max`, where S(P) clamps and the tail goes flat
+    AsymptoteOvershoot { bin: i64, price: f64, p_max: f64 },
+    /// Geometric ΔX_0/ΔX_{bins-1} beyond `--max-allocation-ratio`
+    AllocationSpread { ratio: f64, max: f64, theta: f64 },
+    /// The uncapped fee at `--vol-accum` exceeds `max_fee_rate` from `distance`
+    /// bins off the active bin outward
+    FeeCapSaturated { distance: i64, va: f64, cap: f64 },
+    /// Logistic Σ P·ΔX off the exact integral by more than `--max-integration-error`
+    CoarseBins {
+        rel_err: f64,
+        max: f64,
+        bin_step_bps: f64,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::MalformedAllowlist { path, lines } => {
+                let shown: Vec<String> = lines.iter().take(10).map(|n| n.to_string()).collect();
+                write!(
+                    f,
+                    "{} malformed allowlist line(s) skipped in {} (lines {}{})",
+                    lines.len(),
+                    path,
+                    shown.join(", "),
+                    if lines.len() > 10 { ", …" } else { "" }
+                )
+            }
+            Warning::DegeneratePriceGap(gap) => write!(
+                f,
+                "bins {} and {} are {:.1} ULP apart (ΔP={:.3e}); bin_step_bps is too small for this price level",
+                gap.bin,
+                gap.bin + 1,
+                gap.ulps,
+                gap.gap
+            ),
+            Warning::DustBins {
+                dust,
+                bins,
+                threshold,
+            } => write!(f, "{} of {} bins hold ΔX < {}", dust, bins, threshold),
+            Warning::ContractSize { chunk, bytes } => write!(
+                f,
+                "ScheduleChunk{} is ≈{} bytes, over the {}-byte contract size limit; lower --solidity-chunk-bins",
+                chunk, bytes, CONTRACT_SIZE_LIMIT
+            ),
+            Warning::AsymptoteOvershoot { bin, price, p_max } => write!(
+                f,
+                "grid overshoots the asymptote: bin {} price {:.12} ≥ p_max={}, so S(P) is clamped and bins from {} on hold no supply; lower --bins or raise --p-max",
+                bin, price, p_max, bin
+            ),
+            Warning::AllocationSpread { ratio, max, theta } => write!(
+                f,
+                "bin 0 holds {:.3e}× the last bin's ΔX (over --max-allocation-ratio {:e}), so the tail bins are dust; raise --theta (now {}) toward 1",
+                ratio, max, theta
+            ),
+            Warning::FeeCapSaturated { distance, va, cap } => {
+                let from = if *distance == 0 {
+                    "every bin".to_string()
+                } else {
+                    format!("bins {} or more from the active bin", distance)
+                };
+                write!(
+                    f,
+                    "the fee is capped at max_fee_rate={} in {} at --vol-accum {}, so it no longer tracks volatility there; raise --max-fee-rate or lower --base-factor/--vol-accum",
+                    cap, from, va
+                )
+            }
+            Warning::CoarseBins {
+                rel_err,
+                max,
+                bin_step_bps,
+            } => write!(
+                f,
+                "Σ P·ΔX is off the exact ∫ P dS by {:.3e} (over --max-integration-error {}); the {} bps bins are too coarse for this curve, lower --bin-step-bps",
+                rel_err, max, bin_step_bps
+            ),
+        }
+    }
+}
+
+/// Prints `w` to stderr and records it for `--fail-on-warning`
+fn warn(warnings: &mut Vec<Warning>, w: Warning) {
+    eprintln!("warning: {}", w);
+    warnings.push(w);
+}
+
+/// Totals written by [`write_outputs`] and the warnings raised on the way
+struct Written {
+    total_supply: f64,
+    total_revenue: f64,
+    warnings: Vec<Warning>,
+}
+
+fn parse_bin_range(s: &str) -> Result<Range<i64>, String> {
+    let (lo, hi) = s
+        .split_once("..")
+        .ok_or_else(|| format!("expected start..end, got {:?}", s))?;
+    let start: i64 = lo.trim().parse().map_err(|e| format!("bad start: {}", e))?;
+    let end: i64 = hi.trim().parse().map_err(|e| format!("bad end: {}", e))?;
+    if start > end {
+        return Err(format!("range start {} > end {}", start, end));
+    }
+    Ok(start..end)
 }
 
 fn validate_inputs(args: &Args, grid: &Grid) -> Result<()> {
+    if args.preview == Some(0) {
+        return Err(anyhow!("preview must be ≥ 1 rows"));
+    }
+    if !args.format.writes_csv() {
+        for (set, flag) in [
+            (args.resume, "--resume"),
+            (args.verify_output, "--verify-output"),
+            (args.round_trip_test, "--round-trip-test"),
+        ] {
+            if set {
+                return Err(anyhow!("{} needs --format csv", flag));
+            }
+        }
+    }
+    if let Some(rows) = args.split_output {
+        if rows < 1 {
+            return Err(anyhow!("split_output must be ≥ 1 (got {})", rows));
+        }
+        if !args.format.writes_csv() {
+            return Err(anyhow!("--split-output needs --format csv"));
+        }
+        for (set, flag) in [
+            (args.resume, "--resume"),
+            (args.verify_output, "--verify-output"),
+            (args.round_trip_test, "--round-trip-test"),
+        ] {
+            if set {
+                return Err(anyhow!("{} reads schedule.csv; drop --split-output", flag));
+            }
+        }
+    }
+    if args.output_stdout && args.format == OutputFormat::Both {
+        return Err(anyhow!("--output-stdout writes one --format, not both"));
+    }
+    if args.format == OutputFormat::Parquet {
+        if !cfg!(feature = "parquet") {
+            return Err(anyhow!(
+                "--format parquet needs bcurve built with `--features parquet`"
+            ));
+        }
+        if args.output_stdout {
+            return Err(anyhow!("--format parquet cannot be written to stdout"));
+        }
+    }
+    if args.max_allocation_ratio.is_nan() || args.max_allocation_ratio < 1.0 {
+        return Err(anyhow!(
+            "max_allocation_ratio must be ≥ 1 (got {})",
+            args.max_allocation_ratio
+        ));
+    }
+    if args.max_integration_error.is_nan() || args.max_integration_error < 0.0 {
+        return Err(anyhow!(
+            "max_integration_error must be ≥ 0 (got {})",
+            args.max_integration_error
+        ));
+    }
+    if args.solidity_chunk_bins < 1 {
+        return Err(anyhow!(
+            "solidity_chunk_bins must be ≥ 1 (got {})",
+            args.solidity_chunk_bins
+        ));
+    }
     if !grid.p0.is_finite() || grid.p0 <= 0.0 {
         return Err(anyhow!("p0 must be finite and > 0 (got {})", grid.p0));
     }
@@ -112,12 +836,107 @@ fn validate_inputs(args: &Args, grid: &Grid) -> Result<()> {
             return Err(anyhow!("bins must be ≥ 1 (got {})", n));
         }
     }
+    let requested = match (args.bins, args.end_price) {
+        (Some(n), _) => Some(n),
+        (None, Some(p_end)) if p_end > grid.p0 => Some(compute_bins_from_end_price(grid, p_end)?),
+        _ => None,
+    };
+    if let Some(n) = requested {
+        if n.saturating_add(args.below_bins) > args.max_bins {
+            return Err(anyhow!(
+                "{} bins exceeds --max-bins {}; raise --max-bins if this is intended",
+                n.saturating_add(args.below_bins),
+                args.max_bins
+            ));
+        }
+    }
+    if args.below_bins < 0 {
+        return Err(anyhow!("below_bins must be ≥ 0 (got {})", args.below_bins));
+    }
+    if args.below_bins > 0 && args.mode != CurveKind::Geometric {
+        return Err(anyhow!("--below-bins is only supported in geometric mode"));
+    }
+    if !args.va_max.is_finite() || args.va_max < 0.0 {
+        return Err(anyhow!(
+            "va_max must be finite and ≥ 0 (got {})",
+            args.va_max
+        ));
+    }
+    if args.until_pmax_eps.is_some() && args.mode != CurveKind::Logistic {
+        return Err(anyhow!(
+            "--until-pmax-eps is only supported in logistic mode"
+        ));
+    }
+    if args.tiers.is_some() && args.mode != CurveKind::Step {
+        return Err(anyhow!("tiers are only supported in step mode"));
+    }
+    if args.fdv_csv.is_some() && args.mode != CurveKind::Step {
+        return Err(anyhow!("--fdv-csv is only supported in step mode"));
+    }
+    if (args.exp_a.is_some() || args.exp_b.is_some()) && args.mode != CurveKind::Exponential {
+        return Err(anyhow!(
+            "--exp-a/--exp-b are only supported in exponential mode"
+        ));
+    }
+    if (args.base.is_some() || args.slope.is_some()) && args.mode != CurveKind::Linear {
+        return Err(anyhow!("--base/--slope are only supported in linear mode"));
+    }
+    if args.compare_amm.is_some() && args.mode != CurveKind::Geometric {
+        return Err(anyhow!("--compare-amm is only supported in geometric mode"));
+    }
+    if !args.compare_theta.is_empty() && args.mode != CurveKind::Geometric {
+        return Err(anyhow!(
+            "--compare-theta is only supported in geometric mode"
+        ));
+    }
+    if let Some(theta) = args.compare_theta.iter().find(|t| !t.is_finite()) {
+        return Err(anyhow!(
+            "--compare-theta values must be finite (got {})",
+            theta
+        ));
+    }
+    if args.accuracy_sweep && args.mode != CurveKind::Logistic {
+        return Err(anyhow!(
+            "--accuracy-sweep is only supported in logistic mode"
+        ));
+    }
+    if args.geometric_from_logistic && args.mode != CurveKind::Logistic {
+        return Err(anyhow!(
+            "--geometric-from-logistic is only supported in logistic mode"
+        ));
+    }
+    if let Some(n) = args.adaptive_bins {
+        if args.mode != CurveKind::Logistic {
+            return Err(anyhow!(
+                "--adaptive-bins is only supported in logistic mode"
+            ));
+        }
+        if n < 2 || n > args.max_bins {
+            return Err(anyhow!(
+                "adaptive_bins must be in [2, --max-bins {}] (got {})",
+                args.max_bins,
+                n
+            ));
+        }
+    }
     if !(0.0..=1.0).contains(&args.max_fee_rate) {
         return Err(anyhow!(
             "max_fee_rate must be in [0,1] decimal (got {})",
             args.max_fee_rate
         ));
     }
+    if !args.distance_fee.is_finite() || args.distance_fee < 0.0 {
+        return Err(anyhow!(
+            "distance_fee must be finite and ≥ 0 (got {})",
+            args.distance_fee
+        ));
+    }
+    if !(0.0..1.0).contains(&args.cap_softness) {
+        return Err(anyhow!(
+            "cap_softness must be in [0, 1) (got {})",
+            args.cap_softness
+        ));
+    }
     if let Some(bps) = args.price_guard_bps {
         if !(0.0..10_000.0).contains(&bps) {
             return Err(anyhow!(
@@ -130,21 +949,241 @@ fn validate_inputs(args: &Args, grid: &Grid) -> Result<()> {
 }
 
 fn main() -> Result<()> {
-    let args = Args::parse();
-    let grid = Grid {
-        p0: args.p0,
-        bin_step_bps: args.bin_step_bps,
+    let args = match parse_args(std::env::args_os()) {
+        Ok(args) => args,
+        // Keep clap's own usage, help and version output
+        Err(e) => match e.downcast::<clap::Error>() {
+            Ok(e) => e.exit(),
+            Err(e) => return Err(e),
+        },
+    };
+    if args.list_modes {
+        print!("{}", format_mode_list());
+        return Ok(());
+    }
+    if let Some(path) = &args.batch {
+        return run_batch(&args, path);
+    }
+    run(&args).map(|_| ())
+}
+
+/// Parses the command line `argv`, first merging in the `--config` file if one is
+/// named. File values become the defaults of the flags they are keyed by, so a
+/// flag given on the command line always wins, missing keys keep the clap
+/// defaults and a file value is dropped when the command line sets a flag that
+/// conflicts with it. The step-mode `[[tiers]]` tables have no flag and are
+/// only read from the file.
+fn parse_args<I, T>(argv: I) -> Result<Args>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let argv: Vec<OsString> = argv.into_iter().map(Into::into).collect();
+    let cli = Args::command()
+        .ignore_errors(true)
+        .try_get_matches_from(&argv)?;
+    let Some(path) = cli.get_one::<String>("config") else {
+        return Ok(Args::try_parse_from(argv)?);
+    };
+    let text =
+        std::fs::read_to_string(path).with_context(|| format!("failed to read config {}", path))?;
+    let mut table: toml::Table =
+        toml::from_str(&text).with_context(|| format!("failed to parse config {}", path))?;
+    let on_cli = |id: &str| cli.value_source(id) == Some(ValueSource::CommandLine);
+    let tiers = match table.remove("tiers") {
+        Some(_) if on_cli("fdv_csv") => None,
+        Some(_) if table.contains_key("fdv_csv") => {
+            return Err(anyhow!(
+                "config {}: \"tiers\" cannot be used with \"fdv_csv\"",
+                path
+            ));
+        }
+        Some(value) => Some(
+            value
+                .try_into::<Vec<Tier>>()
+                .with_context(|| format!("config {}: bad [[tiers]]", path))?,
+        ),
+        None => None,
+    };
+    let mut command = Args::command();
+    let mut defaults = Vec::new();
+    for (key, value) in &table {
+        if key == "config" {
+            return Err(anyhow!("config {}: config files cannot be nested", path));
+        }
+        let arg = command
+            .get_arguments()
+            .find(|a| a.get_id() == key.as_str())
+            .ok_or_else(|| anyhow!("unknown option {:?} in config {}", key, path))?;
+        let overridden = on_cli(key)
+            || command
+                .get_arg_conflicts_with(arg)
+                .iter()
+                .any(|other| on_cli(other.get_id().as_str()));
+        if overridden {
+            continue;
+        }
+        if let Some(other) = command.get_arg_conflicts_with(arg).iter().find(|other| {
+            let id = other.get_id().as_str();
+            table.contains_key(id) && !on_cli(id)
+        }) {
+            return Err(anyhow!(
+                "config {}: {:?} cannot be used with {:?}",
+                path,
+                key,
+                other.get_id().as_str()
+            ));
+        }
+        let values = match value {
+            toml::Value::Array(items) => items.iter().collect(),
+            v => vec![v],
+        };
+        let values = values
+            .into_iter()
+            .map(|v| match v {
+                toml::Value::String(s) => Ok(s.clone()),
+                toml::Value::Integer(n) => Ok(n.to_string()),
+                toml::Value::Float(x) => Ok(x.to_string()),
+                toml::Value::Boolean(b) => Ok(b.to_string()),
+                _ => Err(anyhow!(
+                    "config {}: {:?} must be a string, number, boolean or array of them",
+                    path,
+                    key
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        defaults.push((key.clone(), values));
+    }
+    for (key, values) in defaults {
+        command = command.mut_arg(key, |a| a.default_values(values));
+    }
+    let matches = command.try_get_matches_from(argv)?;
+    let mut args = Args::from_arg_matches(&matches)?;
+    args.tiers = tiers;
+    Ok(args)
+}
+
+/// CLI parameters a run of `kind` cannot do without
+fn required_params(kind: CurveKind) -> &'static str {
+    match kind {
+        CurveKind::Geometric => "--r0 or --target-supply",
+        CurveKind::Logistic => "--p-max, or --fit-csv",
+        CurveKind::Step => "[[tiers]] in --config, or --fdv-csv",
+        CurveKind::Exponential => "--exp-b, and --exp-a or --target-supply",
+        CurveKind::Linear => "--base (--slope defaults to 0)",
+    }
+}
+
+/// `--list-modes`: each registered mode, its shape and its required parameters
+fn format_mode_list() -> String {
+    let width = CurveKind::all()
+        .iter()
+        .map(|k| k.name().len())
+        .max()
+        .unwrap_or(0);
+    let mut out = String::new();
+    for kind in CurveKind::all() {
+        out += &format!(
+            "{:<width$}  {}\n",
+            kind.name(),
+            kind.summary(),
+            width = width
+        );
+        out += &format!(
+            "{:<width$}  requires {}\n",
+            "",
+            required_params(*kind),
+            width = width
+        );
+    }
+    out
+}
+
+/// One full CLI run for `args`; `None` for `--fee-only`, which builds no curve
+fn run(args: &Args) -> Result<Option<RunResult>> {
+    let grid = match args.bins_per_decade {
+        _ if args.step_from_endpoints => {
+            if args.mode != CurveKind::Geometric {
+                return Err(anyhow!(
+                    "--step-from-endpoints is only supported in geometric mode"
+                ));
+            }
+            // requires_all guarantees these are present
+            Geometric::from_endpoints(
+                args.p0,
+                args.end_price.unwrap_or_default(),
+                args.bins.unwrap_or_default(),
+                args.theta,
+                args.target_supply.unwrap_or_default(),
+            )?
+            .grid
+        }
+        Some(n) => {
+            if !n.is_finite() || n <= 0.0 {
+                return Err(anyhow!(
+                    "bins_per_decade must be finite and > 0 (got {})",
+                    n
+                ));
+            }
+            Grid::from_bins_per_decade(args.p0, n)
+        }
+        None => Grid {
+            p0: args.p0,
+            bin_step_bps: args.bin_step_bps,
+            spacing: args.grid_spacing,
+        },
+    };
+    let grid = match (args.anchor_price, args.anchor_bin) {
+        (Some(price), Some(bin)) => {
+            if !price.is_finite() || price <= 0.0 {
+                return Err(anyhow!(
+                    "anchor_price must be finite and > 0 (got {})",
+                    price
+                ));
+            }
+            grid.anchored_at(price, bin)
+        }
+        _ => grid,
     };
-    validate_inputs(&args, &grid)?;
+    validate_inputs(args, &grid)?;
 
-    let mut allowlist = HashSet::new();
+    let mut warnings = Vec::new();
+    let address_match = if args.allowlist_exact {
+        AddressMatch::Exact
+    } else {
+        AddressMatch::CaseInsensitive
+    };
+    let mut allowlist = Allowlist::new(address_match);
     if let Some(path) = &args.allowlist_path {
         if Path::new(path).exists() {
-            for line in std::fs::read_to_string(path)?.lines() {
-                let addr = line.trim();
-                if !addr.is_empty() {
-                    allowlist.insert(addr.to_string());
-                }
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read allowlist {}", path))?;
+            let parsed = parse_allowlist(&text, args.allowlist_format, address_match)
+                .with_context(|| format!("failed to parse allowlist {}", path))?;
+            let stats = &parsed.stats;
+            if !stats.malformed.is_empty() {
+                warn(
+                    &mut warnings,
+                    Warning::MalformedAllowlist {
+                        path: path.clone(),
+                        lines: stats.malformed.clone(),
+                    },
+                );
+            }
+            if args.verbose {
+                info!(
+                    args,
+                    "Allowlist {}: {} lines, {} valid, {} duplicates, {} malformed",
+                    path,
+                    stats.total_lines,
+                    stats.valid,
+                    stats.duplicates,
+                    stats.malformed.len()
+                );
+            }
+            allowlist.extend(parsed.addresses);
+            for entry in parsed.entries.into_values() {
+                allowlist.insert_entry(entry);
             }
         }
     }
@@ -153,188 +1192,1981 @@ fn main() -> Result<()> {
         tau_start_pct: args.tau_start_pct,
         tau_end_pct: args.tau_end_pct,
         ramp_secs: args.tau_ramp_secs,
+        tau_decay: args.tau_decay,
     };
 
     // fees
     let fees = DlmmFeeParams {
         base_factor: args.base_factor,
-        bin_step_bps: args.bin_step_bps,
+        bin_step_bps: grid.price_step_bps(),
         variable_fee_control: args.variable_fee_control,
         max_fee_rate: args.max_fee_rate,
+        min_fee_rate: args.min_fee_rate,
+        cap_softness: args.cap_softness,
+        distance_fee_delta: args.distance_fee,
+        protocol_share: args.protocol_share.unwrap_or(0.0),
     };
+    fees.validate()?;
 
-    create_dir_all(&args.out_dir)?;
-
-    match args.mode.as_str() {
-        "geometric" => run_geometric(&args, grid, fees, policy),
-        "logistic" => run_logistic(&args, grid, fees, policy),
-        m => Err(anyhow!("unknown mode: {}", m)),
+    if writes_to_out_dir(args) {
+        create_dir_all(&args.out_dir)
+            .with_context(|| format!("failed to create output directory {}", args.out_dir))?;
     }
-}
-
-fn compute_bins_from_end_price(grid: &Grid, end_price: f64) -> i64 {
-    let q = grid.q();
-    let ratio = end_price / grid.p0;
-    assert!(ratio.is_finite(), "end_price / p0 must be finite");
-    // Caller must ensure end_price > p0 for an increasing bin count.
-    let n = (ratio.ln() / q.ln()).ceil() as i64;
-    n.max(1)
-}
 
-fn run_geometric(
-    args: &Args,
-    grid: Grid,
-    fees: DlmmFeeParams,
-    policy: LaunchPhasePolicy,
-) -> Result<()> {
-    let bins = if let Some(n) = args.bins {
-        n
-    } else if let Some(p_end) = args.end_price {
-        if p_end <= grid.p0 {
+    if args.fee_tradeoff {
+        if fees.base_fee_rate() == 0.0 {
             return Err(anyhow!(
-                "geometric: require end_price > p0; got end_price={} ≤ p0={}",
-                p_end,
-                grid.p0
+                "--fee-tradeoff sweeps multiples of the base fee, which is 0 (--base-factor {})",
+                args.base_factor
             ));
         }
-        compute_bins_from_end_price(&grid, p_end)
-    } else {
-        500
-    };
-
-    let theta = args.theta.clamp(-2.0, 2.0);
-    let mut curve = Geometric {
-        grid,
-        theta,
-        r0_quote: args.r0.unwrap_or(0.0),
-    };
+        write_fee_tradeoff_csv(&args.out_dir, &fees, args.elasticity, args.base_volume)
+            .with_context(|| format!("failed to write {}/fee_tradeoff.csv", args.out_dir))?;
+    }
 
-    if curve.r0_quote <= 0.0 {
-        let target_s = args
-            .target_supply
-            .ok_or_else(|| anyhow!("geometric: need --r0 or --target-supply"))?;
-        curve.r0_quote = curve.solve_r0_from_supply(target_s, bins);
+    if args.fee_only {
+        run_fee_only(args, &fees)?;
+        return Ok(None);
     }
 
-    let rep = verify_geometric(&curve, bins)?;
+    let (result, run_warnings) = match (&args.replay, args.mode) {
+        (Some(path), _) => run_replay(args, path)?,
+        (None, CurveKind::Geometric) => run_geometric(args, grid, fees, policy)?,
+        (None, CurveKind::Logistic) => run_logistic(args, grid, fees, policy)?,
+        (None, CurveKind::Step) => run_step(args, grid, fees, policy)?,
+        (None, CurveKind::Exponential) => run_exponential(args, grid, fees, policy)?,
+        (None, CurveKind::Linear) => run_linear(args, grid, fees, policy)?,
+    };
+    warnings.extend(run_warnings);
     if args.verbose {
-        println!(
-            "[{}] bins={} sumS={:.6} closed={:.6} rel_err={:.3e} monotone={}",
-            curve.name(),
-            rep.bins,
-            rep.supply_sum,
-            rep.supply_closed.unwrap(),
-            rep.rel_err_supply.unwrap(),
-            rep.monotone_ok
-        );
-        println!(
-            "  Growth factor g=q^θ={:.12}, Decay factor r=q^(θ-1)={:.12}",
-            curve.g(),
-            curve.r()
+        print_run_result(args, &result);
+    }
+    if args.verify_json {
+        let path = format!("{}/verify.json", args.out_dir);
+        let write = || -> Result<()> {
+            let mut file = File::create(&path)?;
+            serde_json::to_writer_pretty(&mut file, &result.report)?;
+            writeln!(file)?;
+            Ok(())
+        };
+        write().with_context(|| format!("failed to write {}", path))?;
+    }
+    if args.fail_on_warning && !warnings.is_empty() {
+        let list: Vec<String> = warnings.iter().map(|w| format!("  - {}", w)).collect();
+        return Err(anyhow!(
+            "{} warning(s) with --fail-on-warning:\n{}",
+            warnings.len(),
+            list.join("\n")
+        ));
+    }
+    Ok(Some(result))
+}
+
+/// Path of chart `name` under `--out-dir`, with the `--chart-format` extension
+fn chart_path(args: &Args, name: &str) -> String {
+    format!(
+        "{}/{}.{}",
+        args.out_dir,
+        name,
+        args.chart_format.extension()
+    )
+}
+
+/// Whether the run writes any file under `--out-dir`; with `--output-stdout` or
+/// `--print-config` only the side outputs do
+fn writes_to_out_dir(args: &Args) -> bool {
+    !args.stdout_is_data()
+        || args.fee_tradeoff
+        || args.accuracy_sweep
+        || args.compare_amm.is_some()
+        || !args.compare_theta.is_empty()
+        || args.trades.is_some()
+        || args.montecarlo
+        || args.apr
+        || args.sidecar
+        || args.verify_json
+}
+
+/// A `--batch` line and how its run ended
+struct BatchOutcome {
+    line: usize,
+    out_dir: String,
+    result: Result<Option<RunResult>>,
+}
+
+/// Runs one `--batch` line, split like a shell command line, into `out_dir`. A
+/// panic in the run becomes this line's error instead of taking down the batch.
+fn run_batch_line(config: &str, out_dir: &str) -> Result<Option<RunResult>> {
+    let words = shlex::split(config).ok_or_else(|| anyhow!("unbalanced quotes"))?;
+    let argv = std::iter::once("bcurve".to_string())
+        .chain(words)
+        .chain(["--out-dir".to_string(), out_dir.to_string()]);
+    let run_args = parse_args(argv)?;
+    if run_args.batch.is_some() {
+        return Err(anyhow!("--batch cannot be nested"));
+    }
+    std::panic::catch_unwind(AssertUnwindSafe(|| run(&run_args))).unwrap_or_else(|panic| {
+        let msg = panic
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown cause");
+        Err(anyhow!("run panicked: {}", msg))
+    })
+}
+
+/// Runs the `--batch` configs on `--concurrency` worker threads, then writes
+/// batch_summary.csv in one step (temp file + rename) once all have finished.
+/// Fails after writing the summary if any config failed.
+fn run_batch(args: &Args, path: &str) -> Result<()> {
+    if args.concurrency == 0 {
+        return Err(anyhow!("concurrency must be ≥ 1"));
+    }
+    let text =
+        std::fs::read_to_string(path).with_context(|| format!("failed to read batch {}", path))?;
+    let jobs: Vec<(usize, &str)> = text
+        .lines()
+        .enumerate()
+        .map(|(n, line)| (n + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    if jobs.is_empty() {
+        return Err(anyhow!("batch {} has no configs", path));
+    }
+    create_dir_all(&args.out_dir)
+        .with_context(|| format!("failed to create output directory {}", args.out_dir))?;
+
+    let next = AtomicUsize::new(0);
+    let outcomes = Mutex::new(Vec::with_capacity(jobs.len()));
+    std::thread::scope(|scope| {
+        for _ in 0..args.concurrency.min(jobs.len()) {
+            scope.spawn(|| {
+                while let Some(&(line, config)) = jobs.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    // One directory per line; a config that sets --out-dir itself is rejected by clap
+                    let out_dir = format!("{}/run_{:04}", args.out_dir, line);
+                    let result = run_batch_line(config, &out_dir);
+                    let outcome = BatchOutcome {
+                        line,
+                        out_dir,
+                        result,
+                    };
+                    outcomes
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .push(outcome);
+                }
+            });
+        }
+    });
+    let mut outcomes = outcomes.into_inner().unwrap_or_else(|e| e.into_inner());
+    outcomes.sort_by_key(|o| o.line);
+
+    let summary = format!("{}/batch_summary.csv", args.out_dir);
+    let tmp = format!("{}.tmp", summary);
+    let write = || -> Result<()> {
+        let mut wtr = csv::Writer::from_path(&tmp)?;
+        wtr.write_record([
+            "line",
+            "out_dir",
+            "status",
+            "curve",
+            "bins",
+            "total_supply",
+            "total_revenue",
+            "error",
+        ])?;
+        for o in &outcomes {
+            let line = o.line.to_string();
+            let record = match &o.result {
+                Ok(Some(r)) => [
+                    line,
+                    o.out_dir.clone(),
+                    "ok".into(),
+                    r.curve_name.clone(),
+                    r.bins.to_string(),
+                    r.total_supply.to_string(),
+                    r.total_revenue.to_string(),
+                    String::new(),
+                ],
+                Ok(None) => [
+                    line,
+                    o.out_dir.clone(),
+                    "ok".into(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                ],
+                Err(e) => [
+                    line,
+                    o.out_dir.clone(),
+                    "error".into(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    format!("{:#}", e)
+                        .lines()
+                        .next()
+                        .unwrap_or_default()
+                        .to_string(),
+                ],
+            };
+            wtr.write_record(&record)?;
+        }
+        wtr.flush()?;
+        drop(wtr);
+        std::fs::rename(&tmp, &summary)?;
+        Ok(())
+    };
+    write().with_context(|| format!("failed to write {}", summary))?;
+
+    let failed: Vec<String> = outcomes
+        .iter()
+        .filter_map(|o| {
+            o.result
+                .as_ref()
+                .err()
+                .map(|e| format!("  - line {}: {:#}", o.line, e))
+        })
+        .collect();
+    if args.verbose {
+        info!(
+            args,
+            "Batch {}: {} configs, {} failed, summary in {}",
+            path,
+            outcomes.len(),
+            failed.len(),
+            summary
+        );
+    }
+    if !failed.is_empty() {
+        return Err(anyhow!(
+            "{} of {} batch configs failed (see {}):\n{}",
+            failed.len(),
+            outcomes.len(),
+            summary,
+            failed.join("\n")
+        ));
+    }
+    Ok(())
+}
+
+/// One row of the `--preview` table
+struct PreviewRow {
+    bin: i64,
+    price: f64,
+    delta_x: f64,
+    supply_cum: f64,
+    fee_total: f64,
+}
+
+/// The schedule's preview columns for bins `[first_bin, bins)`, with the same
+/// compensated `supply_cum` as schedule.csv
+fn preview_rows<C: Curve>(c: &C, bins: i64, fees: &dyn FeeModel, va: f64) -> Vec<PreviewRow> {
+    let mut supply = CompensatedSum::default();
+    (c.first_bin()..bins)
+        .map(|i| {
+            let dx = c.delta_x_of_bin(i);
+            supply.add(dx);
+            PreviewRow {
+                bin: i,
+                price: c.price_of_bin(i),
+                delta_x: dx,
+                supply_cum: supply.value(),
+                fee_total: fees.total_fee_rate_at_bin(va, i),
+            }
+        })
+        .collect()
+}
+
+/// Six significant digits, in scientific notation for tiny or huge values
+fn preview_number(x: f64) -> String {
+    if x == 0.0 || !x.is_finite() {
+        return x.to_string();
+    }
+    let magnitude = x.abs().log10().floor();
+    if (-4.0..9.0).contains(&magnitude) {
+        format!("{:.*}", (5.0 - magnitude).max(0.0) as usize, x)
+    } else {
+        format!("{:.5e}", x)
+    }
+}
+
+/// Right-aligned table of the first and last `n` rows; longer schedules get a
+/// `…` row in place of the middle
+fn format_preview(rows: &[PreviewRow], n: usize) -> String {
+    let shown: Vec<Option<&PreviewRow>> = if rows.len() > 2 * n {
+        let (head, tail) = (&rows[..n], &rows[rows.len() - n..]);
+        head.iter()
+            .map(Some)
+            .chain(std::iter::once(None))
+            .chain(tail.iter().map(Some))
+            .collect()
+    } else {
+        rows.iter().map(Some).collect()
+    };
+    let header = ["bin", "price", "delta_x", "supply_cum", "fee_total"].map(String::from);
+    let cells: Vec<[String; 5]> = shown
+        .iter()
+        .map(|row| match row {
+            Some(r) => [
+                r.bin.to_string(),
+                preview_number(r.price),
+                preview_number(r.delta_x),
+                preview_number(r.supply_cum),
+                preview_number(r.fee_total),
+            ],
+            None => ["…"; 5].map(String::from),
+        })
+        .collect();
+    let mut widths = [0; 5];
+    for row in std::iter::once(&header).chain(&cells) {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+    let line = |row: &[String; 5]| -> String {
+        let padded: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, w)| format!("{:>w$}", cell, w = w))
+            .collect();
+        padded.join("  ")
+    };
+    let mut out = line(&header) + "\n";
+    out += &"-".repeat(widths.iter().sum::<usize>() + 2 * (widths.len() - 1));
+    out += "\n";
+    for row in &cells {
+        out += &line(row);
+        out += "\n";
+    }
+    out
+}
+
+fn print_run_result(args: &Args, r: &RunResult) {
+    info!(
+        args,
+        "Wrote {} bins of {} to {}: supply={:.6} revenue={:.6} monotone={} revenue_monotone={}",
+        r.bins,
+        r.curve_name,
+        if args.output_stdout {
+            "stdout"
+        } else {
+            &r.out_dir
+        },
+        r.total_supply,
+        r.total_revenue,
+        r.report.monotone_ok,
+        r.report.revenue_monotone_ok
+    );
+    let a = &r.report.allocation;
+    info!(
+        args,
+        "  Allocation ΔX: mean={:.6} sd={:.6} CV={}",
+        a.mean,
+        a.variance.sqrt(),
+        a.cv.map_or_else(|| "n/a".to_string(), |cv| format!("{:.6}", cv))
+    );
+    if let Some(check) = &r.report.asymptote {
+        info!(
+            args,
+            "  Approaches p_max: {}{}",
+            check.approaches_asymptote_ok,
+            check
+                .first_overshoot_bin
+                .map_or_else(String::new, |bin| format!(" (overshoots from bin {})", bin))
+        );
+    }
+}
+
+fn write_fee_tradeoff_csv(
+    out_dir: &str,
+    fees: &DlmmFeeParams,
+    elasticity: f64,
+    base_volume: f64,
+) -> Result<()> {
+    let mut wtr = csv::Writer::from_path(format!("{}/fee_tradeoff.csv", out_dir))?;
+    wtr.write_record(["fee_rate", "projected_fee_revenue"])?;
+    for (fee, revenue) in fee_revenue_tradeoff(fees, elasticity, base_volume) {
+        wtr.serialize((fee, revenue))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Writes `fees.csv` over `va ∈ [0, va_max]` and the fee chart; no curve is built.
+fn run_fee_only(args: &Args, fees: &DlmmFeeParams) -> Result<()> {
+    let path = if args.output_stdout {
+        "stdout".to_string()
+    } else {
+        format!("{}/fees.csv", args.out_dir)
+    };
+    let write = || -> Result<()> {
+        let out: Box<dyn Write> = if args.output_stdout {
+            Box::new(std::io::stdout().lock())
+        } else {
+            Box::new(File::create(&path)?)
+        };
+        let mut wtr = csv::Writer::from_writer(out);
+        wtr.write_record(["va", "fee_base", "fee_var", "fee_total"])?;
+        let steps = args.va_steps.max(1);
+        for n in 0..=steps {
+            let va = args.va_max * n as f64 / steps as f64;
+            wtr.serialize((
+                va,
+                fees.base_fee_rate(),
+                fees.variable_fee_rate(va),
+                fees.total_fee_rate(va),
+            ))?;
+        }
+        wtr.flush()?;
+        Ok(())
+    };
+    write().with_context(|| format!("failed to write {}", path))?;
+    if args.draw && !args.output_stdout {
+        plot_fee_components(fees, &chart_path(args, "fee_vs_volatility"))?;
+    }
+    Ok(())
+}
+
+fn write_accuracy_csv(out_dir: &str, curve: &LogisticS, bins: i64) -> Result<()> {
+    let mut wtr = csv::Writer::from_path(format!("{}/accuracy.csv", out_dir))?;
+    wtr.write_record(["bin_step_bps", "rel_error"])?;
+    for row in accuracy_sweep(curve, bins, &ACCURACY_SWEEP_STEPS_BPS) {
+        wtr.serialize(row)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Writes `amm_compare.csv`: the geometric allocation per bin beside an `amm` position
+/// over `[P_0, P_bins]` worth the same capital, i.e. raising the schedule's Σ P_i·ΔX_i
+/// when sold through the range. Returns the largest |difference|.
+fn write_amm_compare_csv(out_dir: &str, curve: &Geometric, bins: i64, amm: AmmKind) -> Result<f64> {
+    let AmmKind::UniswapV3 = amm;
+    let (p_lo, p_hi) = (curve.price_of_bin(0), curve.price_of_bin(bins));
+    let liquidity = uniswapv3_liquidity_for_quote(p_lo, p_hi, curve.revenue_closed(bins));
+    let v3 = uniswapv3_allocation(&curve.grid, p_lo, p_hi, liquidity, bins);
+    let mut wtr = csv::Writer::from_path(format!("{}/amm_compare.csv", out_dir))?;
+    wtr.write_record(["bin", "price", "dlmm_delta_x", "v3_delta_x", "diff"])?;
+    let mut max_diff = 0.0_f64;
+    for (i, v3_dx) in (0..bins).zip(v3) {
+        let dx = curve.delta_x_of_bin(i);
+        max_diff = max_diff.max((dx - v3_dx).abs());
+        wtr.serialize((i, curve.price_of_bin(i), dx, v3_dx, dx - v3_dx))?;
+    }
+    wtr.flush()?;
+    Ok(max_diff)
+}
+
+/// Bins needed to reach `end_price`, erroring instead of panicking on a
+/// non-finite price or a degenerate grid
+fn compute_bins_from_end_price(grid: &Grid, end_price: f64) -> Result<i64> {
+    // Caller must ensure end_price > p0 for an increasing bin count.
+    let below = grid.bin_of_price(end_price).with_context(|| {
+        format!(
+            "cannot size bins from end_price={} on a grid with p0={}, bin_step_bps={}",
+            end_price, grid.p0, grid.bin_step_bps
+        )
+    })?;
+    let n = if grid.price_of_bin(below) < end_price {
+        below + 1
+    } else {
+        below
+    };
+    Ok(n.max(1))
+}
+
+/// Draws compare_theta.png: `curve` redone at each `--compare-theta` θ, with R₀
+/// re-solved to `curve`'s total supply unless `--r0` was given
+fn plot_theta_comparison(args: &Args, curve: &Geometric, bins: i64) -> Result<()> {
+    let supply = curve.s_n_closed(bins);
+    let curves: Vec<(Geometric, String)> = args
+        .compare_theta
+        .iter()
+        .map(|&theta| {
+            let mut c = Geometric {
+                theta: theta.clamp(-2.0, 2.0),
+                ..*curve
+            };
+            if args.r0.is_none() {
+                c.r0_quote = c.solve_r0_from_supply(supply, bins);
+            }
+            (c, format!("θ={}", theta))
+        })
+        .collect();
+    let series: Vec<(&dyn Curve, &str, _)> = curves
+        .iter()
+        .zip(SERIES_COLORS.iter().cycle())
+        .map(|((c, label), &color)| (c as &dyn Curve, label.as_str(), color))
+        .collect();
+    plot_price_vs_supply_multi(
+        &series,
+        bins,
+        args.max_plot_points,
+        &chart_path(args, "compare_theta"),
+    )
+}
+
+fn run_geometric(
+    args: &Args,
+    grid: Grid,
+    fees: DlmmFeeParams,
+    policy: LaunchPhasePolicy,
+) -> Result<(RunResult, Vec<Warning>)> {
+    let (bins, bin_source) = resolve_bins(args, &grid, "geometric")?;
+
+    let theta = args.theta.clamp(-2.0, 2.0);
+    let mut curve = Geometric {
+        grid,
+        theta,
+        r0_quote: args.r0.unwrap_or(0.0),
+    };
+
+    if curve.r0_quote <= 0.0 {
+        let target_s = args
+            .target_supply
+            .ok_or_else(|| anyhow!("geometric: need --r0 or --target-supply"))?;
+        curve.r0_quote = curve.solve_r0_from_supply(target_s, bins);
+    }
+
+    let rep = verify_geometric(&curve, bins)?;
+    let mut warnings = Vec::new();
+    let ratio = rep.allocation_ratio.unwrap_or(1.0);
+    if ratio > args.max_allocation_ratio {
+        let spread = Warning::AllocationSpread {
+            ratio,
+            max: args.max_allocation_ratio,
+            theta: curve.theta,
+        };
+        warn(&mut warnings, spread);
+    }
+    if args.verbose {
+        info!(
+            args,
+            "[{}] bins={} sumS={:.6} closed={:.6} rel_err={:.3e} monotone={} (non-strict steps: {})",
+            curve.name(),
+            rep.bins,
+            rep.supply_sum,
+            rep.supply_closed.unwrap(),
+            rep.rel_err_supply.unwrap(),
+            rep.monotone_ok,
+            rep.non_strict_steps
+        );
+        info!(
+            args,
+            "  Revenue closed={:.6} rel_err={:.3e}",
+            rep.revenue_closed.unwrap(),
+            rep.rel_err_revenue.unwrap()
+        );
+        info!(
+            args,
+            "  Growth factor g=q^θ={:.12}, Decay factor r=q^(θ-1)={:.12}",
+            curve.g(),
+            curve.r()
+        );
+        info!(
+            args,
+            "  Allocation ratio ΔX_0/ΔX_{}: {:.3e}",
+            bins - 1,
+            ratio
+        );
+        info!(
+            args,
+            "  Allocation shape: {:?} (convex={}, concave={})",
+            rep.shape,
+            rep.convex_ok,
+            rep.concave_ok
         );
-        println!(
+        info!(
+            args,
             "  Cumulative supply at n={}: {:.6}",
             bins,
             curve.cumulative_supply(bins)
         );
-        println!("  Allowlist size: {}", policy.allowlist.len());
-        println!(
+        info!(args, "  Allowlist size: {}", policy.allowlist_len());
+        info!(
+            args,
             "  Launch surcharge: τ(0s)={:.1}% → τ({:.0}s)={:.1}%",
             policy.tau(0.0),
             policy.ramp_secs,
             policy.tau(policy.ramp_secs)
         );
     }
+    if let Some(amm) = args.compare_amm {
+        let max_diff = write_amm_compare_csv(&args.out_dir, &curve, bins, amm)
+            .with_context(|| format!("failed to write {}/amm_compare.csv", args.out_dir))?;
+        if args.verbose {
+            info!(
+                args,
+                "  AMM comparison: max |ΔX_dlmm - ΔX_v3| = {:.6}", max_diff
+            );
+        }
+    }
 
-    write_schedule_csv_geometric(
-        &args.out_dir,
-        &curve,
+    if !args.compare_theta.is_empty() {
+        plot_theta_comparison(args, &curve, bins)?;
+    }
+
+    let mode_lines = [
+        format!("Mode: Geometric, θ={}, R₀={}", curve.theta, curve.r0_quote),
+        format!("Bins: {} (source: {})", bins, bin_source.label()),
+        format!(
+            "Growth factor g={:.12}, Decay factor r={:.12}",
+            curve.g(),
+            curve.r()
+        ),
+    ];
+    if args.below_bins == 0 {
+        let written = write_outputs(
+            args,
+            &curve,
+            Resolved {
+                config: CurveConfig::Geometric(curve),
+                bin_source: bin_source.label(),
+            },
+            bins,
+            &fees,
+            &policy,
+            &mode_lines,
+        )?;
+        let result = RunResult {
+            curve_name: curve.name().to_string(),
+            bins,
+            total_supply: written.total_supply,
+            total_revenue: written.total_revenue,
+            report: rep,
+            out_dir: args.out_dir.clone(),
+        };
+        warnings.extend(written.warnings);
+        return Ok((result, warnings));
+    }
+    let two_sided = TwoSided {
+        core: curve,
+        below_bins: args.below_bins,
+    };
+    let mut lines = mode_lines.to_vec();
+    lines.push(format!(
+        "Two-sided: bins {}..{}, supply below P₀={:.6}, above P₀={:.6}",
+        -two_sided.below_bins,
         bins,
-        fees,
-        args.vol_accum,
+        two_sided.supply_below(),
+        two_sided.supply_above(bins)
+    ));
+    if args.verbose {
+        info!(args, "  {}", lines[lines.len() - 1]);
+    }
+    let written = write_outputs(
+        args,
+        &two_sided,
+        Resolved {
+            config: CurveConfig::TwoSided(two_sided),
+            bin_source: bin_source.label(),
+        },
+        bins,
+        &fees,
         &policy,
-        args.price_guard_bps,
+        &lines,
     )?;
-    if args.draw {
-        plot_price_vs_supply(
-            &curve,
+    let result = RunResult {
+        curve_name: two_sided.name().to_string(),
+        bins: bins + two_sided.below_bins,
+        total_supply: written.total_supply,
+        total_revenue: written.total_revenue,
+        report: rep,
+        out_dir: args.out_dir.clone(),
+    };
+    warnings.extend(written.warnings);
+    Ok((result, warnings))
+}
+
+/// Rewrites the schedule and charts of a `--replay` scenario with this run's output
+/// options (`--out-dir`, `--format`, charts, simulations)
+fn run_replay(args: &Args, path: &str) -> Result<(RunResult, Vec<Warning>)> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("failed to read {}", path))?;
+    let scenario: Scenario =
+        serde_json::from_str(&text).with_context(|| format!("failed to parse {}", path))?;
+    let Sidecar {
+        curve,
+        bins,
+        bin_source,
+        exclude_bins,
+        fees,
+        policy,
+        format,
+    } = &scenario.schedule;
+    fees.validate()?;
+    if args.verbose {
+        info!(
+            args,
+            "Replaying {} (bcurve {}, bins from {})", path, scenario.version, bin_source
+        );
+    }
+    let curve = curve.boxed();
+    let mut warnings = Vec::new();
+    let (total_supply, total_revenue) = match exclude_bins {
+        Some(excluded) => {
+            let masked = MaskedCurve {
+                inner: curve.clone(),
+                excluded: excluded.clone(),
+            };
+            write_schedule_and_plots(args, &masked, *bins, fees, policy, format, &mut warnings)?
+        }
+        None => write_schedule_and_plots(args, &curve, *bins, fees, policy, format, &mut warnings)?,
+    };
+    let result = RunResult {
+        curve_name: curve.name().to_string(),
+        bins: *bins - curve.first_bin(),
+        total_supply,
+        total_revenue,
+        report: schedule_report(&curve, *bins, total_supply),
+        out_dir: args.out_dir.clone(),
+    };
+    Ok((result, warnings))
+}
+
+/// Compensated `(supply_cum, revenue_cum)` of the schedule's last row, without writing it
+fn schedule_totals<C: Curve>(c: &C, bins: i64) -> (f64, f64) {
+    let (mut supply, mut revenue) = (CompensatedSum::default(), CompensatedSum::default());
+    for (_, p, dx) in c.bins_iter(c.first_bin()..bins) {
+        supply.add(dx);
+        revenue.add(p * dx);
+    }
+    (supply.value(), revenue.value())
+}
+
+/// Which option determined the schedule's bin count
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BinSource {
+    Explicit,
+    EndPrice,
+    UntilPmax,
+    Default,
+}
+impl BinSource {
+    fn label(self) -> &'static str {
+        match self {
+            BinSource::Explicit => "explicit",
+            BinSource::EndPrice => "end_price",
+            BinSource::UntilPmax => "until_pmax_eps",
+            BinSource::Default => "default",
+        }
+    }
+}
+
+/// Resolves the bin count from `--bins`, `--end-price`, `--until-pmax-eps`, or the
+/// default of 500, reporting which one applied
+fn resolve_bins(args: &Args, grid: &Grid, mode: &str) -> Result<(i64, BinSource)> {
+    if let Some(n) = args.bins {
+        Ok((n, BinSource::Explicit))
+    } else if let Some(p_end) = args.end_price {
+        if p_end <= grid.p0 {
+            return Err(anyhow!(
+                "{}: require end_price > p0; got end_price={} ≤ p0={}",
+                mode,
+                p_end,
+                grid.p0
+            ));
+        }
+        Ok((
+            compute_bins_from_end_price(grid, p_end)?,
+            BinSource::EndPrice,
+        ))
+    } else if let Some(eps) = args.until_pmax_eps {
+        Ok((bins_until_pmax(args, grid, eps)?, BinSource::UntilPmax))
+    } else {
+        Ok((500, BinSource::Default))
+    }
+}
+
+/// Bins needed for P_i to come within `eps·(p_max - p_min)` of `p_max`, capped at `--max-bins`
+fn bins_until_pmax(args: &Args, grid: &Grid, eps: f64) -> Result<i64> {
+    let p_max = args
+        .p_max
+        .ok_or_else(|| anyhow!("logistic: --until-pmax-eps needs --p-max"))?;
+    if !(eps > 0.0 && eps < 1.0) {
+        return Err(anyhow!("until_pmax_eps must be in (0, 1) (got {})", eps));
+    }
+    let target = p_max - eps * (p_max - args.p_min);
+    let last = grid.bins_for_price_range(target);
+    if last >= args.max_bins {
+        return Err(anyhow!(
+            "price does not reach {} (p_max - {}·range) within --max-bins {}",
+            target,
+            eps,
+            args.max_bins
+        ));
+    }
+    // Include the bin that first reaches the target
+    Ok((last + 1).max(2))
+}
+
+fn run_logistic(
+    args: &Args,
+    grid: Grid,
+    fees: DlmmFeeParams,
+    policy: LaunchPhasePolicy,
+) -> Result<(RunResult, Vec<Warning>)> {
+    let (bins, bin_source) = resolve_bins(args, &grid, "logistic")?;
+    if bins < 2 {
+        // ΔX_i = S(P_{i+1}) - S(P_i) leaves the last bin empty
+        return Err(anyhow!("need ≥2 bins for logistic (got {})", bins));
+    }
+
+    let mut fit_line = None;
+    let curve = if let Some(path) = &args.fit_csv {
+        let points = read_csv_rows(path)?;
+        let fit = LogisticS::fit(&points, grid, bins)?;
+        fit_line = Some(format!(
+            "Fitted to {} points from {}: RMS residual={:.6e} ({} iterations)",
+            points.len(),
+            path,
+            fit.rms_residual,
+            fit.iterations
+        ));
+        fit.curve
+    } else {
+        let p_max = args
+            .p_max
+            .ok_or_else(|| anyhow!("logistic: need --p-max or --fit-csv"))?;
+        let mut s_mid = args.s_mid;
+        if s_mid == 0.0 {
+            s_mid = ((p_max - grid.p0) / (grid.p0 - args.p_min)).ln() / args.k;
+        }
+        LogisticS {
+            grid,
+            p_min: args.p_min,
+            p_max,
+            k: args.k,
+            s_mid,
+            bins,
+        }
+    };
+    if !(curve.p_min < grid.p0 && grid.p0 < curve.p_max) {
+        return Err(anyhow!(
+            "require p_min < p0 < p_max; got p_min={}, p0={}, p_max={}",
+            curve.p_min,
+            grid.p0,
+            curve.p_max
+        ));
+    }
+    let (s_inf, p_inf) = curve.inflection();
+    let rep = verify_logistic(&curve, bins)?;
+    if args.verbose {
+        info!(
+            args,
+            "[{}] bins={} p_min={:.6} p_max={:.6} k={:.8} s_mid={:.2}",
+            curve.name(),
+            bins,
+            curve.p_min,
+            curve.p_max,
+            curve.k,
+            curve.s_mid
+        );
+        if let Some(line) = &fit_line {
+            info!(args, "  {}", line);
+        }
+        info!(args, "  Inflection: S={:.6}, P={:.12}", s_inf, p_inf);
+        info!(
+            args,
+            "  Cumulative supply at n={}: {:.6}",
             bins,
-            &format!("{}/price_vs_supply.png", &args.out_dir),
+            curve.cumulative_supply(bins)
+        );
+        info!(
+            args,
+            "  sumS={:.6} S(P_{})-S(P_0)={:.6} rel_err={:.3e} monotone={} (non-strict steps: {})",
+            rep.supply_sum,
+            bins - 1,
+            rep.supply_closed.unwrap(),
+            rep.rel_err_supply.unwrap(),
+            rep.monotone_ok,
+            rep.non_strict_steps
+        );
+        info!(args, "  Allowlist size: {}", policy.allowlist_len());
+        info!(
+            args,
+            "  Launch surcharge: τ(0s)={:.1}% → τ({:.0}s)={:.1}%",
+            policy.tau(0.0),
+            policy.ramp_secs,
+            policy.tau(policy.ramp_secs)
+        );
+    }
+
+    let mut mode_lines = vec![
+        format!("Mode: {}", curve.name()),
+        format!("Bins: {} (source: {})", bins, bin_source.label()),
+        format!("Total supply: {:.6}", curve.cumulative_supply(bins)),
+        format!("Inflection: S={:.6}, P={:.12}", s_inf, p_inf),
+    ];
+    mode_lines.extend(fit_line);
+    if args.accuracy_sweep {
+        write_accuracy_csv(&args.out_dir, &curve, bins)
+            .with_context(|| format!("failed to write {}/accuracy.csv", args.out_dir))?;
+    }
+    let (mut result, mut warnings) = if args.geometric_from_logistic {
+        let fit = Geometric::best_fit_to(&curve, bins)?;
+        let peak = (0..bins)
+            .map(|i| curve.delta_x_of_bin(i))
+            .fold(0.0, f64::max);
+        let line = format!(
+            "Geometric fit to {}: θ={:.6} R0={:.6} RMS ΔX residual={:.6e} max bin error={:.6e} ({:.3}% of peak ΔX)",
+            curve.name(),
+            fit.curve.theta,
+            fit.curve.r0_quote,
+            fit.rms_residual,
+            fit.max_bin_error,
+            fit.max_bin_error / peak * 100.0
+        );
+        if args.verbose {
+            info!(args, "  {}", line);
+        }
+        mode_lines[0] = format!("Mode: {}", fit.curve.name());
+        mode_lines[2] = format!("Total supply: {:.6}", fit.curve.s_n_closed(bins));
+        mode_lines.push(line);
+        finish_run(
+            args,
+            &fit.curve,
+            Resolved {
+                config: CurveConfig::Geometric(fit.curve),
+                bin_source: bin_source.label(),
+            },
+            bins,
+            &fees,
+            &policy,
+            &mode_lines,
+        )?
+    } else if let Some(n) = args.adaptive_bins {
+        let adaptive = AdaptiveLogistic::new(
+            curve,
+            curve.price_of_bin(0),
+            curve.price_of_bin(bins - 1),
+            n,
         )?;
-        plot_tokens_per_bin(
+        // Same price range and bin count on the p0·q^i grid, for comparison
+        let q = adaptive.price_of_bin(n - 1) / adaptive.price_of_bin(0);
+        let uniform = LogisticS {
+            grid: Grid::new(
+                curve.grid.p0,
+                (q.powf(1.0 / (n - 1) as f64) - 1.0) * 10_000.0,
+            ),
+            bins: n,
+            ..curve
+        };
+        let line = format!(
+            "Adaptive bins: P∈[{:.12}, {:.12}], Σ P·ΔX rel err {:.3e} (uniform grid, same bins: {:.3e})",
+            adaptive.price_of_bin(0),
+            adaptive.price_of_bin(n - 1),
+            binned_integration_error(&curve, &adaptive, n),
+            supply_integration_error(&uniform, n)
+        );
+        if args.verbose {
+            info!(args, "  {}", line);
+        }
+        mode_lines[0] = format!("Mode: {}", adaptive.name());
+        mode_lines[1] = format!("Bins: {} (source: adaptive_bins)", n);
+        mode_lines.push(line);
+        finish_run(
+            args,
+            &adaptive,
+            Resolved {
+                config: CurveConfig::AdaptiveLogistic(adaptive.clone()),
+                bin_source: "adaptive_bins",
+            },
+            n,
+            &fees,
+            &policy,
+            &mode_lines,
+        )?
+    } else {
+        let (result, mut warnings) = finish_run(
+            args,
             &curve,
+            Resolved {
+                config: CurveConfig::Logistic(curve),
+                bin_source: bin_source.label(),
+            },
             bins,
-            &format!("{}/tokens_per_bin.png", &args.out_dir),
+            &fees,
+            &policy,
+            &mode_lines,
         )?;
-        plot_fee_vs_vol(
-            |va| fees.total_fee_rate(va),
-            &format!("{}/fee_vs_volatility.png", &args.out_dir),
+        // Only this path emits the uniform p0·q^i grid that the integration error measures
+        let rel_err = supply_integration_error(&curve, bins);
+        if rel_err > args.max_integration_error {
+            let coarse = Warning::CoarseBins {
+                rel_err,
+                max: args.max_integration_error,
+                bin_step_bps: curve.grid.bin_step_bps,
+            };
+            warn(&mut warnings, coarse);
+        }
+        (result, warnings)
+    };
+    if let Some(bin) = rep.asymptote.and_then(|check| check.first_overshoot_bin) {
+        let overshoot = Warning::AsymptoteOvershoot {
+            bin,
+            price: curve.price_of_bin(bin),
+            p_max: curve.p_max,
+        };
+        warn(&mut warnings, overshoot);
+    }
+    result.report = rep;
+    Ok((result, warnings))
+}
+
+/// Smallest distance from the active bin, up to `max_distance`, at which the
+/// uncapped fee at `va` exceeds `max_fee_rate`; the fee only grows with distance
+fn first_fee_capped_distance(fees: &DlmmFeeParams, va: f64, max_distance: i64) -> Option<i64> {
+    let over_cap =
+        |d: i64| fees.base_fee_rate_at_bin(d) + fees.variable_fee_rate(va) > fees.max_fee_rate;
+    if !over_cap(max_distance) {
+        return None;
+    }
+    let (mut lo, mut hi) = (0, max_distance);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if over_cap(mid) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    Some(lo)
+}
+
+/// Writes a run's outputs and builds its [`RunResult`] from the written totals
+fn finish_run<C: Curve + Clone>(
+    args: &Args,
+    curve: &C,
+    resolved: Resolved,
+    bins: i64,
+    fees: &DlmmFeeParams,
+    policy: &LaunchPhasePolicy,
+    mode_lines: &[String],
+) -> Result<(RunResult, Vec<Warning>)> {
+    let written = write_outputs(args, curve, resolved, bins, fees, policy, mode_lines)?;
+    let result = RunResult {
+        curve_name: curve.name().to_string(),
+        bins,
+        total_supply: written.total_supply,
+        total_revenue: written.total_revenue,
+        report: schedule_report(curve, bins, written.total_supply),
+        out_dir: args.out_dir.clone(),
+    };
+    Ok((result, written.warnings))
+}
+
+/// The curve-agnostic verifier checks over `[first_bin, bins)`, for curves
+/// without a closed form
+fn schedule_report<C: Curve>(curve: &C, bins: i64, supply_sum: f64) -> Report {
+    let (monotone_ok, non_strict_steps) = check_monotone(curve, bins);
+    let (convex_ok, concave_ok) = check_allocation_shape(curve, bins);
+    Report {
+        bins,
+        supply_sum,
+        supply_closed: None,
+        rel_err_supply: None,
+        monotone_ok,
+        non_strict_steps,
+        revenue_monotone_ok: check_revenue_monotone(curve, bins),
+        convex_ok,
+        concave_ok,
+        shape: AllocationShape::from_checks(convex_ok, concave_ok),
+        allocation: allocation_stats(curve, bins),
+        asymptote: None,
+        allocation_ratio: None,
+        revenue_closed: None,
+        rel_err_revenue: None,
+    }
+}
+
+fn run_step(
+    args: &Args,
+    grid: Grid,
+    fees: DlmmFeeParams,
+    policy: LaunchPhasePolicy,
+) -> Result<(RunResult, Vec<Warning>)> {
+    let (curve, bins, source, detail) = if let Some(path) = &args.fdv_csv {
+        let points = read_csv_rows(path)?;
+        let fit = curve_for_fdv_path(grid, &points).with_context(|| format!("in {}", path))?;
+        if fit.bins > args.max_bins {
+            return Err(anyhow!(
+                "{} bins exceeds --max-bins {}; raise --max-bins if this is intended",
+                fit.bins,
+                args.max_bins
+            ));
+        }
+        let line = format!(
+            "Tiers from {} FDV points in {}: max grid snapping error {:.3e}",
+            points.len(),
+            path,
+            fit.max_snap_error
+        );
+        (fit.curve, fit.bins, "fdv_csv", line)
+    } else {
+        let (bins, bin_source) = resolve_bins(args, &grid, "step")?;
+        let tiers = args
+            .tiers
+            .as_ref()
+            .ok_or_else(|| anyhow!("step: need [[tiers]] in --config, or --fdv-csv"))?;
+        let tiers = tiers.iter().map(|t| (t.start_bin, t.delta_x)).collect();
+        let curve = StepCurve::new(grid, tiers, bins).context("in the config tiers")?;
+        let line = format!("Tiers from {}", args.config.as_deref().unwrap_or("config"));
+        (curve, bins, bin_source.label(), line)
+    };
+    let total = curve.cumulative_supply(bins);
+    if args.verbose {
+        info!(
+            args,
+            "[{}] bins={} tiers={} supply={:.6}",
+            curve.name(),
+            bins,
+            curve.tiers.len(),
+            total
+        );
+        info!(args, "  {}", detail);
+    }
+    let mode_lines = [
+        format!("Mode: {}, {} tiers", curve.name(), curve.tiers.len()),
+        format!("Bins: {} (source: {})", bins, source),
+        detail,
+        format!("Total supply: {:.6}", total),
+    ];
+    finish_run(
+        args,
+        &curve,
+        Resolved {
+            config: CurveConfig::Step(curve.clone()),
+            bin_source: source,
+        },
+        bins,
+        &fees,
+        &policy,
+        &mode_lines,
+    )
+}
+
+fn run_exponential(
+    args: &Args,
+    grid: Grid,
+    fees: DlmmFeeParams,
+    policy: LaunchPhasePolicy,
+) -> Result<(RunResult, Vec<Warning>)> {
+    let (bins, bin_source) = resolve_bins(args, &grid, "exponential")?;
+    let b = args
+        .exp_b
+        .ok_or_else(|| anyhow!("exponential: need --exp-b"))?;
+    if !b.is_finite() {
+        return Err(anyhow!("exp_b must be finite (got {})", b));
+    }
+    let mut curve = Exponential { grid, a: 0.0, b };
+    curve.a = match (args.exp_a, args.target_supply) {
+        (Some(a), _) => a,
+        (None, Some(target_s)) => curve.solve_a_from_supply(target_s, bins),
+        (None, None) => return Err(anyhow!("exponential: need --exp-a or --target-supply")),
+    };
+    let total = curve.cumulative_supply(bins);
+    if !(curve.a.is_finite() && curve.a > 0.0 && total.is_finite()) {
+        return Err(anyhow!(
+            "exponential: need finite a > 0 and a finite total over {} bins (got a={}, supply={})",
+            bins,
+            curve.a,
+            total
+        ));
+    }
+    if args.verbose {
+        info!(
+            args,
+            "[{}] bins={} a={:.6} b={} supply={:.6}",
+            curve.name(),
+            bins,
+            curve.a,
+            curve.b,
+            total
+        );
+    }
+    let mode_lines = [
+        format!("Mode: {}, a={}, b={}", curve.name(), curve.a, curve.b),
+        format!("Bins: {} (source: {})", bins, bin_source.label()),
+        format!("Total supply: {:.6}", total),
+    ];
+    finish_run(
+        args,
+        &curve,
+        Resolved {
+            config: CurveConfig::Exponential(curve),
+            bin_source: bin_source.label(),
+        },
+        bins,
+        &fees,
+        &policy,
+        &mode_lines,
+    )
+}
+
+fn run_linear(
+    args: &Args,
+    grid: Grid,
+    fees: DlmmFeeParams,
+    policy: LaunchPhasePolicy,
+) -> Result<(RunResult, Vec<Warning>)> {
+    let (bins, bin_source) = resolve_bins(args, &grid, "linear")?;
+    let base = args.base.ok_or_else(|| anyhow!("linear: need --base"))?;
+    let slope = args.slope.unwrap_or(0.0);
+    let curve = Linear::new(grid, base, slope, bins).context("in --base and --slope")?;
+    let rep = verify_linear(&curve, bins)?;
+    if args.verbose {
+        info!(
+            args,
+            "[{}] bins={} sumS={:.6} closed={:.6} rel_err={:.3e} monotone={}",
+            curve.name(),
+            rep.bins,
+            rep.supply_sum,
+            rep.supply_closed.unwrap_or_default(),
+            rep.rel_err_supply.unwrap_or_default(),
+            rep.monotone_ok
+        );
+    }
+    let mode_lines = [
+        format!("Mode: {}, base={}, slope={}", curve.name(), base, slope),
+        format!("Bins: {} (source: {})", bins, bin_source.label()),
+        format!("Total supply: {:.6}", curve.cumulative_supply(bins)),
+    ];
+    let (mut result, warnings) = finish_run(
+        args,
+        &curve,
+        Resolved {
+            config: CurveConfig::Linear(curve),
+            bin_source: bin_source.label(),
+        },
+        bins,
+        &fees,
+        &policy,
+        &mode_lines,
+    )?;
+    result.report = rep;
+    Ok((result, warnings))
+}
+
+/// Reads the rows of a headered CSV, skipping `#` comment lines.
+fn read_csv_rows<T: DeserializeOwned>(path: &str) -> Result<Vec<T>> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .comment(Some(b'#'))
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .with_context(|| format!("failed to read {}", path))?;
+    let mut rows = Vec::new();
+    for rec in rdr.deserialize() {
+        rows.push(rec.with_context(|| format!("failed to parse {}", path))?);
+    }
+    Ok(rows)
+}
+
+/// Writes the schedule and charts, applying `--exclude-bins` when set, plus the
+/// schedule.json sidecar describing `resolved` when requested; with
+/// `--print-config` prints the resolved [`Scenario`] instead of writing them.
+/// Returns the `supply_cum`/`revenue_cum` totals and any warnings raised.
+fn write_outputs<C: Curve + Clone>(
+    args: &Args,
+    c: &C,
+    resolved: Resolved,
+    bins: i64,
+    fees: &DlmmFeeParams,
+    policy: &LaunchPhasePolicy,
+    mode_lines: &[String],
+) -> Result<Written> {
+    if let Some(base) = args.bin_id_base {
+        check_bin_ids(base, args.bin_id_bits, c.first_bin(), bins)?;
+    }
+    let mut warnings = Vec::new();
+    if let Some(gap) = min_price_gap(c, bins).filter(|g| g.is_degenerate()) {
+        warn(&mut warnings, Warning::DegeneratePriceGap(gap));
+    }
+    let max_distance = c.first_bin().unsigned_abs().max((bins - 1).unsigned_abs());
+    if let Some(distance) = first_fee_capped_distance(fees, args.vol_accum, max_distance as i64) {
+        let saturated = Warning::FeeCapSaturated {
+            distance,
+            va: args.vol_accum,
+            cap: fees.max_fee_rate,
+        };
+        warn(&mut warnings, saturated);
+    }
+    if let Some(threshold) = args.dust_threshold {
+        let dust = count_dust_bins(c, bins, threshold);
+        let rows = bins - c.first_bin();
+        let pct = 100.0 * dust as f64 / rows.max(1) as f64;
+        info!(
+            args,
+            "Dust bins (ΔX < {}): {} of {} ({:.2}%)", threshold, dust, rows, pct
+        );
+        if dust > 0 {
+            warn(
+                &mut warnings,
+                Warning::DustBins {
+                    dust,
+                    bins: rows,
+                    threshold,
+                },
+            );
+        }
+        if let Some(max_pct) = args.max_dust_pct {
+            if pct > max_pct {
+                return Err(anyhow!(
+                    "{:.2}% of bins are dust (ΔX < {}), above --max-dust-pct {}",
+                    pct,
+                    threshold,
+                    max_pct
+                ));
+            }
+        }
+    }
+    let mut lines = mode_lines.to_vec();
+    let masked = args.exclude_bins.clone().map(|excluded| MaskedCurve {
+        inner: c.clone(),
+        excluded,
+    });
+    if let Some(masked) = &masked {
+        let removed = c.cumulative_supply(bins) - masked.cumulative_supply(bins);
+        if args.verbose {
+            info!(
+                args,
+                "  Excluded bins {}..{}: removed ΔX={:.6}",
+                masked.excluded.start,
+                masked.excluded.end,
+                removed
+            );
+        }
+        lines.push(format!(
+            "Excluded bins: {}..{} (ΔX removed: {:.6})",
+            masked.excluded.start, masked.excluded.end, removed
+        ));
+    }
+    let mut format = ScheduleFormat::new(args, lines);
+    format.guard_by_bin = matches!(resolved.config, CurveConfig::Geometric(_));
+    // Read before writing: the old schedule may be the one about to be replaced
+    let old_schedule = match &args.diff {
+        Some(path) => Some((path, read_schedule_values(path)?)),
+        None => None,
+    };
+    if args.print_config {
+        let scenario = Scenario {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            schedule: Sidecar {
+                curve: resolved.config,
+                bins,
+                bin_source: resolved.bin_source.to_string(),
+                exclude_bins: args.exclude_bins.clone(),
+                fees: *fees,
+                policy: policy.clone(),
+                format,
+            },
+        };
+        println!("{}", serde_json::to_string_pretty(&scenario)?);
+        let (total_supply, total_revenue) = match &masked {
+            Some(masked) => schedule_totals(masked, bins),
+            None => schedule_totals(c, bins),
+        };
+        return Ok(Written {
+            total_supply,
+            total_revenue,
+            warnings,
+        });
+    }
+    let (total_supply, total_revenue) = match &masked {
+        Some(masked) => {
+            write_schedule_and_plots(args, masked, bins, fees, policy, &format, &mut warnings)?
+        }
+        None => write_schedule_and_plots(args, c, bins, fees, policy, &format, &mut warnings)?,
+    };
+    if let Some((path, old)) = &old_schedule {
+        let new = match &masked {
+            Some(masked) => schedule_values(masked, bins),
+            None => schedule_values(c, bins),
+        };
+        report_schedule_diff(args, path, old, &new)?;
+    }
+    if args.sidecar || args.round_trip_test {
+        let sidecar = Sidecar {
+            curve: resolved.config,
+            bins,
+            bin_source: resolved.bin_source.to_string(),
+            exclude_bins: args.exclude_bins.clone(),
+            fees: *fees,
+            policy: policy.clone(),
+            format,
+        };
+        let path = format!("{}/schedule.json", args.out_dir);
+        let write = || -> Result<()> {
+            let mut file = File::create(&path)?;
+            serde_json::to_writer_pretty(&mut file, &sidecar)?;
+            writeln!(file)?;
+            Ok(())
+        };
+        write().with_context(|| format!("failed to write {}", path))?;
+        if args.round_trip_test {
+            round_trip_test(&args.out_dir, &sidecar)?;
+            if args.verbose {
+                info!(
+                    args,
+                    "Round-trip test: schedule.csv regenerated from schedule.json"
+                );
+            }
+        }
+    }
+    Ok(Written {
+        total_supply,
+        total_revenue,
+        warnings,
+    })
+}
+
+/// Re-reads schedule.json, checks it matches `written`, regenerates the schedule
+/// from it and compares every cell against schedule.csv on disk
+fn round_trip_test(out_dir: &str, written: &Sidecar) -> Result<()> {
+    let json_path = format!("{}/schedule.json", out_dir);
+    let text = std::fs::read_to_string(&json_path)
+        .with_context(|| format!("failed to read {}", json_path))?;
+    let loaded: Sidecar =
+        serde_json::from_str(&text).with_context(|| format!("failed to parse {}", json_path))?;
+    let changed: Vec<&str> = [
+        ("curve", loaded.curve != written.curve),
+        ("bins", loaded.bins != written.bins),
+        ("exclude_bins", loaded.exclude_bins != written.exclude_bins),
+        ("fees", loaded.fees != written.fees),
+        ("policy", loaded.policy != written.policy),
+        ("format", loaded.format != written.format),
+    ]
+    .into_iter()
+    .filter_map(|(name, differs)| differs.then_some(name))
+    .collect();
+    if !changed.is_empty() {
+        return Err(anyhow!(
+            "round-trip: {} did not preserve {}",
+            json_path,
+            changed.join(", ")
+        ));
+    }
+
+    let csv_path = format!("{}/schedule.csv", out_dir);
+    let on_disk = std::fs::read_to_string(&csv_path)
+        .with_context(|| format!("failed to read {}", csv_path))?;
+    let regenerated = loaded.regenerate()?;
+    let (disk_comments, disk_rows) =
+        split_schedule(&on_disk).with_context(|| format!("failed to parse {}", csv_path))?;
+    let (regen_comments, regen_rows) =
+        split_schedule(&regenerated).context("failed to parse the regenerated schedule")?;
+    if let Some((disk, regen)) = disk_comments
+        .iter()
+        .zip(&regen_comments)
+        .find(|(disk, regen)| disk != regen)
+    {
+        return Err(anyhow!(
+            "round-trip: {} comment {:?} on disk, {:?} regenerated",
+            csv_path,
+            disk,
+            regen
+        ));
+    }
+    if disk_comments.len() != regen_comments.len() {
+        return Err(anyhow!(
+            "round-trip: {} has {} comment lines, regenerated {}",
+            csv_path,
+            disk_comments.len(),
+            regen_comments.len()
+        ));
+    }
+    for (a, b) in disk_rows.iter().zip(&regen_rows) {
+        let line = a.position().map_or(0, |p| p.line());
+        if a.len() != b.len() {
+            return Err(anyhow!(
+                "round-trip: {} line {} has {} cells, regenerated {}",
+                csv_path,
+                line,
+                a.len(),
+                b.len()
+            ));
+        }
+        if let Some(col) = a.iter().zip(b).position(|(x, y)| x != y) {
+            return Err(anyhow!(
+                "round-trip: {} line {} cell {}: {:?} on disk, {:?} regenerated",
+                csv_path,
+                line,
+                col + 1,
+                &a[col],
+                &b[col]
+            ));
+        }
+    }
+    if disk_rows.len() != regen_rows.len() {
+        return Err(anyhow!(
+            "round-trip: {} has {} rows, regenerated {}",
+            csv_path,
+            disk_rows.len(),
+            regen_rows.len()
+        ));
+    }
+    Ok(())
+}
+
+/// A schedule's `#` comment lines, and its data section (header included) read
+/// with the same `#` comment handling as [`read_csv_rows`]
+fn split_schedule(text: &str) -> Result<(Vec<&str>, Vec<csv::StringRecord>)> {
+    let comments = text.lines().filter(|l| l.starts_with('#')).collect();
+    let rows = csv::ReaderBuilder::new()
+        .comment(Some(b'#'))
+        .has_headers(false)
+        .from_reader(text.as_bytes())
+        .into_records()
+        .collect::<Result<_, _>>()?;
+    Ok((comments, rows))
+}
+
+/// Errors unless `base + i` fits a signed `bits`-wide integer for every bin in `[lo, hi)`
+fn check_bin_ids(base: i64, bits: u32, lo: i64, hi: i64) -> Result<()> {
+    if !(2..=64).contains(&bits) {
+        return Err(anyhow!("bin_id_bits must be in [2, 64] (got {})", bits));
+    }
+    let max = i64::MAX >> (64 - bits);
+    let min = -max - 1;
+    for i in [lo, hi - 1] {
+        match base.checked_add(i) {
+            Some(id) if (min..=max).contains(&id) => {}
+            _ => {
+                return Err(anyhow!(
+                    "bin_id {} + {} overflows a {}-bit signed id (range {}..={})",
+                    base,
+                    i,
+                    bits,
+                    min,
+                    max
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_schedule_and_plots<C: Curve>(
+    args: &Args,
+    c: &C,
+    bins: i64,
+    fees: &DlmmFeeParams,
+    policy: &LaunchPhasePolicy,
+    format: &ScheduleFormat,
+    warnings: &mut Vec<Warning>,
+) -> Result<(f64, f64)> {
+    let totals = match args.format {
+        OutputFormat::Csv => write_schedule_file(args, c, bins, fees, policy, format)?,
+        OutputFormat::Json => write_schedule_json_file(args, c, bins, fees, policy, format)?,
+        OutputFormat::Both => {
+            write_schedule_json_file(args, c, bins, fees, policy, format)?;
+            write_schedule_file(args, c, bins, fees, policy, format)?
+        }
+        OutputFormat::Solidity => write_schedule_sol(args, c, bins, fees.bin_step_bps, warnings)?,
+        OutputFormat::Parquet => write_schedule_parquet_file(args, c, bins, fees, policy, format)?,
+    };
+    if let Some(n) = args.preview {
+        let table = format_preview(&preview_rows(c, bins, fees, format.vol_accum), n);
+        info!(args, "{}", table.trim_end());
+    }
+    if args.draw && !args.output_stdout {
+        let chart = PriceChartOptions {
+            invert_price: args.invert_prices,
+            normalize_supply: args.normalize_supply,
+            log_price: args.log_price,
+            max_points: args.max_plot_points,
+        };
+        plot_price_vs_supply_with(c, bins, &chart_path(args, "price_vs_supply"), &chart)?;
+        plot_tokens_per_bin(
+            c,
+            bins,
+            args.max_plot_points,
+            &chart_path(args, "tokens_per_bin"),
         )?;
+        plot_fee_components(fees, &chart_path(args, "fee_vs_volatility"))?;
+        if args.depth_chart {
+            plot_depth_chart(
+                &depth_chart_sampled(c, 0, bins, args.max_plot_points),
+                c.price_of_bin(0),
+                args.max_plot_points,
+                &chart_path(args, "depth_chart"),
+            )?;
+        }
+    }
+    if let Some(path) = &args.trades {
+        let trades = read_csv_rows(path)?;
+        let out = format!("{}/simulation.csv", args.out_dir);
+        let write = || -> Result<()> {
+            let mut wtr = csv::Writer::from_path(&out)?;
+            for o in simulate_trades(c, bins, fees, format.vol_accum, policy, &trades) {
+                wtr.serialize(o)?;
+            }
+            wtr.flush()?;
+            Ok(())
+        };
+        write().with_context(|| format!("failed to write {}", out))?;
+    }
+    if args.montecarlo {
+        let config = MonteCarloConfig {
+            runs: args.mc_runs,
+            horizon_secs: policy.ramp_secs,
+            arrival_rate: args.mc_arrival_rate,
+            median_quote: args.mc_median_quote,
+            size_sigma: args.mc_size_sigma,
+            allowlisted_share: args.mc_allowlisted_share,
+            va: format.vol_accum,
+        };
+        let stats = simulate(c, bins, fees, policy, &config, args.seed)?;
+        let out = format!("{}/montecarlo.csv", args.out_dir);
+        let write = || -> Result<()> {
+            let mut wtr = csv::Writer::from_path(&out)?;
+            wtr.write_record(["metric", "mean", "min", "p5", "p50", "p95", "max"])?;
+            for (metric, d) in stats.metrics() {
+                wtr.serialize((metric, d.mean, d.min, d.p5, d.p50, d.p95, d.max))?;
+            }
+            wtr.flush()?;
+            Ok(())
+        };
+        write().with_context(|| format!("failed to write {}", out))?;
+        if args.verbose {
+            info!(
+                args,
+                "Monte Carlo (seed {}): {} launches, {:.1} buys each, fee revenue p50={:.6} p95={:.6}",
+                args.seed,
+                stats.runs,
+                stats.mean_trades,
+                stats.fee_revenue.p50,
+                stats.fee_revenue.p95
+            );
+        }
+    }
+    if let Some(volume) = args.daily_volume.filter(|_| args.apr) {
+        let out = format!("{}/apr.csv", args.out_dir);
+        let write = || -> Result<()> {
+            let mut wtr = csv::Writer::from_path(&out)?;
+            wtr.write_record(["bin", "tvl", "apr"])?;
+            for (i, apr) in estimate_bin_apr(c, bins, fees, format.vol_accum, volume) {
+                let tvl = c.price_of_bin(i) * c.delta_x_of_bin(i);
+                let apr = apr.map(|a| a.to_string()).unwrap_or_default();
+                wtr.write_record([i.to_string(), tvl.to_string(), apr])?;
+            }
+            wtr.flush()?;
+            Ok(())
+        };
+        write().with_context(|| format!("failed to write {}", out))?;
+    }
+    if args.repl {
+        let stdin = std::io::stdin();
+        run_repl(c, bins, stdin.lock(), std::io::stdout().lock())?;
+    }
+    Ok(totals)
+}
+
+/// Writes schedule.csv (resuming it with `--resume`) and checks it with `--verify-output`
+fn write_schedule_file<C: Curve>(
+    args: &Args,
+    c: &C,
+    bins: i64,
+    fees: &dyn FeeModel,
+    policy: &LaunchPhasePolicy,
+    format: &ScheduleFormat,
+) -> Result<(f64, f64)> {
+    let report = |current: i64, total: i64| {
+        eprint!("\r{:>3}%", current * 100 / total.max(1));
+        if current == total {
+            eprintln!();
+        }
+    };
+    let progress: Option<&dyn Fn(i64, i64)> = if args.progress { Some(&report) } else { None };
+    if args.output_stdout {
+        let rows = RowLoop {
+            progress,
+            ..RowLoop::default()
+        };
+        let stdout = BufWriter::new(std::io::stdout().lock());
+        return write_schedule_csv(stdout, format, c, bins, fees, policy, rows)
+            .context("failed to write the schedule to stdout");
+    }
+    if let Some(rows_per_file) = args.split_output {
+        let open = |k: usize| -> Result<File> {
+            let path = format!("{}/schedule_{}.csv", args.out_dir, k);
+            File::create(&path).with_context(|| format!("failed to write {}", path))
+        };
+        let rows = RowLoop {
+            split: Some(SplitOutput {
+                rows_per_file,
+                open: &open,
+            }),
+            progress,
+            ..RowLoop::default()
+        };
+        let totals = write_schedule_csv(open(0)?, format, c, bins, fees, policy, rows)
+            .context("failed to write the schedule shards")?;
+        write_schedule_index(&args.out_dir, c.first_bin(), bins, rows_per_file)?;
+        return Ok(totals);
+    }
+    let path = format!("{}/schedule.csv", args.out_dir);
+    let write = || -> Result<(f64, f64)> {
+        if args.resume && Path::new(&path).exists() {
+            let (file, resume) = open_for_resume(&path, format, c.first_bin())?;
+            if args.verbose {
+                info!(args, "Resuming {} at bin {}", path, resume.next_bin);
+            }
+            let rows = RowLoop {
+                resume: Some(resume),
+                progress,
+                ..RowLoop::default()
+            };
+            return write_schedule_csv(file, format, c, bins, fees, policy, rows);
+        }
+        let rows = RowLoop {
+            progress,
+            ..RowLoop::default()
+        };
+        write_schedule_csv(File::create(&path)?, format, c, bins, fees, policy, rows)
+    };
+    let totals = write().with_context(|| format!("failed to write {}", path))?;
+    if args.verify_output {
+        let rel = reconcile_schedule(c, bins, &format!("{}/schedule.csv", args.out_dir))?;
+        if args.verbose {
+            info!(
+                args,
+                "Output reconciliation: revenue_cum rel err {:.3e}", rel
+            );
+        }
+    }
+    Ok(totals)
+}
+
+/// Changed bins `--diff` prints before deferring to diff.csv
+const DIFF_PRINT_ROWS: usize = 10;
+
+/// `--diff`: prints how `new` differs from the schedule at `old_path` and, unless
+/// the schedule went to stdout, writes every bin's differences to diff.csv
+fn report_schedule_diff(
+    args: &Args,
+    old_path: &str,
+    old: &[BinValues],
+    new: &[BinValues],
+) -> Result<()> {
+    let (diffs, summary) = diff_schedules(old, new);
+    info!(
+        args,
+        "Diff vs {}: {} bins matched, {} only in old, {} only in new, {} changed",
+        old_path,
+        summary.matched,
+        summary.only_old,
+        summary.only_new,
+        summary.changed
+    );
+    info!(
+        args,
+        "  max |Δprice|={:.6e}, max |ΔΔX|={:.6e}, max |Δrevenue|={:.6e}, supply change={:.6}",
+        summary.max_abs_price_change,
+        summary.max_abs_delta_x_change,
+        summary.max_abs_revenue_change,
+        summary.supply_change
+    );
+    let side = |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{:.6e}", v));
+    for d in diffs.iter().filter(|d| d.is_change()).take(DIFF_PRINT_ROWS) {
+        info!(
+            args,
+            "  bin {}: price {} → {}, ΔX {} → {}, revenue {} → {}",
+            d.bin,
+            side(d.price_old),
+            side(d.price_new),
+            side(d.delta_x_old),
+            side(d.delta_x_new),
+            side(d.revenue_old),
+            side(d.revenue_new)
+        );
+    }
+    let written = !args.output_stdout;
+    if summary.changed > DIFF_PRINT_ROWS {
+        info!(
+            args,
+            "  … {} more changed bins{}",
+            summary.changed - DIFF_PRINT_ROWS,
+            if written { " in diff.csv" } else { "" }
+        );
+    }
+    if written {
+        let path = format!("{}/diff.csv", args.out_dir);
+        let write = || -> Result<()> {
+            let mut wtr = csv::Writer::from_path(&path)?;
+            for d in &diffs {
+                wtr.serialize(d)?;
+            }
+            wtr.flush()?;
+            Ok(())
+        };
+        write().with_context(|| format!("failed to write {}", path))?;
     }
     Ok(())
 }
 
-fn write_schedule_csv_geometric(
-    out_dir: &str,
-    c: &Geometric,
+/// Writes schedule.rows.json for `--format json|both` (to stdout with `--output-stdout`)
+fn write_schedule_json_file<C: Curve>(
+    args: &Args,
+    c: &C,
     bins: i64,
-    fees: DlmmFeeParams,
-    va: f64,
+    fees: &dyn FeeModel,
     policy: &LaunchPhasePolicy,
-    price_guard_bps: Option<f64>,
-) -> Result<()> {
-    let file_path = format!("{}/schedule.csv", out_dir);
-    let mut file = File::create(&file_path)?;
-
-    // Write metadata header
-    writeln!(file, "# DLMM Bonding Curve Schedule")?;
-    writeln!(file, "# Mode: Geometric, θ={}, R₀={}", c.theta, c.r0_quote)?;
-    writeln!(
-        file,
-        "# Growth factor g={:.12}, Decay factor r={:.12}",
-        c.g(),
-        c.r()
-    )?;
-    writeln!(file, "# Volatility accumulator: {}", va)?;
+    format: &ScheduleFormat,
+) -> Result<(f64, f64)> {
+    if args.output_stdout {
+        let stdout = BufWriter::new(std::io::stdout().lock());
+        return write_schedule_json(stdout, format, c, bins, fees, policy)
+            .context("failed to write the schedule to stdout");
+    }
+    let path = format!("{}/schedule.rows.json", args.out_dir);
+    let write = || -> Result<(f64, f64)> {
+        let file = BufWriter::new(File::create(&path)?);
+        write_schedule_json(file, format, c, bins, fees, policy)
+    };
+    write().with_context(|| format!("failed to write {}", path))
+}
 
-    // Launch policy configuration
-    writeln!(
-        file,
-        "# Launch policy: allowlist={} addresses",
-        policy.allowlist.len()
-    )?;
-    writeln!(
-        file,
-        "# Surcharge ramp: {:.1}% → {:.1}% over {:.0}s",
-        policy.tau_start_pct, policy.tau_end_pct, policy.ramp_secs
-    )?;
+/// Writes Schedule.sol for `--format solidity`, warning about chunks over the
+/// contract size limit
+fn write_schedule_sol<C: Curve>(
+    args: &Args,
+    c: &C,
+    bins: i64,
+    bin_step_bps: f64,
+    warnings: &mut Vec<Warning>,
+) -> Result<(f64, f64)> {
+    let path = if args.output_stdout {
+        "stdout".to_string()
+    } else {
+        format!("{}/Schedule.sol", args.out_dir)
+    };
+    let opts = SolidityOptions {
+        decimals: args.decimals,
+        chunk_bins: args.solidity_chunk_bins,
+    };
+    let write = || -> Result<_> {
+        let out: Box<dyn Write> = if args.output_stdout {
+            Box::new(BufWriter::new(std::io::stdout().lock()))
+        } else {
+            Box::new(BufWriter::new(File::create(&path)?))
+        };
+        write_solidity(out, c, bins, bin_step_bps, &opts)
+    };
+    let report = write().with_context(|| format!("failed to write {}", path))?;
+    for (chunk, &bytes) in report.chunk_bytes.iter().enumerate() {
+        if bytes > CONTRACT_SIZE_LIMIT {
+            warn(warnings, Warning::ContractSize { chunk, bytes });
+        }
+    }
+    if args.verbose {
+        info!(
+            args,
+            "Wrote {}: {} chunk(s), largest ≈{} bytes",
+            path,
+            report.chunks.len(),
+            report.chunk_bytes.iter().max().unwrap_or(&0)
+        );
+    }
+    Ok((report.total_supply, report.total_revenue))
+}
 
-    // Optional price-guard metadata
-    if let Some(impact_bps) = price_guard_bps {
-        let bins_to_report = [0, bins / 2, bins.saturating_sub(1)];
-        for b in bins_to_report {
-            let p = c.price_of_bin(b);
-            writeln!(file, "# Guard @ bin {} (P={:.12}):", b, p)?;
-            writeln!(
-                file,
-                "#   Min X→Y: {:.12}",
-                DlmmFeeParams::min_price_sell_x_for_y(p, impact_bps)
-            )?;
-            writeln!(
-                file,
-                "#   Min Y→X: {:.12}",
-                DlmmFeeParams::min_price_sell_y_for_x(p, impact_bps)
-            )?;
+/// Where a `--resume`d schedule continues: the next bin and the cumulative columns
+/// of the last complete row.
+///
+/// Only the rounded cumulative values survive in the file, so the Neumaier
+/// compensation restarts from zero at the seam; the resumed totals can differ from
+/// an uninterrupted run in the last bit or so.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ResumePoint {
+    next_bin: i64,
+    supply_cum: f64,
+    revenue_cum: f64,
+    surcharge_revenue_cum: f64,
+}
+impl ResumePoint {
+    /// A fresh schedule starting at `first_bin`
+    fn at(first_bin: i64) -> Self {
+        ResumePoint {
+            next_bin: first_bin,
+            supply_cum: 0.0,
+            revenue_cum: 0.0,
+            surcharge_revenue_cum: 0.0,
         }
     }
-    writeln!(file)?;
+}
 
-    // Create CSV writer (write one header row)
-    let mut wtr = csv::WriterBuilder::new()
-        .has_headers(false)
-        .from_writer(file);
-    // explicit header
-    wtr.write_record([
+/// How [`write_schedule_csv`] drives its row loop
+#[derive(Clone, Copy, Default)]
+struct RowLoop<'a> {
+    /// Append rows from this point instead of writing the metadata and header first
+    resume: Option<ResumePoint>,
+    /// Called with `(current, total)` roughly every 1% of bins and once at completion
+    progress: Option<&'a dyn Fn(i64, i64)>,
+    /// Start a new file every `rows_per_file` rows (`--split-output`)
+    split: Option<SplitOutput<'a>>,
+}
+
+/// Shards [`write_schedule_csv`] output across files. Every shard gets the
+/// metadata and header; the cumulative sums run on across shards untouched.
+#[derive(Clone, Copy)]
+struct SplitOutput<'a> {
+    rows_per_file: i64,
+    /// Opens shard k ≥ 1; the writer passed to [`write_schedule_csv`] is shard 0
+    open: &'a dyn Fn(usize) -> Result<File>,
+}
+
+/// One file of a `--split-output` schedule, as listed in schedule_index.json
+#[derive(Serialize)]
+struct ScheduleShard {
+    file: String,
+    first_bin: i64,
+    last_bin: i64,
+    rows: i64,
+}
+
+/// Writes schedule_index.json listing the `--split-output` shards of bins
+/// `[first_bin, bins)` in order
+fn write_schedule_index(
+    out_dir: &str,
+    first_bin: i64,
+    bins: i64,
+    rows_per_file: i64,
+) -> Result<()> {
+    let shards: Vec<ScheduleShard> = (first_bin..bins)
+        .step_by(rows_per_file as usize)
+        .enumerate()
+        .map(|(k, start)| {
+            let end = (start + rows_per_file).min(bins);
+            ScheduleShard {
+                file: format!("schedule_{}.csv", k),
+                first_bin: start,
+                last_bin: end - 1,
+                rows: end - start,
+            }
+        })
+        .collect();
+    let path = format!("{}/schedule_index.json", out_dir);
+    let write = || -> Result<()> {
+        let mut file = File::create(&path)?;
+        let index = serde_json::json!({
+            "rows_per_file": rows_per_file,
+            "shards": shards,
+        });
+        serde_json::to_writer_pretty(&mut file, &index)?;
+        writeln!(file)?;
+        Ok(())
+    };
+    write().with_context(|| format!("failed to write {}", path))
+}
+
+/// Column names of schedule.csv for `format`; later additions go last so existing
+/// columns keep their positions
+fn schedule_columns(format: &ScheduleFormat) -> Vec<&'static str> {
+    let mut header = vec![
         "bin",
         "price",
         "delta_x",
@@ -344,263 +3176,620 @@ fn write_schedule_csv_geometric(
         "fee_base",
         "fee_var",
         "fee_total",
-    ])?;
-
-    // Neumaier compensated sums
-    let mut s_cum = 0.0;
-    let mut s_cmp = 0.0;
-    let mut r_cum = 0.0;
-    let mut r_cmp = 0.0;
-    let fee_b = fees.base_fee_rate();
-    let fee_v = fees.variable_fee_rate(va);
-    let fee_tot = fees.total_fee_rate(va);
-
-    for i in 0..bins {
+    ];
+    if format.invert_prices {
+        header.push("price_inv");
+    }
+    if format.surcharge_at_secs.is_some() {
+        header.push("effective_ask");
+    }
+    if format.account_surcharge {
+        header.extend(["surcharge_revenue_bin", "surcharge_revenue_cum"]);
+    }
+    if format.log_columns {
+        header.extend(["log_price", "log_return_from_prev"]);
+    }
+    if format.bin_id_base.is_some() {
+        header.push("bin_id");
+    }
+    header.push("fee_total_bps");
+    if format.protocol_fee_columns {
+        header.extend(["fee_protocol", "fee_lp"]);
+    }
+    if format.surcharge_at_secs.is_some() {
+        header.push("fee_total_with_surcharge");
+    }
+    header
+}
+
+/// Bytes read from the end of a partial schedule.csv to find its last complete row
+const RESUME_TAIL_BYTES: u64 = 64 * 1024;
+
+/// Opens a partial schedule.csv for `--resume`: checks its columns against `format`,
+/// drops a torn final line, and returns the file positioned for appending together
+/// with where to continue. Only the header and the file's tail are read.
+fn open_for_resume(
+    path: &str,
+    format: &ScheduleFormat,
+    first_bin: i64,
+) -> Result<(File, ResumePoint)> {
+    let columns = schedule_columns(format);
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)?;
+    let mut header_pos = 0;
+    let mut header = None;
+    for line in std::io::BufReader::new(&file).lines() {
+        let line = line?;
+        header_pos += line.len() as u64 + 1;
+        if !line.starts_with('#') && !line.trim().is_empty() {
+            header = Some(line);
+            break;
+        }
+    }
+    let header = header.ok_or_else(|| anyhow!("--resume: {} has no header row", path))?;
+    if header.split(',').ne(columns.iter().copied()) {
+        return Err(anyhow!(
+            "--resume: columns of {} ({}) differ from this run's ({})",
+            path,
+            header,
+            columns.join(",")
+        ));
+    }
+
+    let len = file.metadata()?.len();
+    let tail_pos = len
+        .saturating_sub(RESUME_TAIL_BYTES)
+        .max(header_pos.min(len));
+    file.seek(SeekFrom::Start(tail_pos))?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail)?;
+    // Anything after the last newline is a row cut off mid-write
+    let complete = tail.iter().rposition(|&b| b == b'\n').map_or(0, |n| n + 1);
+    let last = std::str::from_utf8(&tail[..complete])?
+        .lines()
+        .next_back()
+        .filter(|l| !l.trim().is_empty());
+
+    let mut resume = ResumePoint::at(first_bin);
+    if let Some(last) = last {
+        let fields: Vec<&str> = last.split(',').collect();
+        let parse = |name: &str| -> Result<f64> {
+            columns
+                .iter()
+                .position(|c| *c == name)
+                .and_then(|i| fields.get(i))
+                .and_then(|f| f.parse().ok())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "--resume: unreadable {} in last row of {}: {}",
+                        name,
+                        path,
+                        last
+                    )
+                })
+        };
+        resume = ResumePoint {
+            next_bin: parse("bin")? as i64 + 1,
+            supply_cum: parse("supply_cum")?,
+            revenue_cum: parse("revenue_cum")?,
+            surcharge_revenue_cum: if format.account_surcharge {
+                parse("surcharge_revenue_cum")?
+            } else {
+                0.0
+            },
+        };
+    }
+    file.set_len(tail_pos + complete as u64)?;
+    file.seek(SeekFrom::End(0))?;
+    Ok((file, resume))
+}
+
+/// Writes the `schedule.csv` contents to `file` and returns the final `(supply_cum, revenue_cum)`
+fn write_schedule_csv<'w, C: Curve, W: Write + 'w>(
+    mut file: W,
+    format: &ScheduleFormat,
+    c: &C,
+    bins: i64,
+    fees: &dyn FeeModel,
+    policy: &LaunchPhasePolicy,
+    rows: RowLoop<'_>,
+) -> Result<(f64, f64)> {
+    if rows.resume.is_none() {
+        write_schedule_metadata(&mut file, format, c, bins, policy)?;
+    }
+
+    // Create CSV writer (write one header row); boxed so --split-output can swap files
+    let csv_writer =
+        |w: Box<dyn Write + 'w>| csv::WriterBuilder::new().has_headers(false).from_writer(w);
+    let mut wtr = csv_writer(Box::new(file));
+    if rows.resume.is_none() {
+        wtr.write_record(schedule_columns(format))?;
+    }
+    let progress = rows.progress;
+    let start = rows
+        .resume
+        .unwrap_or_else(|| ResumePoint::at(c.first_bin()));
+    let mut row_builder = RowBuilder::new(format, fees, policy, &start);
+    let lo = c.first_bin();
+    let total = bins - lo;
+    let progress_every = (total / 100).max(1);
+
+    for i in start.next_bin.max(lo)..bins {
+        if let Some(split) = rows.split {
+            if i > lo && (i - lo) % split.rows_per_file == 0 {
+                wtr.flush()?;
+                let mut shard = (split.open)(((i - lo) / split.rows_per_file) as usize)?;
+                write_schedule_metadata(&mut shard, format, c, bins, policy)?;
+                wtr = csv_writer(Box::new(shard));
+                wtr.write_record(schedule_columns(format))?;
+            }
+        }
+        if let Some(cb) = progress {
+            if (i - lo) % progress_every == 0 {
+                cb(i - lo, total);
+            }
+        }
+        wtr.serialize(row_builder.row(c, i)?)?;
+    }
+    if let Some(cb) = progress {
+        cb(total, total);
+    }
+    wtr.flush()?;
+    Ok(row_builder.totals())
+}
+
+/// τ(t) in percent for the `--surcharge-at-secs` columns: the trader's
+/// [`LaunchPhasePolicy::effective_tau`] under `--surcharge-address`, else the full
+/// non-allowlisted surcharge
+fn surcharge_pct_at(policy: &LaunchPhasePolicy, format: &ScheduleFormat, t: f64) -> f64 {
+    match &format.surcharge_address {
+        Some(addr) => policy.effective_tau(addr, t),
+        None => policy.tau(t),
+    }
+}
+
+/// Computes schedule rows in bin order, carrying the compensated cumulative columns
+struct RowBuilder<'a> {
+    format: &'a ScheduleFormat,
+    fees: &'a dyn FeeModel,
+    /// Surcharge τ(t)/100 at the chosen time since launch, for a non-allowlisted
+    /// trader or the `--surcharge-address` one
+    surcharge_at: Option<f64>,
+    surcharge_rate: Option<f64>,
+    fee_v: f64,
+    s_cum: CompensatedSum,
+    r_cum: CompensatedSum,
+    sr_cum: CompensatedSum,
+}
+impl<'a> RowBuilder<'a> {
+    fn new(
+        format: &'a ScheduleFormat,
+        fees: &'a dyn FeeModel,
+        policy: &LaunchPhasePolicy,
+        start: &ResumePoint,
+    ) -> Self {
+        RowBuilder {
+            format,
+            fees,
+            surcharge_at: format
+                .surcharge_at_secs
+                .map(|t| surcharge_pct_at(policy, format, t) / 100.0),
+            surcharge_rate: format.account_surcharge.then(|| policy.tau(0.0) / 100.0),
+            fee_v: fees.variable_fee_rate(format.vol_accum),
+            s_cum: CompensatedSum::starting_at(start.supply_cum),
+            r_cum: CompensatedSum::starting_at(start.revenue_cum),
+            sr_cum: CompensatedSum::starting_at(start.surcharge_revenue_cum),
+        }
+    }
+
+    /// The row of bin `i`, which must follow the previous call's bin
+    fn row<C: Curve>(&mut self, c: &C, i: i64) -> Result<Row> {
+        let format = self.format;
         let p = c.price_of_bin(i);
         let dx = c.delta_x_of_bin(i);
         let r_bin = p * dx;
-        // supply
-        let t_s = s_cum + dx;
-        if s_cum.abs() >= dx.abs() {
-            s_cmp += (s_cum - t_s) + dx;
-        } else {
-            s_cmp += (dx - t_s) + s_cum;
+        if format.invert_prices && p <= 0.0 {
+            return Err(anyhow!(
+                "cannot invert non-positive price {} at bin {}",
+                p,
+                i
+            ));
         }
-        s_cum = t_s;
-        // revenue
-        let t_r = r_cum + r_bin;
-        if r_cum.abs() >= r_bin.abs() {
-            r_cmp += (r_cum - t_r) + r_bin;
-        } else {
-            r_cmp += (r_bin - t_r) + r_cum;
+        self.s_cum.add(dx);
+        self.r_cum.add(r_bin);
+        let sr_bin = self.surcharge_rate.map(|rate| r_bin * rate);
+        if let Some(x) = sr_bin {
+            self.sr_cum.add(x);
         }
-        r_cum = t_r;
+        // The active bin at launch is bin 0 (P₀)
+        let fee_total = self.fees.total_fee_rate_at_bin(format.vol_accum, i);
+        // Split after the cap so the two parts always add back to fee_total
+        let split = format
+            .protocol_fee_columns
+            .then(|| split_fee(fee_total, self.fees.protocol_share()));
 
-        wtr.serialize(Row {
+        Ok(Row {
             bin: i,
             price: p,
             delta_x: dx,
-            supply_cum: s_cum + s_cmp,
+            supply_cum: self.s_cum.value(),
             revenue_bin: r_bin,
-            revenue_cum: r_cum + r_cmp,
-            fee_base: fee_b,
-            fee_var: fee_v,
-            fee_total: fee_tot,
-        })?;
+            revenue_cum: self.r_cum.value(),
+            fee_base: self.fees.base_fee_rate_at_bin(i),
+            fee_var: self.fee_v,
+            fee_total,
+            price_inv: format.invert_prices.then(|| 1.0 / p),
+            effective_ask: self.surcharge_at.map(|tau| p * (1.0 + tau)),
+            surcharge_revenue_bin: sr_bin,
+            surcharge_revenue_cum: sr_bin.map(|_| self.sr_cum.value()),
+            log_price: format.log_columns.then(|| p.ln()),
+            log_return_from_prev: format
+                .log_columns
+                .then(|| (i > c.first_bin()).then(|| (p / c.price_of_bin(i - 1)).ln())),
+            bin_id: format.bin_id_base.map(|base| base + i),
+            fee_total_bps: rate_to_bps(fee_total),
+            fee_protocol: split.map(|(protocol, _)| protocol),
+            fee_lp: split.map(|(_, lp)| lp),
+            fee_total_with_surcharge: self.surcharge_at.map(|tau| fee_total + tau),
+        })
+    }
+
+    /// Final `(supply_cum, revenue_cum)`
+    fn totals(&self) -> (f64, f64) {
+        (self.s_cum.value(), self.r_cum.value())
     }
-    wtr.flush()?;
-    Ok(())
 }
 
-fn run_logistic(
-    args: &Args,
-    grid: Grid,
-    fees: DlmmFeeParams,
-    policy: LaunchPhasePolicy,
-) -> Result<()> {
-    let p_max = args
-        .p_max
-        .ok_or_else(|| anyhow!("logistic: need --p-max"))?;
-    if !(args.p_min < grid.p0 && grid.p0 < p_max) {
-        return Err(anyhow!(
-            "require p_min < p0 < p_max; got p_min={}, p0={}, p_max={}",
-            args.p_min,
-            grid.p0,
-            p_max
-        ));
+/// Price guards at one bin of the schedule, from [`DlmmFeeParams`]'s min-price helpers
+#[derive(Serialize, Deserialize)]
+struct PriceGuard {
+    label: String,
+    bin: i64,
+    price: f64,
+    min_price_sell_x_for_y: f64,
+    min_price_sell_y_for_x: f64,
+}
+
+/// Guards at the start, middle and end bins of `[first_bin, bins)` for `impact_bps`
+fn price_guards<C: Curve>(c: &C, bins: i64, impact_bps: f64) -> Vec<PriceGuard> {
+    let first = c.first_bin();
+    [
+        ("start", first),
+        ("mid", first + (bins - first) / 2),
+        ("end", bins.saturating_sub(1)),
+    ]
+    .into_iter()
+    .map(|(label, bin)| {
+        let price = c.price_of_bin(bin);
+        PriceGuard {
+            label: label.to_string(),
+            bin,
+            price,
+            min_price_sell_x_for_y: DlmmFeeParams::min_price_sell_x_for_y(price, impact_bps),
+            min_price_sell_y_for_x: DlmmFeeParams::min_price_sell_y_for_x(price, impact_bps),
+        }
+    })
+    .collect()
+}
+
+/// The schedule.csv metadata block as structured fields, for schedule.rows.json
+#[derive(Serialize, Deserialize)]
+struct ScheduleMetadata {
+    /// [`Curve::name`]
+    mode: String,
+    first_bin: i64,
+    bins: i64,
+    /// Run description (mode, growth/decay factors, …) as in the `# ` lines
+    header_lines: Vec<String>,
+    vol_accum: f64,
+    allowlist_len: usize,
+    tau_start_pct: f64,
+    tau_end_pct: f64,
+    ramp_secs: f64,
+    tau_decay: TauDecay,
+    surcharge_at_secs: Option<f64>,
+    surcharge_address: Option<String>,
+    account_surcharge: bool,
+    /// Empty without `--price-guard-bps`
+    price_guards: Vec<PriceGuard>,
+}
+
+impl ScheduleMetadata {
+    fn new<C: Curve>(
+        c: &C,
+        bins: i64,
+        format: &ScheduleFormat,
+        policy: &LaunchPhasePolicy,
+    ) -> Self {
+        ScheduleMetadata {
+            mode: c.name().to_string(),
+            first_bin: c.first_bin(),
+            bins,
+            header_lines: format.header_lines.clone(),
+            vol_accum: format.vol_accum,
+            allowlist_len: policy.allowlist_len(),
+            tau_start_pct: policy.tau_start_pct,
+            tau_end_pct: policy.tau_end_pct,
+            ramp_secs: policy.ramp_secs,
+            tau_decay: policy.tau_decay,
+            surcharge_at_secs: format.surcharge_at_secs,
+            surcharge_address: format.surcharge_address.clone(),
+            account_surcharge: format.account_surcharge,
+            price_guards: format
+                .price_guard_bps
+                .map(|bps| price_guards(c, bins, bps))
+                .unwrap_or_default(),
+        }
     }
-    let bins = if let Some(n) = args.bins {
-        n
-    } else if let Some(p_end) = args.end_price {
-        if p_end <= grid.p0 {
-            return Err(anyhow!(
-                "logistic: require end_price > p0; got end_price={} ≤ p0={}",
-                p_end,
-                grid.p0
-            ));
+}
+
+/// Writes the schedule as one JSON object (`metadata`, `rows`) to `file`, streaming
+/// the rows one at a time, and returns the final `(supply_cum, revenue_cum)`
+fn write_schedule_json<C: Curve, W: Write>(
+    mut file: W,
+    format: &ScheduleFormat,
+    c: &C,
+    bins: i64,
+    fees: &dyn FeeModel,
+    policy: &LaunchPhasePolicy,
+) -> Result<(f64, f64)> {
+    let lo = c.first_bin();
+    let mut row_builder = RowBuilder::new(format, fees, policy, &ResumePoint::at(lo));
+    write!(file, "{{\"metadata\":")?;
+    serde_json::to_writer(&mut file, &ScheduleMetadata::new(c, bins, format, policy))?;
+    write!(file, ",\"rows\":[")?;
+    for i in lo..bins {
+        if i > lo {
+            write!(file, ",")?;
         }
-        compute_bins_from_end_price(&grid, p_end)
-    } else {
-        500
-    };
+        serde_json::to_writer(&mut file, &row_builder.row(c, i)?)?;
+    }
+    writeln!(file, "]}}")?;
+    file.flush()?;
+    Ok(row_builder.totals())
+}
+
+/// Rows per record batch of schedule.parquet; only one batch is held in memory
+#[cfg(feature = "parquet")]
+const PARQUET_BATCH_ROWS: i64 = 65_536;
+
+/// Reads one schedule column out of a [`Row`]; `None` is an empty cell
+#[cfg(feature = "parquet")]
+#[derive(Clone, Copy)]
+enum ParquetCell {
+    Int(fn(&Row) -> Option<i64>),
+    UInt(fn(&Row) -> Option<u32>),
+    Float(fn(&Row) -> Option<f64>),
+}
 
-    let mut s_mid = args.s_mid;
-    if s_mid == 0.0 {
-        s_mid = ((p_max - grid.p0) / (grid.p0 - args.p_min)).ln() / args.k;
+/// The reader for schedule column `name` and whether it is nullable (an
+/// optional `Row` field). Errors on a name without a mapping, so a column added
+/// to [`schedule_columns`] cannot silently come out empty.
+#[cfg(feature = "parquet")]
+fn parquet_column(name: &str) -> Result<(ParquetCell, bool)> {
+    use ParquetCell::{Float, Int, UInt};
+    let required = |cell| Ok((cell, false));
+    let optional = |cell| Ok((cell, true));
+    match name {
+        "bin" => required(Int(|r| Some(r.bin))),
+        "price" => required(Float(|r| Some(r.price))),
+        "delta_x" => required(Float(|r| Some(r.delta_x))),
+        "supply_cum" => required(Float(|r| Some(r.supply_cum))),
+        "revenue_bin" => required(Float(|r| Some(r.revenue_bin))),
+        "revenue_cum" => required(Float(|r| Some(r.revenue_cum))),
+        "fee_base" => required(Float(|r| Some(r.fee_base))),
+        "fee_var" => required(Float(|r| Some(r.fee_var))),
+        "fee_total" => required(Float(|r| Some(r.fee_total))),
+        "price_inv" => optional(Float(|r| r.price_inv)),
+        "effective_ask" => optional(Float(|r| r.effective_ask)),
+        "surcharge_revenue_bin" => optional(Float(|r| r.surcharge_revenue_bin)),
+        "surcharge_revenue_cum" => optional(Float(|r| r.surcharge_revenue_cum)),
+        "log_price" => optional(Float(|r| r.log_price)),
+        "log_return_from_prev" => optional(Float(|r| r.log_return_from_prev.flatten())),
+        "bin_id" => optional(Int(|r| r.bin_id)),
+        "fee_total_bps" => required(UInt(|r| Some(r.fee_total_bps))),
+        "fee_protocol" => optional(Float(|r| r.fee_protocol)),
+        "fee_lp" => optional(Float(|r| r.fee_lp)),
+        "fee_total_with_surcharge" => optional(Float(|r| r.fee_total_with_surcharge)),
+        _ => Err(anyhow!("schedule column {:?} has no Parquet mapping", name)),
     }
-    let curve = LogisticS {
-        grid,
-        p_min: args.p_min,
-        p_max,
-        k: args.k,
-        s_mid,
-        bins,
+}
+
+/// Writes the schedule as Parquet: the schedule.csv columns under the same names
+/// (`bin`/`bin_id` Int64, `fee_total_bps` UInt32, the rest Float64; optional
+/// columns are nullable where schedule.csv may leave a cell empty), built
+/// [`PARQUET_BATCH_ROWS`] rows at a time. Each field of the schedule.rows.json
+/// `metadata` is a key-value entry (strings as-is, the rest as JSON). Returns
+/// the final `(supply_cum, revenue_cum)`.
+#[cfg(feature = "parquet")]
+fn write_schedule_parquet<C: Curve, W: Write + Send>(
+    file: W,
+    format: &ScheduleFormat,
+    c: &C,
+    bins: i64,
+    fees: &dyn FeeModel,
+    policy: &LaunchPhasePolicy,
+) -> Result<(f64, f64)> {
+    use arrow_array::{ArrayRef, Float64Array, Int64Array, RecordBatch, UInt32Array};
+    use arrow_schema::{DataType, Field, Schema};
+    use parquet::arrow::ArrowWriter;
+    use parquet::basic::Compression;
+    use parquet::file::metadata::KeyValue;
+    use parquet::file::properties::WriterProperties;
+    use std::sync::Arc;
+
+    let names = schedule_columns(format);
+    let columns = names
+        .iter()
+        .map(|&name| parquet_column(name))
+        .collect::<Result<Vec<_>>>()?;
+    let schema = Arc::new(Schema::new(
+        names
+            .iter()
+            .zip(&columns)
+            .map(|(&name, &(cell, nullable))| {
+                let ty = match cell {
+                    ParquetCell::Int(_) => DataType::Int64,
+                    ParquetCell::UInt(_) => DataType::UInt32,
+                    ParquetCell::Float(_) => DataType::Float64,
+                };
+                Field::new(name, ty, nullable)
+            })
+            .collect::<Vec<_>>(),
+    ));
+    let metadata = match serde_json::to_value(ScheduleMetadata::new(c, bins, format, policy))? {
+        serde_json::Value::Object(fields) => fields
+            .into_iter()
+            .map(|(key, value)| match value {
+                serde_json::Value::String(s) => KeyValue::new(key, s),
+                v => KeyValue::new(key, v.to_string()),
+            })
+            .collect(),
+        _ => Vec::new(),
     };
-    if args.verbose {
-        println!(
-            "[{}] bins={} p_min={:.6} p_max={:.6} k={:.8} s_mid={:.2}",
-            curve.name(),
-            bins,
-            args.p_min,
-            p_max,
-            args.k,
-            s_mid
-        );
-        println!(
-            "  Cumulative supply at n={}: {:.6}",
-            bins,
-            curve.cumulative_supply(bins)
-        );
-        println!("  Allowlist size: {}", policy.allowlist.len());
-        println!(
-            "  Launch surcharge: τ(0s)={:.1}% → τ({:.0}s)={:.1}%",
-            policy.tau(0.0),
-            policy.ramp_secs,
-            policy.tau(policy.ramp_secs)
-        );
-    }
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .set_key_value_metadata(Some(metadata))
+        .build();
+    let mut writer = ArrowWriter::try_new(file, schema.clone(), Some(props))?;
 
-    write_schedule_csv_generic(
-        &args.out_dir,
-        &curve,
-        bins,
-        fees,
-        args.vol_accum,
-        &policy,
-        args.price_guard_bps,
-    )?;
-    if args.draw {
-        plot_price_vs_supply(
-            &curve,
-            bins,
-            &format!("{}/price_vs_supply.png", &args.out_dir),
-        )?;
-        plot_tokens_per_bin(
-            &curve,
-            bins,
-            &format!("{}/tokens_per_bin.png", &args.out_dir),
-        )?;
-        plot_fee_vs_vol(
-            |va| fees.total_fee_rate(va),
-            &format!("{}/fee_vs_volatility.png", &args.out_dir),
-        )?;
+    let lo = c.first_bin();
+    let mut row_builder = RowBuilder::new(format, fees, policy, &ResumePoint::at(lo));
+    let mut start = lo;
+    while start < bins {
+        let end = start.saturating_add(PARQUET_BATCH_ROWS).min(bins);
+        let rows = (start..end)
+            .map(|i| row_builder.row(c, i))
+            .collect::<Result<Vec<_>>>()?;
+        let arrays: Vec<ArrayRef> = columns
+            .iter()
+            .map(|&(cell, _)| -> ArrayRef {
+                match cell {
+                    ParquetCell::Int(get) => Arc::new(rows.iter().map(get).collect::<Int64Array>()),
+                    ParquetCell::UInt(get) => {
+                        Arc::new(rows.iter().map(get).collect::<UInt32Array>())
+                    }
+                    ParquetCell::Float(get) => {
+                        Arc::new(rows.iter().map(get).collect::<Float64Array>())
+                    }
+                }
+            })
+            .collect();
+        writer.write(&RecordBatch::try_new(schema.clone(), arrays)?)?;
+        start = end;
     }
-    Ok(())
+    writer.close()?;
+    Ok(row_builder.totals())
 }
 
-fn write_schedule_csv_generic<C: Curve>(
-    out_dir: &str,
+/// Writes schedule.parquet for `--format parquet`
+#[cfg(feature = "parquet")]
+fn write_schedule_parquet_file<C: Curve>(
+    args: &Args,
     c: &C,
     bins: i64,
-    fees: DlmmFeeParams,
-    va: f64,
+    fees: &dyn FeeModel,
     policy: &LaunchPhasePolicy,
-    price_guard_bps: Option<f64>,
-) -> Result<()> {
-    let file_path = format!("{}/schedule.csv", out_dir);
-    let mut file = File::create(&file_path)?;
+    format: &ScheduleFormat,
+) -> Result<(f64, f64)> {
+    let path = format!("{}/schedule.parquet", args.out_dir);
+    let write = || -> Result<(f64, f64)> {
+        write_schedule_parquet(File::create(&path)?, format, c, bins, fees, policy)
+    };
+    write().with_context(|| format!("failed to write {}", path))
+}
+
+/// Without the `parquet` feature, `--format parquet` is rejected by `validate_inputs`
+#[cfg(not(feature = "parquet"))]
+fn write_schedule_parquet_file<C: Curve>(
+    _args: &Args,
+    _c: &C,
+    _bins: i64,
+    _fees: &dyn FeeModel,
+    _policy: &LaunchPhasePolicy,
+    _format: &ScheduleFormat,
+) -> Result<(f64, f64)> {
+    Err(anyhow!(
+        "--format parquet needs bcurve built with `--features parquet`"
+    ))
+}
 
-    // Write metadata header
+/// Writes the `# ` metadata block and blank line that precede the schedule header
+fn write_schedule_metadata<C: Curve, W: Write>(
+    file: &mut W,
+    format: &ScheduleFormat,
+    c: &C,
+    bins: i64,
+    policy: &LaunchPhasePolicy,
+) -> Result<()> {
+    let va = format.vol_accum;
     writeln!(file, "# DLMM Bonding Curve Schedule")?;
-    writeln!(file, "# Mode: {}", c.name())?;
+    for line in &format.header_lines {
+        writeln!(file, "# {}", line)?;
+    }
     writeln!(file, "# Volatility accumulator: {}", va)?;
-    writeln!(file, "# Total supply: {:.6}", c.cumulative_supply(bins))?;
 
     // Launch policy configuration
     writeln!(
         file,
         "# Launch policy: allowlist={} addresses",
-        policy.allowlist.len()
-    )?;
-    writeln!(
-        file,
-        "# Surcharge ramp: {:.1}% → {:.1}% over {:.0}s",
-        policy.tau_start_pct, policy.tau_end_pct, policy.ramp_secs
+        policy.allowlist_len()
     )?;
+    match policy.tau_decay {
+        TauDecay::Linear => writeln!(
+            file,
+            "# Surcharge ramp: {:.1}% → {:.1}% over {:.0}s",
+            policy.tau_start_pct, policy.tau_end_pct, policy.ramp_secs
+        )?,
+        TauDecay::Exponential { half_life_secs } => writeln!(
+            file,
+            "# Surcharge decay: {:.1}% → {:.1}% exponentially, half-life {}s",
+            policy.tau_start_pct, policy.tau_end_pct, half_life_secs
+        )?,
+    }
+
+    if let Some(t) = format.surcharge_at_secs {
+        let trader = match &format.surcharge_address {
+            Some(addr) => format!("trader {}", addr),
+            None => "non-allowlisted traders".to_string(),
+        };
+        writeln!(
+            file,
+            "# effective_ask, fee_total_with_surcharge: ask and fee for {}, price·(1+τ/100) and fee_total+τ/100 at t={}s (τ={:.4}%)",
+            trader,
+            t,
+            surcharge_pct_at(policy, format, t)
+        )?;
+    }
+    // Worst case for the treasury projection: all flow non-allowlisted at t=0
+    let surcharge_rate = format.account_surcharge.then(|| policy.tau(0.0) / 100.0);
+    if let Some(rate) = surcharge_rate {
+        writeln!(
+            file,
+            "# surcharge_revenue: revenue_bin·τ(0)/100 with τ(0)={:.4}%, all buys non-allowlisted at launch",
+            rate * 100.0
+        )?;
+    }
 
     // Optional price-guard metadata
-    if let Some(impact_bps) = price_guard_bps {
-        for (label, bin) in [
-            ("start", 0),
-            ("mid", bins / 2),
-            ("end", bins.saturating_sub(1)),
-        ] {
-            let price = c.price_of_bin(bin);
-            writeln!(file, "# Guard @ {} (bin {}, P={:.12}):", label, bin, price)?;
-            writeln!(
-                file,
-                "#   Min X→Y: {:.12}",
-                DlmmFeeParams::min_price_sell_x_for_y(price, impact_bps)
-            )?;
-            writeln!(
-                file,
-                "#   Min Y→X: {:.12}",
-                DlmmFeeParams::min_price_sell_y_for_x(price, impact_bps)
-            )?;
+    if let Some(impact_bps) = format.price_guard_bps {
+        for g in price_guards(c, bins, impact_bps) {
+            if format.guard_by_bin {
+                writeln!(file, "# Guard @ bin {} (P={:.12}):", g.bin, g.price)?;
+            } else {
+                writeln!(
+                    file,
+                    "# Guard @ {} (bin {}, P={:.12}):",
+                    g.label, g.bin, g.price
+                )?;
+            }
+            writeln!(file, "#   Min X→Y: {:.12}", g.min_price_sell_x_for_y)?;
+            writeln!(file, "#   Min Y→X: {:.12}", g.min_price_sell_y_for_x)?;
         }
     }
 
     writeln!(file)?;
-
-    // Create CSV writer (write one header row)
-    let mut wtr = csv::WriterBuilder::new()
-        .has_headers(false)
-        .from_writer(file);
-    // explicit header
-    wtr.write_record([
-        "bin",
-        "price",
-        "delta_x",
-        "supply_cum",
-        "revenue_bin",
-        "revenue_cum",
-        "fee_base",
-        "fee_var",
-        "fee_total",
-    ])?;
-
-    // Neumaier compensated sums
-    let mut s_cum = 0.0;
-    let mut s_cmp = 0.0;
-    let mut r_cum = 0.0;
-    let mut r_cmp = 0.0;
-    let fee_b = fees.base_fee_rate();
-    let fee_v = fees.variable_fee_rate(va);
-    let fee_tot = fees.total_fee_rate(va);
-
-    for i in 0..bins {
-        let p = c.price_of_bin(i);
-        let dx = c.delta_x_of_bin(i);
-        let r_bin = p * dx;
-        // supply
-        let t_s = s_cum + dx;
-        if s_cum.abs() >= dx.abs() {
-            s_cmp += (s_cum - t_s) + dx;
-        } else {
-            s_cmp += (dx - t_s) + s_cum;
-        }
-        s_cum = t_s;
-        // revenue
-        let t_r = r_cum + r_bin;
-        if r_cum.abs() >= r_bin.abs() {
-            r_cmp += (r_cum - t_r) + r_bin;
-        } else {
-            r_cmp += (r_bin - t_r) + r_cum;
-        }
-        r_cum = t_r;
-
-        wtr.serialize(Row {
-            bin: i,
-            price: p,
-            delta_x: dx,
-            supply_cum: s_cum + s_cmp,
-            revenue_bin: r_bin,
-            revenue_cum: r_cum + r_cmp,
-            fee_base: fee_b,
-            fee_var: fee_v,
-            fee_total: fee_tot,
-        })?;
-    }
-    wtr.flush()?;
     Ok(())
 }
@@ -0,0 +1,135 @@
+//! f64 functions missing from `core`: forwarded to `std` when it is available and
+//! to `libm` otherwise, so the curve and fee math builds either way. Results can
+//! differ in the last bits between the builds: `std` calls the platform's math
+//! library, and its `powi` multiplies repeatedly where `libm::pow` does not.
+
+#[cfg(feature = "std")]
+#[inline]
+pub(crate) fn powi(x: f64, n: i32) -> f64 {
+    x.powi(n)
+}
+#[cfg(not(feature = "std"))]
+#[inline]
+pub(crate) fn powi(x: f64, n: i32) -> f64 {
+    libm::pow(x, n as f64)
+}
+
+/// x^n for a bin index: [`powi`] while `n` fits in an `i32`, [`powf`] beyond, so
+/// indices past `i32::MAX` keep their exponent instead of wrapping
+#[inline]
+pub(crate) fn powi_i64(x: f64, n: i64) -> f64 {
+    match i32::try_from(n) {
+        Ok(n) => powi(x, n),
+        Err(_) => powf(x, n as f64),
+    }
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub(crate) fn powf(x: f64, y: f64) -> f64 {
+    x.powf(y)
+}
+#[cfg(not(feature = "std"))]
+#[inline]
+pub(crate) fn powf(x: f64, y: f64) -> f64 {
+    libm::pow(x, y)
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub(crate) fn ln(x: f64) -> f64 {
+    x.ln()
+}
+#[cfg(not(feature = "std"))]
+#[inline]
+pub(crate) fn ln(x: f64) -> f64 {
+    libm::log(x)
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub(crate) fn exp(x: f64) -> f64 {
+    x.exp()
+}
+#[cfg(not(feature = "std"))]
+#[inline]
+pub(crate) fn exp(x: f64) -> f64 {
+    libm::exp(x)
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub(crate) fn ceil(x: f64) -> f64 {
+    x.ceil()
+}
+#[cfg(not(feature = "std"))]
+#[inline]
+pub(crate) fn ceil(x: f64) -> f64 {
+    libm::ceil(x)
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub(crate) fn floor(x: f64) -> f64 {
+    x.floor()
+}
+#[cfg(not(feature = "std"))]
+#[inline]
+pub(crate) fn floor(x: f64) -> f64 {
+    libm::floor(x)
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub(crate) fn sin(x: f64) -> f64 {
+    x.sin()
+}
+#[cfg(not(feature = "std"))]
+#[inline]
+pub(crate) fn sin(x: f64) -> f64 {
+    libm::sin(x)
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub(crate) fn asin(x: f64) -> f64 {
+    x.asin()
+}
+#[cfg(not(feature = "std"))]
+#[inline]
+pub(crate) fn asin(x: f64) -> f64 {
+    libm::asin(x)
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub(crate) fn ln_1p(x: f64) -> f64 {
+    x.ln_1p()
+}
+#[cfg(not(feature = "std"))]
+#[inline]
+pub(crate) fn ln_1p(x: f64) -> f64 {
+    libm::log1p(x)
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub(crate) fn exp_m1(x: f64) -> f64 {
+    x.exp_m1()
+}
+#[cfg(not(feature = "std"))]
+#[inline]
+pub(crate) fn exp_m1(x: f64) -> f64 {
+    libm::expm1(x)
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub(crate) fn sqrt(x: f64) -> f64 {
+    x.sqrt()
+}
+#[cfg(not(feature = "std"))]
+#[inline]
+pub(crate) fn sqrt(x: f64) -> f64 {
+    libm::sqrt(x)
+}
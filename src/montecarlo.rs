@@ -0,0 +1,229 @@
+//! Seeded Monte Carlo launches: random buy flow over the surcharge ramp
+//!
+//! Each run draws Poisson buy arrivals with log-normal quote sizes and replays
+//! them through [`simulate_trades`], so fees, surcharge and fills match the
+//! `--trades` replay exactly. The generator is a self-contained SplitMix64, so a
+//! seed reproduces the same numbers on every platform and dependency version.
+
+use crate::curves::Curve;
+use crate::dlmm::{FeeModel, LaunchPhasePolicy};
+use crate::sim::{simulate_trades, TradeEvent};
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+/// Address used for simulated buys that are not allowlisted
+pub const PUBLIC_ADDRESS: &str = "montecarlo_public";
+
+/// Random flow driving [`simulate`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MonteCarloConfig {
+    /// Independent launches simulated
+    pub runs: usize,
+    /// Seconds from launch over which buys arrive, usually the surcharge ramp
+    pub horizon_secs: f64,
+    /// Mean buys per second (Poisson arrivals)
+    pub arrival_rate: f64,
+    /// Median gross quote per buy; sizes are log-normal around it
+    pub median_quote: f64,
+    /// σ of ln(quote_in)
+    pub size_sigma: f64,
+    /// Probability a buy comes from an allowlisted address
+    pub allowlisted_share: f64,
+    /// Volatility accumulator for the fee
+    pub va: f64,
+}
+
+/// Mean, extremes and nearest-rank percentiles of one metric across runs
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Distribution {
+    /// Arithmetic mean
+    pub mean: f64,
+    /// Smallest sample
+    pub min: f64,
+    /// 5th percentile
+    pub p5: f64,
+    /// Median
+    pub p50: f64,
+    /// 95th percentile
+    pub p95: f64,
+    /// Largest sample
+    pub max: f64,
+}
+
+impl Distribution {
+    /// Summarizes `samples` (sorted in place); all zero when empty
+    pub fn from_samples(samples: &mut [f64]) -> Self {
+        if samples.is_empty() {
+            return Distribution {
+                mean: 0.0,
+                min: 0.0,
+                p5: 0.0,
+                p50: 0.0,
+                p95: 0.0,
+                max: 0.0,
+            };
+        }
+        samples.sort_by(f64::total_cmp);
+        let n = samples.len();
+        let rank = |pct: f64| samples[((pct / 100.0 * n as f64).ceil() as usize).clamp(1, n) - 1];
+        Distribution {
+            mean: samples.iter().sum::<f64>() / n as f64,
+            min: samples[0],
+            p5: rank(5.0),
+            p50: rank(50.0),
+            p95: rank(95.0),
+            max: samples[n - 1],
+        }
+    }
+}
+
+/// Outcome distributions over all [`MonteCarloConfig::runs`] launches
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SimStats {
+    /// Launches simulated
+    pub runs: usize,
+    /// Mean buys per launch
+    pub mean_trades: f64,
+    /// Σ swap fees paid per launch
+    pub fee_revenue: Distribution,
+    /// Σ launch surcharge paid per launch
+    pub surcharge_revenue: Distribution,
+    /// Tokens bought per launch
+    pub supply_sold: Distribution,
+    /// Active bin at the end of each launch
+    pub final_active_bin: Distribution,
+}
+
+impl SimStats {
+    /// `(metric, distribution)` rows in output order
+    pub fn metrics(&self) -> [(&'static str, &Distribution); 4] {
+        [
+            ("fee_revenue", &self.fee_revenue),
+            ("surcharge_revenue", &self.surcharge_revenue),
+            ("supply_sold", &self.supply_sold),
+            ("final_active_bin", &self.final_active_bin),
+        ]
+    }
+}
+
+/// SplitMix64: tiny, fast, and fully determined by its seed
+#[derive(Clone, Debug)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+    /// Uniform on the open interval (0, 1)
+    fn uniform(&mut self) -> f64 {
+        ((self.next_u64() >> 11) as f64 + 0.5) / (1_u64 << 53) as f64
+    }
+    /// Standard normal via Box-Muller
+    fn normal(&mut self) -> f64 {
+        let (u1, u2) = (self.uniform(), self.uniform());
+        (-2.0 * u1.ln()).sqrt() * (core::f64::consts::TAU * u2).cos()
+    }
+}
+
+/// Runs `config.runs` random launches of `c` over bins `[0, bins)` from `seed`.
+///
+/// Buys arrive as a Poisson process over `[0, horizon_secs)`, each paying the
+/// surcharge τ(t) unless allowlisted, then the fee, exactly as in
+/// [`simulate_trades`]. Allowlisted buys use the policy's smallest address, so
+/// `allowlisted_share` only matters with a non-empty allowlist.
+pub fn simulate<C: Curve>(
+    c: &C,
+    bins: i64,
+    fees: &dyn FeeModel,
+    policy: &LaunchPhasePolicy,
+    config: &MonteCarloConfig,
+    seed: u64,
+) -> Result<SimStats> {
+    let MonteCarloConfig {
+        runs,
+        horizon_secs,
+        arrival_rate,
+        median_quote,
+        size_sigma,
+        allowlisted_share,
+        va,
+    } = *config;
+    if runs == 0 {
+        return Err(anyhow!("montecarlo: runs must be ≥ 1"));
+    }
+    if !horizon_secs.is_finite() || horizon_secs <= 0.0 {
+        return Err(anyhow!(
+            "montecarlo: horizon must be finite and > 0 (got {}s)",
+            horizon_secs
+        ));
+    }
+    if !arrival_rate.is_finite() || arrival_rate <= 0.0 {
+        return Err(anyhow!(
+            "montecarlo: arrival rate must be finite and > 0 (got {})",
+            arrival_rate
+        ));
+    }
+    if !median_quote.is_finite() || median_quote <= 0.0 {
+        return Err(anyhow!(
+            "montecarlo: median quote must be finite and > 0 (got {})",
+            median_quote
+        ));
+    }
+    if !size_sigma.is_finite() || size_sigma < 0.0 {
+        return Err(anyhow!(
+            "montecarlo: size σ must be finite and ≥ 0 (got {})",
+            size_sigma
+        ));
+    }
+    if !(0.0..=1.0).contains(&allowlisted_share) {
+        return Err(anyhow!(
+            "montecarlo: allowlisted share must be in [0, 1] (got {})",
+            allowlisted_share
+        ));
+    }
+    let allowlisted = policy.allowlist.addresses().min();
+    let mut rng = SplitMix64(seed);
+    let (mut fee, mut surcharge, mut sold, mut active) = (
+        Vec::with_capacity(runs),
+        Vec::with_capacity(runs),
+        Vec::with_capacity(runs),
+        Vec::with_capacity(runs),
+    );
+    let mut trades_total = 0usize;
+    let mut trades = Vec::new();
+    for _ in 0..runs {
+        trades.clear();
+        let mut t = -rng.uniform().ln() / arrival_rate;
+        while t < horizon_secs {
+            let quote_in = median_quote * (size_sigma * rng.normal()).exp();
+            let address = match allowlisted {
+                Some(addr) if rng.uniform() < allowlisted_share => addr,
+                _ => PUBLIC_ADDRESS,
+            };
+            trades.push(TradeEvent {
+                timestamp: t,
+                address: address.to_string(),
+                quote_in,
+            });
+            t += -rng.uniform().ln() / arrival_rate;
+        }
+        trades_total += trades.len();
+        let outcomes = simulate_trades(c, bins, fees, va, policy, &trades);
+        fee.push(outcomes.iter().map(|o| o.fee_paid).sum());
+        surcharge.push(outcomes.iter().map(|o| o.surcharge_paid).sum());
+        sold.push(outcomes.iter().map(|o| o.tokens_out).sum());
+        active.push(outcomes.last().map_or(0, |o| o.active_bin) as f64);
+    }
+    Ok(SimStats {
+        runs,
+        mean_trades: trades_total as f64 / runs as f64,
+        fee_revenue: Distribution::from_samples(&mut fee),
+        surcharge_revenue: Distribution::from_samples(&mut surcharge),
+        supply_sold: Distribution::from_samples(&mut sold),
+        final_active_bin: Distribution::from_samples(&mut active),
+    })
+}
@@ -1,74 +1,429 @@
 //! Visualization utilities for generating charts
 
-use crate::curves::Curve;
-use anyhow::Result;
+use crate::curves::{sample_bins, Curve};
+use crate::dlmm::{DlmmFeeParams, FeeModel};
+use anyhow::{anyhow, Context, Result};
+use plotters::coord::Shift;
 use plotters::prelude::*;
+use std::path::Path;
+use std::str::FromStr;
 
-/// Generates a price vs cumulative supply chart
-pub fn plot_price_vs_supply<C: Curve>(c: &C, bins: i64, out_path: &str) -> Result<()> {
-    let root = BitMapBackend::new(out_path, (1200, 700)).into_drawing_area();
-    root.fill(&WHITE)?;
+/// Default `max_points`: bins drawn per chart before larger schedules are
+/// sampled with [`sample_bins`]
+pub const MAX_PLOT_BINS: usize = 10_000;
+
+/// Chart size in pixels (SVG user units)
+const CHART_SIZE: (u32, u32) = (1200, 700);
+
+/// Image format of the charts, chosen by the output path's extension
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChartFormat {
+    /// Raster PNG
+    #[default]
+    Png,
+    /// Scalable SVG
+    Svg,
+}
+impl ChartFormat {
+    /// `.svg` paths (any case) are SVG, everything else PNG
+    pub fn of_path(out_path: &str) -> Self {
+        match Path::new(out_path).extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("svg") => ChartFormat::Svg,
+            _ => ChartFormat::Png,
+        }
+    }
+    /// File extension without the dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ChartFormat::Png => "png",
+            ChartFormat::Svg => "svg",
+        }
+    }
+}
+impl FromStr for ChartFormat {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "png" => Ok(ChartFormat::Png),
+            "svg" => Ok(ChartFormat::Svg),
+            _ => Err(anyhow!("unknown chart format {:?} (expected png|svg)", s)),
+        }
+    }
+}
+
+/// Runs `$draw` against a white [`CHART_SIZE`] drawing area bound to `$root`,
+/// backed by [`SVGBackend`] or [`BitMapBackend`] per [`ChartFormat::of_path`],
+/// then writes the file. `$draw` is expanded once per backend, so chart code is
+/// written once and stays generic over both.
+macro_rules! with_chart_area {
+    ($out_path:expr, |$root:ident| $draw:block) => {{
+        let out_path: &str = $out_path;
+        match ChartFormat::of_path(out_path) {
+            ChartFormat::Svg => {
+                let $root = SVGBackend::new(out_path, CHART_SIZE).into_drawing_area();
+                with_chart_area!(@draw out_path, $root, $draw)
+            }
+            ChartFormat::Png => {
+                let $root = BitMapBackend::new(out_path, CHART_SIZE).into_drawing_area();
+                with_chart_area!(@draw out_path, $root, $draw)
+            }
+        }
+    }};
+    (@draw $out_path:ident, $root:ident, $draw:block) => {{
+        $root
+            .fill(&WHITE)
+            .with_context(|| format!("failed to draw {}", $out_path))?;
+        $draw
+        $root
+            .present()
+            .with_context(|| format!("failed to write {}", $out_path))?;
+        Ok(())
+    }};
+}
+
+/// Plain chart with the shared margins, caption size and label areas
+fn chart_builder<'a, 'b, DB: DrawingBackend>(
+    root: &'a DrawingArea<DB, Shift>,
+    caption: &str,
+) -> ChartBuilder<'a, 'b, DB> {
+    let mut builder = ChartBuilder::on(root);
+    builder
+        .margin(20)
+        .caption(caption, ("sans-serif", 28))
+        .x_label_area_size(40)
+        .y_label_area_size(60);
+    builder
+}
+
+/// Rendering options for [`plot_price_vs_supply_with`]
+#[derive(Clone, Copy, Debug)]
+pub struct PriceChartOptions {
+    /// Plot 1/P (token per quote) instead of P
+    pub invert_price: bool,
+    /// Divide cumulative supply by the total so the x-axis runs 0..1
+    pub normalize_supply: bool,
+    /// Log-scale y-axis, for prices spanning orders of magnitude; every plotted
+    /// price must be > 0
+    pub log_price: bool,
+    /// Most bins drawn, see [`sample_bins`]
+    pub max_points: usize,
+}
+impl Default for PriceChartOptions {
+    fn default() -> Self {
+        PriceChartOptions {
+            invert_price: false,
+            normalize_supply: false,
+            log_price: false,
+            max_points: MAX_PLOT_BINS,
+        }
+    }
+}
+
+/// Step line of (cumulative supply, P) over `[first_bin, bins)`, two points for
+/// each of the [`sample_bins`]; 1/P with `invert_price`. Every bin still adds to
+/// the supply, but only the sampled ones are stored.
+fn price_supply_steps<C: Curve + ?Sized>(
+    c: &C,
+    bins: i64,
+    invert_price: bool,
+    max_points: usize,
+) -> Vec<(f64, f64)> {
+    let lo = c.first_bin();
+    let mut samples = sample_bins(lo..bins, max_points).peekable();
     let mut supply = 0.0_f64;
-    let mut data: Vec<(f64, f64)> = Vec::with_capacity(2 * bins as usize);
-    for i in 0..bins {
+    let mut data: Vec<(f64, f64)> = Vec::with_capacity(2 * max_points.max(2));
+    for i in lo..bins {
+        if samples.next_if_eq(&i).is_none() {
+            supply += c.delta_x_of_bin(i);
+            continue;
+        }
         let p = c.price_of_bin(i);
+        let p = if invert_price { 1.0 / p } else { p };
         data.push((supply, p));
         supply += c.delta_x_of_bin(i);
         data.push((supply, p)); // step
     }
-    let x_max = data.last().map(|(x, _)| *x).unwrap_or(1.0).max(1e-12);
+    data
+}
+
+/// Generates a price vs cumulative supply chart, SVG for a `.svg` `out_path` and
+/// PNG otherwise (as every chart here)
+pub fn plot_price_vs_supply<C: Curve>(c: &C, bins: i64, out_path: &str) -> Result<()> {
+    plot_price_vs_supply_with(c, bins, out_path, &PriceChartOptions::default())
+}
+
+/// Generates a price vs cumulative supply chart with the given [`PriceChartOptions`]
+pub fn plot_price_vs_supply_with<C: Curve>(
+    c: &C,
+    bins: i64,
+    out_path: &str,
+    opts: &PriceChartOptions,
+) -> Result<()> {
+    let mut data = price_supply_steps(c, bins, opts.invert_price, opts.max_points);
+    let total = data.last().map(|(x, _)| *x).unwrap_or(1.0).max(1e-12);
+    let x_max = if opts.normalize_supply {
+        for pt in &mut data {
+            pt.0 /= total;
+        }
+        1.0
+    } else {
+        total
+    };
     let y_max = data.iter().map(|(_, y)| *y).fold(0.0, f64::max).max(1e-12);
-    let mut chart = ChartBuilder::on(&root)
-        .margin(20)
-        .caption("Price vs Cumulative Supply", ("sans-serif", 28))
-        .x_label_area_size(40)
-        .y_label_area_size(60)
-        .build_cartesian_2d(0.0..x_max, 0.0..(y_max * 1.05))?;
-    chart.configure_mesh().draw()?;
-    chart.draw_series(LineSeries::new(data, &BLACK))?;
-    root.present()?;
-    Ok(())
+    let caption = if opts.invert_price {
+        "Inverse Price (1/P) vs Cumulative Supply"
+    } else {
+        "Price vs Cumulative Supply"
+    };
+    if opts.log_price {
+        if let Some((_, y)) = data.iter().find(|(_, y)| !(*y > 0.0 && y.is_finite())) {
+            return Err(anyhow!(
+                "log-scale price chart needs finite prices > 0 (got {})",
+                y
+            ));
+        }
+        let y_min = data.iter().map(|(_, y)| *y).fold(f64::INFINITY, f64::min);
+        let y_desc = if opts.invert_price {
+            "1/P (log scale)"
+        } else {
+            "price (log scale)"
+        };
+        return with_chart_area!(out_path, |root| {
+            let mut chart = chart_builder(&root, caption)
+                .build_cartesian_2d(0.0..x_max, (y_min / 1.05..y_max * 1.05).log_scale())?;
+            chart
+                .configure_mesh()
+                .x_desc("cumulative supply")
+                .y_desc(y_desc)
+                .y_label_formatter(&|y| format!("{:.2e}", y))
+                .draw()?;
+            chart.draw_series(LineSeries::new(data, &BLACK))?;
+        });
+    }
+    with_chart_area!(out_path, |root| {
+        let mut chart =
+            chart_builder(&root, caption).build_cartesian_2d(0.0..x_max, 0.0..(y_max * 1.05))?;
+        chart.configure_mesh().draw()?;
+        chart.draw_series(LineSeries::new(data, &BLACK))?;
+    })
+}
+
+/// Distinct series colors for overlay charts, cycled past the last
+pub const SERIES_COLORS: [RGBColor; 6] = [
+    RGBColor(31, 119, 180),
+    RGBColor(214, 39, 40),
+    RGBColor(44, 160, 44),
+    RGBColor(148, 103, 189),
+    RGBColor(255, 127, 14),
+    RGBColor(23, 190, 207),
+];
+
+/// Overlays price vs cumulative supply of several curves, each a labeled series
+/// in its color, on axes spanning all of them so none is clipped. The price axis
+/// is fitted to the curves' price range rather than starting at 0, so curves on
+/// the same grid stay apart.
+pub fn plot_price_vs_supply_multi(
+    curves: &[(&dyn Curve, &str, RGBColor)],
+    bins: i64,
+    max_points: usize,
+    out_path: &str,
+) -> Result<()> {
+    let series: Vec<Vec<(f64, f64)>> = curves
+        .iter()
+        .map(|(c, _, _)| price_supply_steps(*c, bins, false, max_points))
+        .collect();
+    let points = || series.iter().flatten();
+    let x_max = points().map(|(x, _)| *x).fold(0.0, f64::max).max(1e-12);
+    let y_min = points().map(|(_, y)| *y).fold(f64::INFINITY, f64::min);
+    let y_max = points().map(|(_, y)| *y).fold(f64::NEG_INFINITY, f64::max);
+    let (y_min, y_max) = if y_min.is_finite() && y_max.is_finite() {
+        let pad = 0.05 * (y_max - y_min).max(1e-12 * y_max.abs()).max(1e-12);
+        (y_min - pad, y_max + pad)
+    } else {
+        (0.0, 1.0)
+    };
+    with_chart_area!(out_path, |root| {
+        let mut chart = chart_builder(&root, "Price vs Cumulative Supply")
+            .build_cartesian_2d(0.0..x_max, y_min..y_max)?;
+        chart
+            .configure_mesh()
+            .x_desc("cumulative supply")
+            .y_desc("price")
+            .draw()?;
+        for (data, &(_, label, color)) in series.iter().zip(curves) {
+            chart
+                .draw_series(LineSeries::new(data.iter().copied(), color.stroke_width(2)))?
+                .label(label)
+                .legend(move |(x, y)| {
+                    PathElement::new([(x, y), (x + 20, y)], color.stroke_width(2))
+                });
+        }
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+    })
 }
 
 /// Generates a line chart showing token distribution across bins
-pub fn plot_tokens_per_bin<C: Curve>(c: &C, bins: i64, out_path: &str) -> Result<()> {
-    let root = BitMapBackend::new(out_path, (1200, 700)).into_drawing_area();
-    root.fill(&WHITE)?;
-    let pts: Vec<(f64, f64)> = (0..bins).map(|i| (i as f64, c.delta_x_of_bin(i))).collect();
-    let x_max = (bins as f64).max(1.0);
+pub fn plot_tokens_per_bin<C: Curve>(
+    c: &C,
+    bins: i64,
+    max_points: usize,
+    out_path: &str,
+) -> Result<()> {
+    let lo = c.first_bin();
+    // Each sampled bin spans to the next one (the last to `bins`), so a single bin
+    // still draws a visible step
+    let mut samples = sample_bins(lo..bins, max_points).peekable();
+    let mut pts: Vec<(f64, f64)> = Vec::with_capacity(2 * max_points.max(2));
+    while let Some(i) = samples.next() {
+        let dx = c.delta_x_of_bin(i);
+        let end = samples.peek().copied().unwrap_or(bins);
+        pts.extend([(i as f64, dx), (end as f64, dx)]);
+    }
+    let x_max = (bins as f64).max(lo as f64 + 1.0);
     let y_max = pts.iter().map(|(_, y)| *y).fold(0.0, f64::max).max(1e-12);
-    let mut chart = ChartBuilder::on(&root)
-        .margin(20)
-        .caption("Tokens per Bin (ΔX_i)", ("sans-serif", 28))
-        .x_label_area_size(40)
-        .y_label_area_size(60)
-        .build_cartesian_2d(0.0..x_max, 0.0..(y_max * 1.05))?;
-    chart.configure_mesh().draw()?;
-    chart.draw_series(LineSeries::new(pts, &BLACK))?;
-    root.present()?;
-    Ok(())
+    with_chart_area!(out_path, |root| {
+        let mut chart = chart_builder(&root, "Tokens per Bin (ΔX_i)")
+            .build_cartesian_2d(lo as f64..x_max, 0.0..(y_max * 1.05))?;
+        chart.configure_mesh().draw()?;
+        chart.draw_series(LineSeries::new(pts, &BLACK))?;
+    })
+}
+
+/// Fee response over `va ∈ [0, 50]`: the capped total beside its base and
+/// variable components, a dashed line at `max_fee_rate` and the region where the
+/// cap (or its soft ramp) binds shaded
+pub fn plot_fee_components(fees: &DlmmFeeParams, out_path: &str) -> Result<()> {
+    let vas: Vec<f64> = (0..=500).map(|v| v as f64 / 10.0).collect();
+    let base = fees.base_fee_rate();
+    let total: Vec<(f64, f64)> = vas
+        .iter()
+        .map(|&va| (va, fees.total_fee_rate(va)))
+        .collect();
+    let x_max = 50.0;
+    let cap = fees.max_fee_rate;
+    let y_top = total
+        .iter()
+        .map(|(_, y)| *y)
+        .fold(cap.max(base), f64::max)
+        .max(1e-12)
+        * 1.15;
+    // The variable part grows as va², so stop it at the top of the chart
+    let variable: Vec<(f64, f64)> = vas
+        .iter()
+        .map(|&va| (va, fees.variable_fee_rate(va)))
+        .take_while(|(_, y)| *y <= y_top)
+        .collect();
+    let capped_from = vas
+        .iter()
+        .zip(&total)
+        .find(|(&va, (_, t))| base + fees.variable_fee_rate(va) > *t)
+        .map(|(&va, _)| va);
+    with_chart_area!(out_path, |root| {
+        let mut chart = chart_builder(&root, "Fee vs Volatility Accumulator")
+            .build_cartesian_2d(0.0..x_max, 0.0..y_top)?;
+        chart
+            .configure_mesh()
+            .x_desc("volatility accumulator (bins)")
+            .y_desc("fee rate")
+            .draw()?;
+        if let Some(va) = capped_from {
+            let shade = RED.mix(0.12);
+            chart
+                .draw_series([Rectangle::new([(va, 0.0), (x_max, y_top)], shade.filled())])?
+                .label("capped")
+                .legend(move |(x, y)| {
+                    Rectangle::new([(x, y - 5), (x + 20, y + 5)], shade.filled())
+                });
+        }
+        let cap_style = RED.stroke_width(2);
+        chart
+            .draw_series(DashedLineSeries::new(
+                [(0.0, cap), (x_max, cap)],
+                8,
+                6,
+                cap_style,
+            ))?
+            .label(format!("max_fee_rate = {}", cap))
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], cap_style));
+        let series = [
+            (vec![(0.0, base), (x_max, base)], "base", SERIES_COLORS[0]),
+            (variable, "variable", SERIES_COLORS[2]),
+            (total, "total (capped)", BLACK),
+        ];
+        for (data, label, color) in series {
+            chart
+                .draw_series(LineSeries::new(data, color.stroke_width(2)))?
+                .label(label)
+                .legend(move |(x, y)| {
+                    PathElement::new([(x, y), (x + 20, y)], color.stroke_width(2))
+                });
+        }
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+    })
 }
 
 /// Generates a chart showing fee rate as a function of volatility accumulator
-pub fn plot_fee_vs_vol(compute_fee: impl Fn(f64) -> f64, out_path: &str) -> Result<()> {
-    let root = BitMapBackend::new(out_path, (1200, 700)).into_drawing_area();
-    root.fill(&WHITE)?;
+#[deprecated(note = "use plot_fee_components, which also shows the cap and fee components")]
+pub fn plot_fee_vs_vol(fees: &dyn FeeModel, out_path: &str) -> Result<()> {
     let pts: Vec<(f64, f64)> = (0..=500)
         .map(|v| {
             let va = v as f64 / 10.0;
-            (va, compute_fee(va))
+            (va, fees.total_fee_rate(va))
         })
         .collect();
     let x_max = 50.0;
     let y_max = pts.iter().map(|(_, y)| *y).fold(0.0, f64::max).max(1e-12);
-    let mut chart = ChartBuilder::on(&root)
-        .margin(20)
-        .caption("Total Fee vs Volatility Accumulator", ("sans-serif", 28))
-        .x_label_area_size(40)
-        .y_label_area_size(60)
-        .build_cartesian_2d(0.0..x_max, 0.0..(y_max * 1.05))?;
-    chart.configure_mesh().draw()?;
-    chart.draw_series(LineSeries::new(pts, &BLACK))?;
-    root.present()?;
-    Ok(())
+    with_chart_area!(out_path, |root| {
+        let mut chart = chart_builder(&root, "Total Fee vs Volatility Accumulator")
+            .build_cartesian_2d(0.0..x_max, 0.0..(y_max * 1.05))?;
+        chart.configure_mesh().draw()?;
+        chart.draw_series(LineSeries::new(pts, &BLACK))?;
+    })
+}
+
+/// Filled depth chart from [`crate::dlmm::depth_chart`] points: bids (priced
+/// below `active_price`) in green, asks in red
+pub fn plot_depth_chart(
+    depth: &[(f64, f64)],
+    active_price: f64,
+    max_points: usize,
+    out_path: &str,
+) -> Result<()> {
+    // Each side gets half of `max_points`, keeping its own first and last point;
+    // data from `depth_chart_sampled` with the same cap already fits
+    let sample = |side: &[(f64, f64)]| -> Vec<(f64, f64)> {
+        sample_bins(0..side.len() as i64, max_points / 2)
+            .map(|i| side[i as usize])
+            .collect()
+    };
+    let split = depth.partition_point(|&(p, _)| p < active_price);
+    let (bids, asks) = (sample(&depth[..split]), sample(&depth[split..]));
+    let x_min = depth.first().map(|(p, _)| *p).unwrap_or(0.0);
+    let x_max = depth
+        .last()
+        .map(|(p, _)| *p)
+        .unwrap_or(1.0)
+        .max(x_min + 1e-12);
+    let y_max = depth.iter().map(|(_, d)| *d).fold(0.0, f64::max).max(1e-12);
+    with_chart_area!(out_path, |root| {
+        let mut chart = chart_builder(&root, "Liquidity Depth (cumulative tokens)")
+            .build_cartesian_2d(x_min..x_max, 0.0..(y_max * 1.05))?;
+        chart
+            .configure_mesh()
+            .x_desc("price")
+            .y_desc("depth")
+            .draw()?;
+        chart.draw_series(AreaSeries::new(bids, 0.0, GREEN.mix(0.3)).border_style(GREEN))?;
+        chart.draw_series(AreaSeries::new(asks, 0.0, RED.mix(0.3)).border_style(RED))?;
+    })
 }
@@ -0,0 +1,61 @@
+//! Line-oriented query loop over a built curve
+
+use crate::curves::Curve;
+use crate::sim::{execute_buy, BuyCursor};
+use anyhow::Result;
+use std::io::{BufRead, Write};
+
+const USAGE: &str =
+    "commands: price <bin> | bin-of <price> | supply <n> | buy <quote> | help | quit";
+
+/// Reads commands from `input` until EOF or `quit`, writing one answer per line to `out`.
+///
+/// `buy` fills consecutive bins starting at the active bin (initially the curve's first bin) and advances
+/// it, so repeated buys walk up the curve. Malformed lines print a usage hint.
+pub fn run_repl<C: Curve, R: BufRead, W: Write>(
+    c: &C,
+    bins: i64,
+    input: R,
+    mut out: W,
+) -> Result<()> {
+    let mut cursor = BuyCursor::at_first_bin(c);
+    for line in input.lines() {
+        let line = line?;
+        let mut words = line.split_whitespace();
+        let (Some(cmd), arg) = (words.next(), words.next()) else {
+            continue;
+        };
+        match (cmd, arg) {
+            ("quit" | "exit", _) => break,
+            ("help", _) => writeln!(out, "{}", USAGE)?,
+            ("price", Some(a)) => match a.parse::<i64>() {
+                Ok(i) => writeln!(out, "P_{} = {:.12}", i, c.price_of_bin(i))?,
+                Err(_) => writeln!(out, "bad bin {:?}; {}", a, USAGE)?,
+            },
+            ("bin-of", Some(a)) => match a.parse::<f64>() {
+                Ok(p) => match c.bin_of_price(p, bins) {
+                    Some(i) => writeln!(out, "bin {} (P={:.12})", i, c.price_of_bin(i))?,
+                    None => writeln!(out, "price {} is below P_0", p)?,
+                },
+                Err(_) => writeln!(out, "bad price {:?}; {}", a, USAGE)?,
+            },
+            ("supply", Some(a)) => match a.parse::<i64>() {
+                Ok(n) => writeln!(out, "S_{} = {:.6}", n, c.cumulative_supply(n))?,
+                Err(_) => writeln!(out, "bad bin count {:?}; {}", a, USAGE)?,
+            },
+            ("buy", Some(a)) => match a.parse::<f64>() {
+                Ok(q) if q >= 0.0 => {
+                    let fill = execute_buy(c, bins, &mut cursor, q);
+                    writeln!(
+                        out,
+                        "tokens={:.6} spent={:.6} unspent={:.6} active_bin={}",
+                        fill.tokens, fill.quote_spent, fill.quote_unspent, cursor.bin
+                    )?;
+                }
+                _ => writeln!(out, "bad quote amount {:?}; {}", a, USAGE)?,
+            },
+            _ => writeln!(out, "{}", USAGE)?,
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,77 @@
+//! Launch dry-runs: buys walking up the curve under fees and the launch surcharge
+
+use crate::curves::Curve;
+pub use crate::curves::{execute_buy, BuyCursor, Fill};
+use crate::dlmm::{FeeModel, LaunchPhasePolicy};
+use serde::{Deserialize, Serialize};
+
+/// One buy in a simulated launch
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TradeEvent {
+    /// Seconds since launch
+    pub timestamp: f64,
+    /// Trader address, checked against the allowlist
+    pub address: String,
+    /// Gross quote paid in, before surcharge and fee
+    pub quote_in: f64,
+}
+
+/// What a [`TradeEvent`] paid and received
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TradeOutcome {
+    /// Seconds since launch
+    pub timestamp: f64,
+    /// Trader address
+    pub address: String,
+    /// Gross quote paid in
+    pub quote_in: f64,
+    /// Surcharge τ(t) applied, in percent (0 for allowlisted addresses)
+    pub surcharge_pct: f64,
+    /// Quote taken by the surcharge
+    pub surcharge_paid: f64,
+    /// Quote taken by the swap fee
+    pub fee_paid: f64,
+    /// Tokens received
+    pub tokens_out: f64,
+    /// Quote returned because the schedule ran out of bins
+    pub quote_unspent: f64,
+    /// Active bin after the trade
+    pub active_bin: i64,
+}
+
+/// Replays `trades` in order against `c`, starting at its first bin.
+///
+/// Each trade pays the surcharge `τ(timestamp)` unless allowlisted, then the fee at
+/// volatility `va` on the remainder, and buys with what is left.
+pub fn simulate_trades<C: Curve>(
+    c: &C,
+    bins: i64,
+    fees: &dyn FeeModel,
+    va: f64,
+    policy: &LaunchPhasePolicy,
+    trades: &[TradeEvent],
+) -> Vec<TradeOutcome> {
+    let mut cursor = BuyCursor::at_first_bin(c);
+    let fee_rate = fees.total_fee_rate(va);
+    trades
+        .iter()
+        .map(|t| {
+            let surcharge_pct = policy.effective_tau(&t.address, t.timestamp);
+            let quote_in = t.quote_in.max(0.0);
+            let surcharge_paid = quote_in * surcharge_pct / 100.0;
+            let fee_paid = (quote_in - surcharge_paid) * fee_rate;
+            let fill = execute_buy(c, bins, &mut cursor, quote_in - surcharge_paid - fee_paid);
+            TradeOutcome {
+                timestamp: t.timestamp,
+                address: t.address.clone(),
+                quote_in: t.quote_in,
+                surcharge_pct,
+                surcharge_paid,
+                fee_paid,
+                tokens_out: fill.tokens,
+                quote_unspent: fill.quote_unspent,
+                active_bin: cursor.bin,
+            }
+        })
+        .collect()
+}
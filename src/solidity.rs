@@ -0,0 +1,254 @@
+//! Solidity constants for on-chain deployment of a schedule
+//!
+//! Prices and allocations are emitted as fixed-point `uint256` literals, split
+//! across libraries of at most `chunk_bins` bins so each stays deployable.
+
+use crate::curves::{CompensatedSum, Curve};
+use anyhow::{anyhow, Result};
+use std::io::Write;
+
+/// EIP-170 cap on deployed contract bytecode, in bytes
+pub const CONTRACT_SIZE_LIMIT: usize = 24_576;
+
+/// Bins per generated library unless configured otherwise
+pub const DEFAULT_CHUNK_BINS: i64 = 256;
+
+/// Rough per-library bytecode beyond the array literals (dispatch, ABI encoding)
+const CHUNK_OVERHEAD_BYTES: usize = 400;
+
+/// Fixed-point scaling and chunking for [`write_solidity`]
+#[derive(Clone, Copy, Debug)]
+pub struct SolidityOptions {
+    /// Values are written as round(x · 10^decimals)
+    pub decimals: u32,
+    /// Bins per `ScheduleChunkN` library
+    pub chunk_bins: i64,
+}
+
+impl Default for SolidityOptions {
+    fn default() -> Self {
+        SolidityOptions {
+            decimals: 18,
+            chunk_bins: DEFAULT_CHUNK_BINS,
+        }
+    }
+}
+
+/// What [`write_solidity`] emitted
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolidityReport {
+    /// Bin range `[start, end)` of each `ScheduleChunkN` library
+    pub chunks: Vec<(i64, i64)>,
+    /// Estimated deployed bytecode per chunk, comparable to [`CONTRACT_SIZE_LIMIT`]
+    pub chunk_bytes: Vec<usize>,
+    /// Compensated Σ ΔX_i over the emitted bins
+    pub total_supply: f64,
+    /// Compensated Σ P_i·ΔX_i over the emitted bins
+    pub total_revenue: f64,
+}
+
+/// 2^256 in decimal, one past the largest `uint256`
+const UINT256_LIMIT: &str =
+    "115792089237316195423570985008687907853269984665640564039457584007913129639936";
+
+/// Decimal `uint256` literal for x · 10^decimals, rounded half up.
+///
+/// Works on the shortest decimal that round-trips `x` rather than on the f64
+/// product, so `1e9` at 18 decimals is exactly `1` followed by 27 zeros. Errors
+/// for negative, non-finite or ≥ 2^256 scaled values.
+pub fn to_fixed_point(x: f64, decimals: u32) -> Result<String> {
+    let overflow = || anyhow!("{} does not fit a uint256 at {} decimals", x, decimals);
+    if !x.is_finite() || x < 0.0 {
+        return Err(overflow());
+    }
+    // `{:e}` gives d.ddd…e±E with the fewest digits that round-trip
+    let sci = format!("{:e}", x);
+    let (mantissa, exp) = sci.split_once('e').ok_or_else(overflow)?;
+    let mut digits: Vec<u8> = mantissa.bytes().filter(|b| b.is_ascii_digit()).collect();
+    let exp: i64 = exp.parse().map_err(|_| overflow())?;
+    // value = digits · 10^shift once scaled
+    let shift = exp - (digits.len() as i64 - 1) + decimals as i64;
+    if shift >= 0 {
+        digits.resize(digits.len() + shift as usize, b'0');
+    } else {
+        let keep = digits.len() as i64 + shift;
+        let round_up = keep >= 0 && digits.get(keep as usize).is_some_and(|&d| d >= b'5');
+        digits.truncate(keep.max(0) as usize);
+        if round_up {
+            // Propagate the carry from the last kept digit
+            let mut i = digits.len();
+            loop {
+                if i == 0 {
+                    digits.insert(0, b'1');
+                    break;
+                }
+                i -= 1;
+                if digits[i] == b'9' {
+                    digits[i] = b'0';
+                } else {
+                    digits[i] += 1;
+                    break;
+                }
+            }
+        }
+    }
+    let start = digits
+        .iter()
+        .position(|&d| d != b'0')
+        .unwrap_or(digits.len());
+    let literal = match std::str::from_utf8(&digits[start..]).map_err(|_| overflow())? {
+        "" => "0",
+        s => s,
+    };
+    let too_big = literal.len() > UINT256_LIMIT.len()
+        || (literal.len() == UINT256_LIMIT.len() && literal >= UINT256_LIMIT);
+    if too_big {
+        return Err(overflow());
+    }
+    Ok(literal.to_string())
+}
+
+/// Rough bytecode for one array element: the PUSH of its minimal big-endian
+/// width plus the offset arithmetic and MSTORE around it
+fn element_bytes(literal: &str) -> usize {
+    let v: f64 = literal.parse().unwrap_or(0.0);
+    let width = if v < 1.0 {
+        0
+    } else {
+        (v.log2().floor() as usize) / 8 + 1
+    };
+    width + 6
+}
+
+/// Writes `Schedule.sol` source for bins `[c.first_bin(), bins)`: a
+/// `ScheduleParams` library of grid constants and one `ScheduleChunkN` library
+/// per `opts.chunk_bins` bins with `prices()` and `allocations()` returning
+/// fixed-size `uint256` arrays.
+pub fn write_solidity<C: Curve, W: Write>(
+    mut out: W,
+    c: &C,
+    bins: i64,
+    bin_step_bps: f64,
+    opts: &SolidityOptions,
+) -> Result<SolidityReport> {
+    if opts.chunk_bins < 1 {
+        return Err(anyhow!("chunk_bins must be ≥ 1 (got {})", opts.chunk_bins));
+    }
+    let lo = c.first_bin();
+    let hi = bins.max(lo);
+    let chunks: Vec<(i64, i64)> = (lo..hi)
+        .step_by(opts.chunk_bins as usize)
+        .map(|start| (start, (start + opts.chunk_bins).min(hi)))
+        .collect();
+
+    writeln!(out, "// SPDX-License-Identifier: MIT")?;
+    writeln!(out, "pragma solidity ^0.8.0;")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "// Generated by bcurve from {}: bins [{}, {}) in {} chunk(s)",
+        c.name(),
+        lo,
+        hi,
+        chunks.len()
+    )?;
+    writeln!(
+        out,
+        "// Fixed point: values are round(x * 10**DECIMALS); allocations are ΔX_i per bin."
+    )?;
+    writeln!(out)?;
+    writeln!(out, "library ScheduleParams {{")?;
+    writeln!(
+        out,
+        "    uint256 internal constant DECIMALS = {};",
+        opts.decimals
+    )?;
+    writeln!(
+        out,
+        "    /// P0 and BIN_STEP_BPS are scaled by 10**DECIMALS like the arrays"
+    )?;
+    writeln!(
+        out,
+        "    uint256 internal constant P0 = {};",
+        to_fixed_point(c.price_of_bin(0), opts.decimals)?
+    )?;
+    writeln!(
+        out,
+        "    uint256 internal constant BIN_STEP_BPS = {};",
+        to_fixed_point(bin_step_bps, opts.decimals)?
+    )?;
+    writeln!(out, "    int256 internal constant FIRST_BIN = {};", lo)?;
+    writeln!(
+        out,
+        "    uint256 internal constant BIN_COUNT = {};",
+        hi - lo
+    )?;
+    writeln!(
+        out,
+        "    uint256 internal constant CHUNK_BINS = {};",
+        opts.chunk_bins
+    )?;
+    writeln!(
+        out,
+        "    uint256 internal constant CHUNK_COUNT = {};",
+        chunks.len()
+    )?;
+    writeln!(out, "}}")?;
+
+    let (mut s_cum, mut r_cum) = (CompensatedSum::default(), CompensatedSum::default());
+    let mut chunk_bytes = Vec::with_capacity(chunks.len());
+    for (k, &(start, end)) in chunks.iter().enumerate() {
+        let mut prices = Vec::with_capacity((end - start) as usize);
+        let mut allocations = Vec::with_capacity((end - start) as usize);
+        for (i, p, dx) in c.bins_iter(start..end) {
+            prices.push(
+                to_fixed_point(p, opts.decimals).map_err(|e| anyhow!("bin {} price: {}", i, e))?,
+            );
+            allocations.push(
+                to_fixed_point(dx, opts.decimals)
+                    .map_err(|e| anyhow!("bin {} allocation: {}", i, e))?,
+            );
+            s_cum.add(dx);
+            r_cum.add(p * dx);
+        }
+        chunk_bytes.push(
+            CHUNK_OVERHEAD_BYTES
+                + prices
+                    .iter()
+                    .chain(&allocations)
+                    .map(|v| element_bytes(v))
+                    .sum::<usize>(),
+        );
+
+        writeln!(out)?;
+        writeln!(out, "/// Bins [{}, {})", start, end)?;
+        writeln!(out, "library ScheduleChunk{} {{", k)?;
+        for (name, values) in [("prices", &prices), ("allocations", &allocations)] {
+            writeln!(
+                out,
+                "    function {}() public pure returns (uint256[{}] memory) {{",
+                name,
+                values.len()
+            )?;
+            writeln!(out, "        return [")?;
+            for (j, v) in values.iter().enumerate() {
+                let sep = if j + 1 == values.len() { "" } else { "," };
+                if j == 0 {
+                    writeln!(out, "            uint256({}){}", v, sep)?;
+                } else {
+                    writeln!(out, "            {}{}", v, sep)?;
+                }
+            }
+            writeln!(out, "        ];")?;
+            writeln!(out, "    }}")?;
+        }
+        writeln!(out, "}}")?;
+    }
+    out.flush()?;
+    Ok(SolidityReport {
+        chunks,
+        chunk_bytes,
+        total_supply: s_cum.value(),
+        total_revenue: r_cum.value(),
+    })
+}
@@ -1,10 +1,11 @@
 //! Verification tools for curve properties and numerical accuracy
 
-use crate::curves::{Curve, Geometric};
+use crate::curves::{CompensatedSum, Curve, Geometric, Grid, Linear, LogisticS};
 use anyhow::{anyhow, Result};
+use serde::Serialize;
 
 /// Verification report containing numerical checks and validation results
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Report {
     /// Number of bins checked
     pub bins: i64,
@@ -14,51 +15,535 @@ pub struct Report {
     pub supply_closed: Option<f64>,
     /// Relative error between sum and closed form
     pub rel_err_supply: Option<f64>,
-    /// Whether price monotonicity holds
+    /// Whether price monotonicity holds (no step falls by more than [`MONOTONE_REL_TOL`])
     pub monotone_ok: bool,
+    /// Steps where P_{i+1} equals P_i at f64 precision, counted apart from decreases
+    pub non_strict_steps: i64,
+    /// Whether cumulative revenue Σ P_i·ΔX_i never decreases (every P_i·ΔX_i ≥ 0)
+    pub revenue_monotone_ok: bool,
+    /// Whether ΔX_i never increases, so each price step buys less supply: price in
+    /// grid steps is convex in supply (S_n is concave in n), see
+    /// [`check_allocation_shape`]
+    pub convex_ok: bool,
+    /// Whether ΔX_i never decreases, so each price step buys more supply: price in
+    /// grid steps is concave in supply
+    pub concave_ok: bool,
+    /// Which regime [`convex_ok`](Report::convex_ok) and
+    /// [`concave_ok`](Report::concave_ok) detected
+    pub shape: AllocationShape,
+    /// Spread of the per-bin allocation ΔX_i
+    pub allocation: AllocationStats,
+    /// Logistic on the DLMM grid only: how the tail meets `p_max`
+    pub asymptote: Option<AsymptoteCheck>,
+    /// Geometric only: ΔX_0 / ΔX_{bins-1}, see [`allocation_ratio`]
+    pub allocation_ratio: Option<f64>,
+    /// Closed-form cumulative revenue Σ P_i·ΔX_i (if available)
+    pub revenue_closed: Option<f64>,
+    /// Relative error between the compensated revenue sum and its closed form
+    pub rel_err_revenue: Option<f64>,
+}
+
+/// Single-pass running mean and variance (Welford), numerically stable without
+/// storing the samples
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Welford {
+    n: u64,
+    mean: f64,
+    m2: f64,
+}
+impl Welford {
+    /// Adds one sample
+    pub fn push(&mut self, x: f64) {
+        self.n += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.n as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+    /// Samples pushed so far
+    pub fn count(&self) -> u64 {
+        self.n
+    }
+    /// Running mean (0 before any sample)
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+    /// Population variance Σ(x - mean)²/n (0 before any sample)
+    pub fn variance(&self) -> f64 {
+        if self.n == 0 {
+            0.0
+        } else {
+            self.m2 / self.n as f64
+        }
+    }
+    /// Coefficient of variation σ/mean; `None` when the mean is 0
+    pub fn cv(&self) -> Option<f64> {
+        (self.mean != 0.0).then(|| self.variance().sqrt() / self.mean.abs())
+    }
+}
+
+/// Mean, variance and coefficient of variation of ΔX_i across a schedule; a
+/// high CV means the allocation is concentrated in few bins
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct AllocationStats {
+    /// Mean ΔX_i
+    pub mean: f64,
+    /// Population variance of ΔX_i
+    pub variance: f64,
+    /// σ / mean, or `None` for an all-zero schedule
+    pub cv: Option<f64>,
+}
+impl From<&Welford> for AllocationStats {
+    fn from(w: &Welford) -> Self {
+        AllocationStats {
+            mean: w.mean(),
+            variance: w.variance(),
+            cv: w.cv(),
+        }
+    }
+}
+
+/// [`AllocationStats`] of ΔX_i over bins `[first_bin, bins)` in one pass
+pub fn allocation_stats<C: Curve>(c: &C, bins: i64) -> AllocationStats {
+    let mut w = Welford::default();
+    for i in c.first_bin()..bins {
+        w.push(c.delta_x_of_bin(i));
+    }
+    AllocationStats::from(&w)
+}
+
+/// Relative slack below which a non-increasing price step counts as an f64 collision
+/// rather than a genuine decrease
+pub const MONOTONE_REL_TOL: f64 = 1e-15;
+
+/// Checks P_i over `[first_bin, bins)`: returns `(monotone_ok, non_strict_steps)`,
+/// treating P_{i+1} ≥ P_i·(1 - [`MONOTONE_REL_TOL`]) as non-decreasing.
+pub fn check_monotone<C: Curve>(c: &C, bins: i64) -> (bool, i64) {
+    let mut ok = true;
+    let mut non_strict = 0;
+    let lo = c.first_bin();
+    let mut prev = c.price_of_bin(lo);
+    for i in lo + 1..bins {
+        let p = c.price_of_bin(i);
+        if p <= prev {
+            if p >= prev * (1.0 - MONOTONE_REL_TOL) {
+                non_strict += 1;
+            } else {
+                ok = false;
+            }
+        }
+        prev = p;
+    }
+    (ok, non_strict)
+}
+
+/// Relative slack below which a step in ΔX_i counts as flat in [`check_allocation_shape`],
+/// for rounding in ΔX_0·r^i
+pub const SHAPE_REL_TOL: f64 = 1e-12;
+
+/// Allocation regime of a schedule, from whether ΔX_i decays or grows, i.e. the
+/// curvature of price in grid steps against supply
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AllocationShape {
+    /// ΔX_i is constant (both convex and concave), e.g. geometric θ = 1
+    Flat,
+    /// ΔX_i decays (convex price), e.g. geometric θ < 1
+    Decaying,
+    /// ΔX_i grows (concave price), e.g. geometric θ > 1
+    Growing,
+    /// ΔX_i rises and falls, e.g. a logistic across its inflection
+    Mixed,
+}
+impl AllocationShape {
+    /// Regime named by a `(convex_ok, concave_ok)` pair from [`check_allocation_shape`]
+    pub fn from_checks(convex_ok: bool, concave_ok: bool) -> Self {
+        match (convex_ok, concave_ok) {
+            (true, true) => AllocationShape::Flat,
+            (true, false) => AllocationShape::Decaying,
+            (false, true) => AllocationShape::Growing,
+            (false, false) => AllocationShape::Mixed,
+        }
+    }
+}
+
+/// Checks ΔX_i over `[first_bin, bins)`: returns `(convex_ok, concave_ok)`, whether
+/// ΔX_i never increases and never decreases. Steps within [`SHAPE_REL_TOL`] of
+/// ΔX_i count as flat and break neither.
+///
+/// The names refer to P(S) with price measured in grid steps, the bin index i(S)
+/// (ln P on a price-spaced grid): ΔX_i is the supply per step, so non-increasing
+/// ΔX_i makes i(S) convex. S_n as a function of n curves the other way.
+pub fn check_allocation_shape<C: Curve>(c: &C, bins: i64) -> (bool, bool) {
+    let mut convex_ok = true;
+    let mut concave_ok = true;
+    let lo = c.first_bin();
+    let mut prev = c.delta_x_of_bin(lo);
+    for i in lo + 1..bins {
+        let dx = c.delta_x_of_bin(i);
+        let slack = prev.abs() * SHAPE_REL_TOL;
+        if dx > prev + slack {
+            convex_ok = false;
+        } else if dx < prev - slack {
+            concave_ok = false;
+        }
+        prev = dx;
+    }
+    (convex_ok, concave_ok)
+}
+
+/// True when every bin's revenue P_i·ΔX_i over `[first_bin, bins)` is non-negative,
+/// so `revenue_cum` never decreases
+pub fn check_revenue_monotone<C: Curve>(c: &C, bins: i64) -> bool {
+    (c.first_bin()..bins).all(|i| c.price_of_bin(i) * c.delta_x_of_bin(i) >= 0.0)
 }
 
 /// Verify S_n = Σ_{i<n} ΔX_0 r^i against the closed form and check P_i monotonicity
+/// (see [`check_monotone`])
 pub fn verify_geometric(c: &Geometric, bins: i64) -> Result<Report> {
-    let mut s_sum = 0.0_f64;
-    let mut comp = 0.0_f64;
+    let rep = verify_closed_form(c, bins, c.s_n_closed(bins), Some(c.revenue_closed(bins)))?;
+    Ok(Report {
+        allocation_ratio: Some(allocation_ratio(c, bins)),
+        ..rep
+    })
+}
 
-    let mut prev_px = f64::NEG_INFINITY;
-    let mut monotone_ok = true;
+/// Verify S_n = n·base + slope·n(n-1)/2 against the summed allocations and check
+/// P_i monotonicity (see [`check_monotone`])
+pub fn verify_linear(c: &Linear, bins: i64) -> Result<Report> {
+    verify_closed_form(c, bins, c.cumulative_supply(bins), None)
+}
 
-    for i in 0..bins {
+/// Verify that Σ ΔX_i telescopes to S(P_{bins-1}) - S(P_0), check P_i monotonicity
+/// (see [`check_monotone`]) and how the tail meets `p_max` (see [`check_asymptote`]).
+///
+/// S(P) is clamped just inside `(p_min, p_max)`, so bins priced outside the band
+/// hold no supply instead of an infinite one; a non-finite S, e.g. from k = 0, is
+/// an error.
+pub fn verify_logistic(c: &LogisticS, bins: i64) -> Result<Report> {
+    if bins < 2 {
+        return Err(anyhow!("need ≥2 bins for logistic (got {})", bins));
+    }
+    let s_closed = c.s_of_p(c.price_of_bin(bins - 1)) - c.s_of_p(c.grid.p0);
+    if !s_closed.is_finite() {
+        return Err(anyhow!(
+            "S(P_{}) - S(P_0) is not finite ({})",
+            bins - 1,
+            s_closed
+        ));
+    }
+    let rep = verify_closed_form(c, bins, s_closed, None)?;
+    Ok(Report {
+        asymptote: Some(check_asymptote(c, bins)),
+        ..rep
+    })
+}
+
+/// Generic checks over `[first_bin, bins)` for any curve: compensated Σ ΔX_i,
+/// P_i monotonicity (see [`check_monotone`]), revenue monotonicity, the spread of
+/// ΔX_i and its convex/concave regime (see [`check_allocation_shape`]). Fails on a
+/// negative or non-finite ΔX_i; closed-form fields are left `None`.
+pub fn verify_curve<C: Curve>(c: &C, bins: i64) -> Result<Report> {
+    scan_curve(c, bins).map(|(rep, _)| rep)
+}
+
+/// [`verify_curve`] with `s_closed` (and `revenue_closed`) cross-checked against the
+/// compensated sums
+fn verify_closed_form<C: Curve>(
+    c: &C,
+    bins: i64,
+    s_closed: f64,
+    revenue_closed: Option<f64>,
+) -> Result<Report> {
+    let (rep, revenue_sum) = scan_curve(c, bins)?;
+    Ok(Report {
+        supply_closed: Some(s_closed),
+        rel_err_supply: Some(rel_err(rep.supply_sum, s_closed)),
+        revenue_closed,
+        rel_err_revenue: revenue_closed.map(|closed| rel_err(revenue_sum, closed)),
+        ..rep
+    })
+}
+
+/// |sum - closed| / |closed|, or 0 when the closed form is 0
+fn rel_err(sum: f64, closed: f64) -> f64 {
+    if closed.abs() > 0.0 {
+        (sum - closed).abs() / closed.abs()
+    } else {
+        0.0
+    }
+}
+
+/// One pass of [`verify_curve`], also returning the compensated Σ P_i·ΔX_i
+fn scan_curve<C: Curve>(c: &C, bins: i64) -> Result<(Report, f64)> {
+    let mut s_sum = CompensatedSum::default();
+    let mut revenue_sum = CompensatedSum::default();
+    let mut revenue_monotone_ok = true;
+    let mut allocation = Welford::default();
+
+    for i in c.first_bin()..bins {
         let dx = c.delta_x_of_bin(i);
+        if !dx.is_finite() {
+            return Err(anyhow!("ΔX_{} is not finite ({})", i, dx));
+        }
         if dx < 0.0 {
             return Err(anyhow!("ΔX_{} < 0", i));
         }
-        let t = s_sum + dx;
-        if s_sum.abs() >= dx.abs() {
-            comp += (s_sum - t) + dx;
-        } else {
-            comp += (dx - t) + s_sum;
+        allocation.push(dx);
+        let revenue = c.price_of_bin(i) * dx;
+        if revenue < 0.0 {
+            revenue_monotone_ok = false;
         }
-        s_sum = t;
+        s_sum.add(dx);
+        revenue_sum.add(revenue);
+    }
+    let (monotone_ok, non_strict_steps) = check_monotone(c, bins);
+    let (convex_ok, concave_ok) = check_allocation_shape(c, bins);
 
-        let p = c.price_of_bin(i);
-        if p <= prev_px {
-            monotone_ok = false;
+    let rep = Report {
+        bins,
+        supply_sum: s_sum.value(),
+        supply_closed: None,
+        rel_err_supply: None,
+        monotone_ok,
+        non_strict_steps,
+        revenue_monotone_ok,
+        convex_ok,
+        concave_ok,
+        shape: AllocationShape::from_checks(convex_ok, concave_ok),
+        allocation: AllocationStats::from(&allocation),
+        asymptote: None,
+        allocation_ratio: None,
+        revenue_closed: None,
+        rel_err_revenue: None,
+    };
+    Ok((rep, revenue_sum.value()))
+}
+
+/// Head-to-tail allocation ratio above which the CLI warns by default
+pub const DEFAULT_MAX_ALLOCATION_RATIO: f64 = 1e9;
+
+/// [`supply_integration_error`] above which the CLI warns that the bins are too
+/// coarse by default; about what a 100 bps step gives
+pub const DEFAULT_MAX_INTEGRATION_ERROR: f64 = 5e-3;
+
+/// ΔX of the first bin over ΔX of the last, `[first_bin, bins)`: how many times
+/// more the head bin holds than the tail bin.
+///
+/// For a geometric curve this is r^-(bins-1) = q^((1-θ)(bins-1)), so small θ or
+/// many bins leave the tail as dust; θ → 1 brings it toward 1. `+∞` when the last
+/// bin is empty.
+pub fn allocation_ratio<C: Curve>(c: &C, bins: i64) -> f64 {
+    let head = c.delta_x_of_bin(c.first_bin());
+    let tail = c.delta_x_of_bin(bins - 1);
+    if tail == 0.0 {
+        f64::INFINITY
+    } else {
+        head / tail
+    }
+}
+
+/// Gaps narrower than this many ULPs of the local price are treated as collisions
+pub const MIN_GAP_ULPS: f64 = 4.0;
+
+/// Smallest gap between consecutive bin prices, relative to f64 resolution
+#[derive(Clone, Copy, Debug)]
+pub struct PriceGap {
+    /// Lower bin i of the narrowest pair (i, i+1)
+    pub bin: i64,
+    /// P_{i+1} - P_i
+    pub gap: f64,
+    /// Gap measured in ULPs of P_i (≈ gap / (|P_i|·ε))
+    pub ulps: f64,
+}
+impl PriceGap {
+    /// True when adjacent prices are within [`MIN_GAP_ULPS`] of colliding,
+    /// suggesting `bin_step_bps` is too small for the price level
+    pub fn is_degenerate(&self) -> bool {
+        self.ulps < MIN_GAP_ULPS
+    }
+}
+
+/// Scans `[first_bin, bins)` for the narrowest consecutive price gap in ULP terms.
+/// Returns `None` when there are fewer than two bins.
+pub fn min_price_gap<C: Curve>(c: &C, bins: i64) -> Option<PriceGap> {
+    let mut best: Option<PriceGap> = None;
+    let lo = c.first_bin();
+    let mut prev = c.price_of_bin(lo);
+    for i in lo..bins - 1 {
+        let next = c.price_of_bin(i + 1);
+        let gap = next - prev;
+        let ulps = gap / (prev.abs() * f64::EPSILON);
+        if best.is_none_or(|b| ulps < b.ulps) {
+            best = Some(PriceGap { bin: i, gap, ulps });
         }
-        prev_px = p;
+        prev = next;
+    }
+    best
+}
+
+/// Counts bins in `[first_bin, bins)` whose allocation ΔX_i falls below `threshold`
+/// tokens (dust that rounds to nothing on-chain)
+pub fn count_dust_bins<C: Curve>(c: &C, bins: i64, threshold: f64) -> i64 {
+    (c.first_bin()..bins)
+        .filter(|&i| c.delta_x_of_bin(i) < threshold)
+        .count() as i64
+}
+
+/// Relative tolerance for [`reconcile_schedule`]
+pub const RECONCILE_REL_TOL: f64 = 1e-12;
+
+/// Re-derives Σ P_i·ΔX_i over `[first_bin, bins)` from the curve and compares it with the `revenue_cum` of the
+/// last row in the schedule CSV at `csv_path` (`#` metadata lines are skipped).
+///
+/// Returns the relative discrepancy, or an error if it exceeds [`RECONCILE_REL_TOL`].
+pub fn reconcile_schedule<C: Curve>(c: &C, bins: i64, csv_path: &str) -> Result<f64> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .comment(Some(b'#'))
+        .from_path(csv_path)?;
+    let col = rdr
+        .headers()?
+        .iter()
+        .position(|h| h == "revenue_cum")
+        .ok_or_else(|| anyhow!("{}: no revenue_cum column", csv_path))?;
+    let mut written = 0.0_f64;
+    let mut rows = 0_i64;
+    for rec in rdr.records() {
+        let rec = rec?;
+        written = rec
+            .get(col)
+            .ok_or_else(|| anyhow!("{}: short row {}", csv_path, rows))?
+            .parse()?;
+        rows += 1;
+    }
+    let lo = c.first_bin();
+    if rows != bins - lo {
+        return Err(anyhow!(
+            "{}: expected {} rows, found {}",
+            csv_path,
+            bins - lo,
+            rows
+        ));
     }
-    let s_sum = s_sum + comp;
 
-    let s_closed = c.s_n_closed(bins);
-    let rel = if s_closed.abs() > 0.0 {
-        (s_sum - s_closed).abs() / s_closed.abs()
+    let expected = (lo..bins)
+        .map(|i| c.price_of_bin(i) * c.delta_x_of_bin(i))
+        .sum::<CompensatedSum>()
+        .value();
+
+    let rel = if expected.abs() > 0.0 {
+        (written - expected).abs() / expected.abs()
     } else {
-        0.0
+        written.abs()
     };
+    if rel.is_nan() || rel > RECONCILE_REL_TOL {
+        return Err(anyhow!(
+            "{}: revenue_cum {} differs from recomputed {} (rel err {:.3e})",
+            csv_path,
+            written,
+            expected,
+            rel
+        ));
+    }
+    Ok(rel)
+}
 
-    Ok(Report {
-        bins,
-        supply_sum: s_sum,
-        supply_closed: Some(s_closed),
-        rel_err_supply: Some(rel),
-        monotone_ok,
-    })
+/// Trailing bins over which [`check_asymptote`] expects the marginal price to taper
+pub const ASYMPTOTE_TAIL_BINS: i64 = 8;
+
+/// Relative slack on the tail's marginal price steps, for rounding in S(P_{i+1}) - S(P_i)
+const ASYMPTOTE_SLOPE_REL_TOL: f64 = 1e-9;
+
+/// How a [`LogisticS`] schedule's tail meets its `p_max` asymptote
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct AsymptoteCheck {
+    /// No bin price reaches `p_max`, and the price per token (P_{i+1} - P_i)/ΔX_i
+    /// never rises across the last [`ASYMPTOTE_TAIL_BINS`] allocated bins past the
+    /// inflection
+    pub approaches_asymptote_ok: bool,
+    /// First bin with p0·q^i ≥ `p_max`. The grid does not know `p_max`, so S(P)
+    /// is clamped from here on and these bins hold no supply.
+    pub first_overshoot_bin: Option<i64>,
+}
+
+/// Checks that the logistic approaches but never exceeds `p_max` over bins
+/// `[0, bins)`.
+///
+/// ΔX_i itself grows toward the asymptote on a geometric grid (S(P) diverges at
+/// `p_max`), so the taper is checked on the marginal price dP/dS, which falls
+/// to zero past the inflection. A grid that overshoots `p_max` fails the check
+/// and reports its first overshooting bin.
+pub fn check_asymptote(c: &LogisticS, bins: i64) -> AsymptoteCheck {
+    let first_overshoot_bin = (0..bins).find(|&i| c.price_of_bin(i) >= c.p_max);
+    let (_, p_inflection) = c.inflection();
+    // The last bin is empty by construction
+    let hi = bins - 1;
+    let slopes: Vec<f64> = ((hi - ASYMPTOTE_TAIL_BINS).max(0)..hi)
+        .filter(|&i| c.price_of_bin(i) >= p_inflection)
+        .map(|i| (c.price_of_bin(i + 1) - c.price_of_bin(i)) / c.delta_x_of_bin(i))
+        .collect();
+    let tapering = slopes
+        .windows(2)
+        .all(|w| w[1] <= w[0] * (1.0 + ASYMPTOTE_SLOPE_REL_TOL));
+    AsymptoteCheck {
+        approaches_asymptote_ok: first_overshoot_bin.is_none() && tapering,
+        first_overshoot_bin,
+    }
+}
+
+/// Bin steps (bps) swept by `--accuracy-sweep`
+pub const ACCURACY_SWEEP_STEPS_BPS: [f64; 8] = [1.0, 2.0, 5.0, 10.0, 25.0, 50.0, 100.0, 200.0];
+
+/// ln(1 + e^x) without overflow
+fn softplus(x: f64) -> f64 {
+    x.max(0.0) + (-x.abs()).exp().ln_1p()
+}
+
+/// Relative discretization error of the binned logistic: Σ P_i·ΔX_i against the
+/// exact ∫ P(S) dS over the same supply span.
+///
+/// The integral is closed-form, P_min·ΔS + (P_max - P_min)/k · [softplus(k(S - S_mid))].
+pub fn supply_integration_error(c: &LogisticS, bins: i64) -> f64 {
+    binned_integration_error(c, c, bins)
+}
+
+/// [`supply_integration_error`] for any binning `c` of the logistic `target`, such
+/// as an [`AdaptiveLogistic`](crate::curves::AdaptiveLogistic) ladder.
+pub fn binned_integration_error<C: Curve>(target: &LogisticS, c: &C, bins: i64) -> f64 {
+    let s0 = target.s_of_p(c.price_of_bin(0));
+    let mut binned = 0.0;
+    let mut span = 0.0;
+    for i in 0..bins {
+        let dx = c.delta_x_of_bin(i);
+        binned += c.price_of_bin(i) * dx;
+        span += dx;
+    }
+    let s1 = s0 + span;
+    let t = target;
+    let exact = t.p_min * span
+        + (t.p_max - t.p_min) / t.k
+            * (softplus(t.k * (s1 - t.s_mid)) - softplus(t.k * (s0 - t.s_mid)));
+    if exact.abs() > 0.0 {
+        (binned - exact).abs() / exact.abs()
+    } else {
+        0.0
+    }
+}
+
+/// [`supply_integration_error`] for each bin step in `steps_bps`, holding the price
+/// range `[P_0, P_bins]` of `c` fixed. Returns `(bin_step_bps, rel_error)` pairs.
+pub fn accuracy_sweep(c: &LogisticS, bins: i64, steps_bps: &[f64]) -> Vec<(f64, f64)> {
+    let p_end = c.price_of_bin(bins);
+    steps_bps
+        .iter()
+        .map(|&bps| {
+            let grid = Grid::new(c.grid.p0, bps);
+            let n = grid.bins_for_price_range(p_end).max(2);
+            let resized = LogisticS {
+                grid,
+                bins: n,
+                ..*c
+            };
+            (bps, supply_integration_error(&resized, n))
+        })
+        .collect()
 }
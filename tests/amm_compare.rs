@@ -0,0 +1,69 @@
+use bcurve::amm_compare::{
+    uniswapv3_allocation, uniswapv3_liquidity_for_quote, uniswapv3_liquidity_for_tokens,
+};
+use bcurve::curves::{Curve, Geometric, Grid, GridSpacing};
+
+#[test]
+fn v3_allocation_sums_to_the_position_and_matches_theta_half() {
+    let grid = Grid {
+        p0: 0.01,
+        bin_step_bps: 25.0,
+        spacing: GridSpacing::Price,
+    };
+    let bins = 200;
+    let (p_lo, p_hi) = (grid.price_of_bin(0), grid.price_of_bin(bins));
+    let l = uniswapv3_liquidity_for_tokens(p_lo, p_hi, 1e6);
+    let v3 = uniswapv3_allocation(&grid, p_lo, p_hi, l, bins);
+    let total: f64 = v3.iter().sum();
+    assert!((total / 1e6 - 1.0).abs() < 1e-9);
+
+    // Constant L gives ΔX_i ∝ P_i^(-1/2): the geometric curve with θ = 1/2
+    let geo = Geometric {
+        grid,
+        theta: 0.5,
+        r0_quote: 1.0,
+    };
+    let geo = Geometric {
+        r0_quote: geo.solve_r0_from_supply(1e6, bins),
+        ..geo
+    };
+    for (i, v3_dx) in v3.iter().enumerate() {
+        let dx = geo.delta_x_of_bin(i as i64);
+        assert!((dx / v3_dx - 1.0).abs() < 1e-9, "bin {i}: {dx} vs {v3_dx}");
+    }
+
+    // Bins outside the range are empty, edge bins get the overlap
+    let inner = uniswapv3_allocation(&grid, grid.price_of_bin(10), grid.price_of_bin(20), l, bins);
+    assert!(inner[..10].iter().chain(&inner[20..]).all(|&x| x == 0.0));
+    assert!(inner[10..20].iter().all(|&x| x > 0.0));
+}
+
+#[test]
+fn v3_position_for_quote_raises_the_schedule_revenue() {
+    let geo = Geometric {
+        grid: Grid::new(0.01, 25.0),
+        theta: 0.5,
+        r0_quote: 100.0,
+    };
+    let bins = 200;
+    let (p_lo, p_hi) = (geo.price_of_bin(0), geo.price_of_bin(bins));
+    let revenue = geo.revenue_closed(bins);
+    let l = uniswapv3_liquidity_for_quote(p_lo, p_hi, revenue);
+    let v3 = uniswapv3_allocation(&geo.grid, p_lo, p_hi, l, bins);
+
+    // Selling bin i's v3 tokens across [P_i, P_{i+1}) raises L·(√P_{i+1} - √P_i)
+    let raised: f64 = (0..bins)
+        .map(|i| l * (geo.price_of_bin(i + 1).sqrt() - geo.price_of_bin(i).sqrt()))
+        .sum();
+    assert!((raised / revenue - 1.0).abs() < 1e-9);
+
+    // Same capital, not the same tokens: v3 sells each bin above P_i, so it
+    // needs fewer tokens than the schedule, by the same factor in every bin
+    let ratio = geo.delta_x_of_bin(0) / v3[0];
+    assert!(ratio > 1.0);
+    for (i, v3_dx) in v3.iter().enumerate() {
+        let dx = geo.delta_x_of_bin(i as i64);
+        assert!((dx / v3_dx / ratio - 1.0).abs() < 1e-9, "bin {i}");
+    }
+    assert!(v3.iter().sum::<f64>() < geo.s_n_closed(bins));
+}
@@ -1,4 +1,5 @@
-use bcurve::dlmm::LaunchPhasePolicy;
+use bcurve::curves::{Curve, CurveKind, Geometric, Grid, TwoSided};
+use bcurve::dlmm::{AddressMatch, Allowlist, LaunchPhasePolicy, TauDecay};
 use std::collections::HashSet;
 use std::fs;
 use std::process::Command;
@@ -37,6 +38,7 @@ fn schedule_has_one_header_and_no_demo_cols() {
         .filter(|l| l.starts_with("bin,price,delta_x"))
         .count();
     assert_eq!(header_count, 1, "CSV must have exactly one header row");
+    assert!(s.contains("\n# Bins: 5 (source: explicit)\n"));
 
     assert!(
         !s.contains("surcharge_launch_pct"),
@@ -54,7 +56,7 @@ fn schedule_has_one_header_and_no_demo_cols() {
         .expect("should have header line");
     assert_eq!(
         header_line,
-        "bin,price,delta_x,supply_cum,revenue_bin,revenue_cum,fee_base,fee_var,fee_total"
+        "bin,price,delta_x,supply_cum,revenue_bin,revenue_cum,fee_base,fee_var,fee_total,fee_total_bps"
     );
 
     // Clean up
@@ -62,6 +64,7 @@ fn schedule_has_one_header_and_no_demo_cols() {
 }
 
 #[test]
+#[allow(clippy::double_ended_iterator_last)]
 fn compensated_summation_accuracy() {
     let out = "out_accuracy_test";
     let status = Command::new("cargo")
@@ -120,10 +123,11 @@ fn launch_phase_policy_allowlist_functionality() {
     allowlist.insert("team_member_789".to_string());
 
     let policy = LaunchPhasePolicy {
-        allowlist,
+        allowlist: allowlist.iter().collect(),
         tau_start_pct: 50.0,
         tau_end_pct: 5.0,
         ramp_secs: 120.0,
+        tau_decay: TauDecay::Linear,
     };
 
     // Test allowlisted addresses
@@ -136,15 +140,1860 @@ fn launch_phase_policy_allowlist_functionality() {
     assert!(!policy.is_allowed("unknown_address"));
     assert!(!policy.is_allowed(""));
 
-    // Test case sensitivity
-    assert!(!policy.is_allowed("PRIVILEGED_TRADER_123"));
+    // Lookups ignore case by default, but not surrounding whitespace
+    assert!(policy.is_allowed("PRIVILEGED_TRADER_123"));
+    assert!(policy.is_allowed("Whale_Address_456"));
     assert!(!policy.is_allowed("privileged_trader_123 "));
 
     // Test allowlist size
     assert_eq!(policy.allowlist.len(), 3);
 
+    // Strict mode matches byte-for-byte
+    let mut exact = Allowlist::new(AddressMatch::Exact);
+    exact.extend(&allowlist);
+    let strict = LaunchPhasePolicy {
+        allowlist: exact,
+        ..policy.clone()
+    };
+    assert!(strict.is_allowed("privileged_trader_123"));
+    assert!(!strict.is_allowed("PRIVILEGED_TRADER_123"));
+
     // Test tau function works correctly
     assert_eq!(policy.tau(0.0), 50.0);
     assert_eq!(policy.tau(120.0), 5.0);
     assert_eq!(policy.tau(60.0), 27.5); // midpoint
 }
+
+#[test]
+fn effective_ask_column_applies_launch_surcharge() {
+    let out = "out_effective_ask_test";
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--mode",
+            "geometric",
+            "--bins",
+            "5",
+            "--r0",
+            "100.0",
+            "--tau-start-pct",
+            "50",
+            "--surcharge-at-secs",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ])
+        .status()
+        .expect("run bcurve");
+    assert!(status.success());
+
+    let s = fs::read_to_string(format!("{out}/schedule.csv")).unwrap();
+    let header_line = s.lines().find(|l| l.starts_with("bin,")).unwrap();
+    assert!(header_line.contains(",fee_total,effective_ask,"));
+    for line in s
+        .lines()
+        .filter(|l| l.starts_with(|c: char| c.is_ascii_digit()))
+    {
+        let fields: Vec<f64> = line.split(',').map(|f| f.parse().unwrap()).collect();
+        let (price, ask) = (fields[1], fields[9]);
+        assert!((ask - price * 1.5).abs() < 1e-12, "ask={ask} price={price}");
+    }
+
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn surcharged_fee_column_follows_the_surcharge_address() {
+    let out = "out_surcharged_fee_test";
+    let run = |extra: &[&str]| {
+        let status = Command::new("cargo")
+            .args([
+                "run",
+                "--release",
+                "--",
+                "--bins",
+                "5",
+                "--r0",
+                "100.0",
+                "--tau-start-pct",
+                "50",
+                "--surcharge-at-secs",
+                "--out-dir",
+                out,
+                "--no-draw",
+            ])
+            .args(extra)
+            .status()
+            .expect("run bcurve");
+        assert!(status.success());
+        let s = fs::read_to_string(format!("{out}/schedule.csv")).unwrap();
+        let header_line = s.lines().find(|l| l.starts_with("bin,")).unwrap();
+        assert!(header_line.ends_with(",fee_total_bps,fee_total_with_surcharge"));
+        s.lines()
+            .filter(|l| l.starts_with(|c: char| c.is_ascii_digit()))
+            .map(|line| line.split(',').map(|f| f.parse().unwrap()).collect())
+            .collect::<Vec<Vec<f64>>>()
+    };
+
+    for fields in run(&[]) {
+        let (fee, fee_surcharged) = (fields[8], fields[11]);
+        assert!((fee_surcharged - (fee + 0.5)).abs() < 1e-12);
+    }
+
+    // An allowlisted trader sees the un-surcharged ask and fee
+    let allowlist = format!("{out}_allowlist.txt");
+    fs::write(&allowlist, "team\n").unwrap();
+    let exempt = [
+        "--allowlist-path",
+        &allowlist,
+        "--surcharge-address",
+        "Team",
+    ];
+    for fields in run(&exempt) {
+        let (price, fee, ask, fee_surcharged) = (fields[1], fields[8], fields[9], fields[11]);
+        assert_eq!((ask, fee_surcharged), (price, fee));
+    }
+
+    let _ = fs::remove_dir_all(out);
+    let _ = fs::remove_file(allowlist);
+}
+
+#[test]
+fn protocol_share_splits_the_capped_fee_into_columns() {
+    let out = "out_protocol_share_test";
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--mode",
+            "geometric",
+            "--bins",
+            "5",
+            "--r0",
+            "100.0",
+            "--variable-fee-control",
+            "100",
+            "--vol-accum",
+            "1000",
+            "--max-fee-rate",
+            "0.05",
+            "--protocol-share",
+            "0.3",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ])
+        .status()
+        .expect("run bcurve");
+    assert!(status.success());
+
+    let s = fs::read_to_string(format!("{out}/schedule.csv")).unwrap();
+    let header_line = s.lines().find(|l| l.starts_with("bin,")).unwrap();
+    assert!(header_line.ends_with(",fee_total,fee_total_bps,fee_protocol,fee_lp"));
+    for line in s
+        .lines()
+        .filter(|l| l.starts_with(|c: char| c.is_ascii_digit()))
+    {
+        let fields: Vec<f64> = line.split(',').map(|f| f.parse().unwrap()).collect();
+        let (total, protocol, lp) = (fields[8], fields[10], fields[11]);
+        // The cap binds, and the split is of the capped fee
+        assert_eq!(total, 0.05);
+        assert_eq!(protocol + lp, total);
+        assert!((protocol - 0.015).abs() < 1e-15, "protocol={protocol}");
+    }
+
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--mode",
+            "geometric",
+            "--bins",
+            "5",
+            "--r0",
+            "100.0",
+            "--protocol-share",
+            "1.5",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ])
+        .status()
+        .expect("run bcurve");
+    assert!(!status.success());
+
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn chart_format_svg_writes_scalable_charts() {
+    let out = "out_svg_charts_test";
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--mode",
+            "geometric",
+            "--bins",
+            "20",
+            "--r0",
+            "100.0",
+            "--chart-format",
+            "svg",
+            "--out-dir",
+            out,
+        ])
+        .status()
+        .expect("run bcurve");
+    assert!(status.success());
+
+    for chart in ["price_vs_supply", "tokens_per_bin", "fee_vs_volatility"] {
+        let svg = fs::read_to_string(format!("{out}/{chart}.svg")).unwrap();
+        assert!(svg.contains("<svg"), "{chart}.svg is not SVG");
+        assert!(!fs::exists(format!("{out}/{chart}.png")).unwrap());
+    }
+
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn compare_theta_overlays_one_chart_per_run() {
+    let out = "out_compare_theta_test";
+    let run = |mode: &str| {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--release",
+                "--",
+                "--mode",
+                mode,
+                "--bins",
+                "200",
+                "--target-supply",
+                "1000000",
+                "--compare-theta",
+                "0.4,0.6,0.8",
+                "--chart-format",
+                "svg",
+                "--no-draw",
+                "--out-dir",
+                out,
+            ])
+            .output()
+            .expect("run bcurve")
+    };
+    assert!(run("geometric").status.success());
+
+    let svg = fs::read_to_string(format!("{out}/compare_theta.svg")).unwrap();
+    for label in ["θ=0.4", "θ=0.6", "θ=0.8"] {
+        assert!(svg.contains(label), "no legend entry {label}");
+    }
+    // --no-draw still skips the per-run charts
+    assert!(!fs::exists(format!("{out}/price_vs_supply.svg")).unwrap());
+
+    let logistic = run("logistic");
+    assert!(!logistic.status.success());
+    assert!(String::from_utf8_lossy(&logistic.stderr).contains("geometric mode"));
+
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn write_errors_name_the_target_path() {
+    let out = "out_error_context_test";
+    // A directory squatting on schedule.csv makes File::create fail
+    fs::create_dir_all(format!("{out}/schedule.csv")).unwrap();
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--bins",
+            "5",
+            "--r0",
+            "100.0",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ])
+        .output()
+        .expect("run bcurve");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(&format!("failed to write {out}/schedule.csv")),
+        "stderr: {stderr}"
+    );
+
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn oversized_bin_counts_are_refused() {
+    let out = "out_max_bins_test";
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--bins",
+            "100000000000",
+            "--r0",
+            "100.0",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ])
+        .output()
+        .expect("run bcurve");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("exceeds --max-bins"), "stderr: {stderr}");
+    assert!(!std::path::Path::new(out).exists());
+}
+
+#[test]
+fn fdv_csv_bin_counts_are_checked_against_max_bins() {
+    let out = "out_fdv_max_bins_test";
+    fs::create_dir_all(out).unwrap();
+    let path = format!("{out}/fdv.csv");
+    // price 0.02 lands near bin 693 of the default 10 bps grid from 0.01
+    fs::write(&path, "supply,fdv\n1000000,20000\n").unwrap();
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--mode",
+            "step",
+            "--fdv-csv",
+            &path,
+            "--max-bins",
+            "100",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ])
+        .output()
+        .expect("run bcurve");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("exceeds --max-bins 100"),
+        "stderr: {stderr}"
+    );
+    assert!(!std::path::Path::new(&format!("{out}/schedule.csv")).exists());
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn non_finite_end_price_is_an_error_not_a_panic() {
+    for end_price in ["inf", "NaN"] {
+        let out = format!("out_end_price_{end_price}_test");
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "--release",
+                "--",
+                "--end-price",
+                end_price,
+                "--target-supply",
+                "1000000",
+                "--out-dir",
+                &out,
+                "--no-draw",
+            ])
+            .output()
+            .expect("run bcurve");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("cannot size bins from end_price"),
+            "stderr: {stderr}"
+        );
+        assert!(!stderr.contains("panicked"), "stderr: {stderr}");
+        let _ = fs::remove_dir_all(&out);
+    }
+}
+
+#[test]
+fn single_bin_runs_in_geometric_and_errors_in_logistic() {
+    let out = "out_single_bin_test";
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--bins",
+            "1",
+            "--r0",
+            "100.0",
+            "--out-dir",
+            out,
+        ])
+        .status()
+        .expect("run bcurve");
+    assert!(status.success());
+    let s = fs::read_to_string(format!("{out}/schedule.csv")).unwrap();
+    let rows: Vec<&str> = s
+        .lines()
+        .filter(|l| l.starts_with(|c: char| c.is_ascii_digit()))
+        .collect();
+    assert_eq!(rows.len(), 1);
+    assert!(rows[0].starts_with("0,0.01,10000.0,10000.0,100.0,100.0,"));
+    let _ = fs::remove_dir_all(out);
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--mode",
+            "logistic",
+            "--p-min",
+            "0.005",
+            "--p-max",
+            "0.05",
+            "--bins",
+            "1",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ])
+        .output()
+        .expect("run bcurve");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("need ≥2 bins for logistic"),
+        "stderr: {stderr}"
+    );
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn fee_only_writes_fee_schedule_without_a_curve() {
+    let out = "out_fee_only_test";
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--fee-only",
+            "--base-factor",
+            "10",
+            "--variable-fee-control",
+            "40000",
+            "--va-max",
+            "10",
+            "--va-steps",
+            "4",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ])
+        .status()
+        .expect("run bcurve");
+    assert!(status.success());
+    assert!(!std::path::Path::new(&format!("{out}/schedule.csv")).exists());
+    let s = fs::read_to_string(format!("{out}/fees.csv")).unwrap();
+    let mut lines = s.lines();
+    assert_eq!(lines.next(), Some("va,fee_base,fee_var,fee_total"));
+    let rows: Vec<Vec<f64>> = lines
+        .map(|l| l.split(',').map(|f| f.parse().unwrap()).collect())
+        .collect();
+    assert_eq!(rows.len(), 5);
+    assert_eq!(rows[4][0], 10.0);
+    for r in &rows {
+        assert!((r[3] - (r[1] + r[2]).min(0.10)).abs() < 1e-12, "{r:?}");
+    }
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn bin_ids_are_offset_and_width_checked() {
+    let out = "out_bin_id_test";
+    let run = |bits: &str| {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--release",
+                "--",
+                "--bins",
+                "3",
+                "--r0",
+                "100.0",
+                "--bin-id-base",
+                "8388608",
+                "--bin-id-bits",
+                bits,
+                "--out-dir",
+                out,
+                "--no-draw",
+            ])
+            .output()
+            .expect("run bcurve")
+    };
+    assert!(run("32").status.success());
+    let s = fs::read_to_string(format!("{out}/schedule.csv")).unwrap();
+    let ids: Vec<&str> = s
+        .lines()
+        .filter(|l| l.starts_with(|c: char| c.is_ascii_digit()))
+        .map(|l| l.rsplit(',').nth(1).unwrap())
+        .collect();
+    assert_eq!(ids, ["8388608", "8388609", "8388610"]);
+
+    let output = run("24");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("overflows a 24-bit"), "stderr: {stderr}");
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn verify_json_is_machine_readable() {
+    let out = "out_verify_json_test";
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--bins",
+            "50",
+            "--r0",
+            "100.0",
+            "--verify-json",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ])
+        .status()
+        .expect("run bcurve");
+    assert!(status.success());
+    let v: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(format!("{out}/verify.json")).unwrap()).unwrap();
+    assert_eq!(v["bins"], 50);
+    assert!(v["rel_err_supply"].as_f64().unwrap() < 1e-9);
+    assert_eq!(v["monotone_ok"], true);
+    assert_eq!(v["revenue_monotone_ok"], true);
+    assert!(v["allocation"]["cv"].as_f64().unwrap() > 0.0);
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn format_json_matches_the_csv_schedule() {
+    let out = "out_format_json_test";
+    let run = |format: &str| {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--release",
+                "--",
+                "--bins",
+                "40",
+                "--r0",
+                "100.0",
+                "--price-guard-bps",
+                "50",
+                "--format",
+                format,
+                "--out-dir",
+                out,
+                "--no-draw",
+            ])
+            .output()
+            .expect("run bcurve")
+    };
+    assert!(run("both").status.success());
+    let v: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(format!("{out}/schedule.rows.json")).unwrap())
+            .unwrap();
+    let meta = &v["metadata"];
+    assert_eq!(meta["mode"], "DLMM-Geometric(θ)");
+    assert_eq!(meta["bins"], 40);
+    assert_eq!(meta["tau_start_pct"], 50.0);
+    assert!(meta["header_lines"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|l| l.as_str().unwrap().starts_with("Growth factor g=")));
+    assert_eq!(meta["price_guards"].as_array().unwrap().len(), 3);
+    assert_eq!(meta["price_guards"][2]["bin"], 39);
+
+    let rows = v["rows"].as_array().unwrap();
+    assert_eq!(rows.len(), 40);
+    assert_eq!(rows[39]["bin"], 39);
+    let csv_text = fs::read_to_string(format!("{out}/schedule.csv")).unwrap();
+    let last = csv_text.lines().last().unwrap();
+    let revenue_cum: f64 = last.split(',').nth(5).unwrap().parse().unwrap();
+    assert_eq!(rows[39]["revenue_cum"].as_f64().unwrap(), revenue_cum);
+
+    // The sidecar keeps schedule.json to itself
+    fs::remove_dir_all(out).unwrap();
+    let mut with_sidecar = Command::new("cargo");
+    with_sidecar.args([
+        "run",
+        "--release",
+        "--",
+        "--r0",
+        "100.0",
+        "--format",
+        "json",
+        "--sidecar",
+        "--out-dir",
+        out,
+        "--no-draw",
+    ]);
+    assert!(with_sidecar.status().expect("run bcurve").success());
+    let sidecar: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(format!("{out}/schedule.json")).unwrap()).unwrap();
+    assert!(sidecar.get("curve").is_some());
+    let rows: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(format!("{out}/schedule.rows.json")).unwrap())
+            .unwrap();
+    assert!(rows["rows"].as_array().is_some_and(|r| !r.is_empty()));
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn round_trip_test_regenerates_schedule_from_sidecar() {
+    let out = "out_round_trip_test";
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--bins",
+            "40",
+            "--r0",
+            "100.0",
+            "--below-bins",
+            "5",
+            "--exclude-bins",
+            "10..12",
+            "--log-columns",
+            "--surcharge-at-secs",
+            "15",
+            "--round-trip-test",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ])
+        .status()
+        .expect("run bcurve");
+    assert!(status.success());
+    let v: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(format!("{out}/schedule.json")).unwrap()).unwrap();
+    assert_eq!(v["curve"]["kind"], "two_sided");
+    assert_eq!(v["curve"]["below_bins"], 5);
+    assert_eq!(v["bins"], 40);
+    assert_eq!(v["bin_source"], "explicit");
+    assert_eq!(v["exclude_bins"]["start"], 10);
+    assert_eq!(v["format"]["surcharge_at_secs"], 15.0);
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn two_sided_cumulative_supply_matches_the_last_supply_cum() {
+    let out = "out_two_sided_supply_cum_test";
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--bins",
+            "30",
+            "--r0",
+            "100.0",
+            "--p0",
+            "0.01",
+            "--bin-step-bps",
+            "10",
+            "--theta",
+            "0.6",
+            "--below-bins",
+            "5",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ])
+        .status()
+        .expect("run bcurve");
+    assert!(status.success());
+
+    let s = fs::read_to_string(format!("{out}/schedule.csv")).unwrap();
+    let last = s.lines().last().expect("schedule has rows");
+    let supply_cum: f64 = last.split(',').nth(3).unwrap().parse().unwrap();
+    let two_sided = TwoSided {
+        core: Geometric {
+            grid: Grid::new(0.01, 10.0),
+            theta: 0.6,
+            r0_quote: 100.0,
+        },
+        below_bins: 5,
+    };
+    assert_eq!(two_sided.cumulative_supply(30), supply_cum);
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn account_surcharge_adds_launch_instant_surcharge_revenue() {
+    let out = "out_account_surcharge_test";
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--bins",
+            "20",
+            "--r0",
+            "100.0",
+            "--tau-start-pct",
+            "40",
+            "--account-surcharge",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ])
+        .status()
+        .expect("run bcurve");
+    assert!(status.success());
+
+    let s = fs::read_to_string(format!("{out}/schedule.csv")).unwrap();
+    let header_line = s.lines().find(|l| l.starts_with("bin,")).unwrap();
+    assert!(header_line
+        .ends_with(",fee_total,surcharge_revenue_bin,surcharge_revenue_cum,fee_total_bps"));
+    let rows: Vec<Vec<f64>> = s
+        .lines()
+        .filter(|l| l.starts_with(|c: char| c.is_ascii_digit()))
+        .map(|l| l.split(',').map(|f| f.parse().unwrap()).collect())
+        .collect();
+    assert_eq!(rows.len(), 20);
+    for r in &rows {
+        assert!((r[9] - r[4] * 0.4).abs() < 1e-12, "{r:?}");
+    }
+    let last = &rows[19];
+    assert!((last[10] / (last[5] * 0.4) - 1.0).abs() < 1e-14);
+
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn resume_continues_a_truncated_schedule() {
+    let out = "out_resume_test";
+    let run = |extra: &[&str]| {
+        let mut args = vec![
+            "run",
+            "--release",
+            "--",
+            "--bins",
+            "500",
+            "--r0",
+            "100.0",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ];
+        args.extend_from_slice(extra);
+        Command::new("cargo")
+            .args(&args)
+            .output()
+            .expect("run bcurve")
+    };
+    assert!(run(&[]).status.success());
+    let full = fs::read_to_string(format!("{out}/schedule.csv")).unwrap();
+    // Cut the file mid-row, as an interrupted run would leave it
+    fs::write(format!("{out}/schedule.csv"), &full[..full.len() * 2 / 3]).unwrap();
+    assert!(run(&["--resume"]).status.success());
+    let resumed = fs::read_to_string(format!("{out}/schedule.csv")).unwrap();
+
+    let rows = |s: &str| -> Vec<Vec<f64>> {
+        s.lines()
+            .filter(|l| l.starts_with(|c: char| c.is_ascii_digit()))
+            .map(|l| l.split(',').map(|f| f.parse().unwrap()).collect())
+            .collect()
+    };
+    let (a, b) = (rows(&full), rows(&resumed));
+    assert_eq!(a.len(), b.len());
+    for (x, y) in a.iter().zip(&b) {
+        assert_eq!(x[..3], y[..3]);
+        for col in [3, 5] {
+            assert!((x[col] / y[col] - 1.0).abs() < 1e-14, "{x:?} vs {y:?}");
+        }
+    }
+
+    // A column layout that differs from the partial file is refused
+    let output = run(&["--resume", "--log-columns"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("differ from this run's"),
+        "stderr: {stderr}"
+    );
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn metadata_block_lists_each_line_once() {
+    let out = "out_metadata_test";
+    let run = |extra: &[&str]| {
+        let mut args = vec![
+            "run",
+            "--release",
+            "--",
+            "--bins",
+            "200",
+            "--r0",
+            "100.0",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ];
+        args.extend_from_slice(extra);
+        let status = Command::new("cargo")
+            .args(&args)
+            .status()
+            .expect("run bcurve");
+        assert!(status.success());
+        fs::read_to_string(format!("{out}/schedule.csv")).unwrap()
+    };
+    let assert_unique = |s: &str| {
+        let metadata: Vec<&str> = s.lines().take_while(|l| l.starts_with('#')).collect();
+        assert_eq!(metadata[0], "# DLMM Bonding Curve Schedule");
+        let unique: HashSet<&str> = metadata.iter().copied().collect();
+        assert_eq!(unique.len(), metadata.len(), "{metadata:#?}");
+    };
+    let full = run(&[]);
+    assert_unique(&full);
+
+    // Resuming keeps the partial file's metadata block as it was
+    fs::write(format!("{out}/schedule.csv"), &full[..full.len() / 2]).unwrap();
+    assert_unique(&run(&["--resume"]));
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn fail_on_warning_turns_warnings_into_a_nonzero_exit() {
+    let out = "out_fail_on_warning_test";
+    let run = |strict: bool| {
+        let mut args = vec![
+            "run",
+            "--release",
+            "--",
+            "--bins",
+            "50",
+            "--r0",
+            "100.0",
+            "--dust-threshold",
+            "1e12",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ];
+        if strict {
+            args.push("--fail-on-warning");
+        }
+        Command::new("cargo")
+            .args(args)
+            .output()
+            .expect("run bcurve")
+    };
+    assert!(run(false).status.success());
+    let strict = run(true);
+    assert!(!strict.status.success());
+    let stderr = String::from_utf8_lossy(&strict.stderr);
+    assert!(
+        stderr.contains("1 warning(s) with --fail-on-warning"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("  - 50 of 50 bins hold ΔX < 1000000000000"),
+        "{stderr}"
+    );
+    // Outputs are still written so the failure can be inspected
+    assert!(fs::metadata(format!("{out}/schedule.csv")).is_ok());
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn steep_geometric_warns_about_allocation_spread() {
+    let out = "out_allocation_ratio_test";
+    let run = |max_ratio: &str| {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--release",
+                "--",
+                "--bins",
+                "400",
+                "--bin-step-bps",
+                "100",
+                "--theta",
+                "0.2",
+                "--r0",
+                "100.0",
+                "--max-allocation-ratio",
+                max_ratio,
+                "--fail-on-warning",
+                "--out-dir",
+                out,
+                "--no-draw",
+            ])
+            .output()
+            .expect("run bcurve")
+    };
+    // r^-(399) = 1.01^(0.8·399) ≈ 2.4e1
+    assert!(run("1e9").status.success());
+    let strict = run("10");
+    assert!(!strict.status.success());
+    let stderr = String::from_utf8_lossy(&strict.stderr);
+    assert!(
+        stderr.contains("raise --theta (now 0.2) toward 1"),
+        "{stderr}"
+    );
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn step_mode_reads_config_tiers_and_round_trips() {
+    let out = "out_step_mode_test";
+    fs::create_dir_all(out).unwrap();
+    let config = format!("{out}/step.toml");
+    fs::write(
+        &config,
+        "mode = \"step\"\n\n[[tiers]]\nstart_bin = 0\ndelta_x = 1000\n\n[[tiers]]\nstart_bin = 10\ndelta_x = 500\n\n# tail\n[[tiers]]\nstart_bin = 20\ndelta_x = 250\n",
+    )
+    .unwrap();
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--config",
+            &config,
+            "--bins",
+            "30",
+            "--round-trip-test",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ])
+        .status()
+        .expect("run bcurve");
+    assert!(status.success());
+    let text = fs::read_to_string(format!("{out}/schedule.csv")).unwrap();
+    let last = text.lines().last().unwrap();
+    let cols: Vec<&str> = last.split(',').collect();
+    assert_eq!(cols[0], "29");
+    assert_eq!(cols[2].parse::<f64>().unwrap(), 250.0);
+    assert_eq!(cols[3].parse::<f64>().unwrap(), 17_500.0);
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn batch_lines_are_split_like_a_shell() {
+    let out = "out_batch_quoting_test";
+    let _ = fs::remove_dir_all(out);
+    fs::create_dir_all(out).unwrap();
+    fs::write(format!("{out}/team list.txt"), "team\n").unwrap();
+    let batch = format!("{out}/batch.txt");
+    fs::write(
+        &batch,
+        format!(
+            "--bins 10 --r0 100 --no-draw --allowlist-path '{out}/team list.txt'\n--bins 10 --r0 100 --no-draw --allowlist-path \"{out}/team list.txt\n"
+        ),
+    )
+    .unwrap();
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--batch",
+            &batch,
+            "--out-dir",
+            out,
+        ])
+        .status()
+        .expect("run bcurve");
+    assert!(!status.success());
+    let mut rdr = csv::Reader::from_path(format!("{out}/batch_summary.csv")).unwrap();
+    let rows: Vec<csv::StringRecord> = rdr.records().map(|r| r.unwrap()).collect();
+    assert_eq!(&rows[0][2], "ok", "{:?}", rows[0]);
+    assert_eq!(&rows[1][2], "error");
+    assert!(rows[1][7].contains("unbalanced quotes"), "{:?}", rows[1]);
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn batch_runs_every_line_and_summarises_failures() {
+    let out = "out_batch_test";
+    let _ = fs::remove_dir_all(out);
+    fs::create_dir_all(out).unwrap();
+    let batch = format!("{out}/batch.txt");
+    fs::write(
+        &batch,
+        "# geometric sweep\n--bins 40 --r0 100 --no-draw\n\n--bins 60 --theta 0.3 --r0 100 --no-draw\n--bins 0 --no-draw\n",
+    )
+    .unwrap();
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--batch",
+            &batch,
+            "--concurrency",
+            "2",
+            "--out-dir",
+            out,
+        ])
+        .status()
+        .expect("run bcurve");
+    // The bins = 0 line fails the batch, but only after everything ran
+    assert!(!status.success());
+    let mut rdr = csv::Reader::from_path(format!("{out}/batch_summary.csv")).unwrap();
+    let rows: Vec<csv::StringRecord> = rdr.records().map(|r| r.unwrap()).collect();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(&rows[0][0], "2");
+    assert_eq!(&rows[0][2], "ok");
+    assert_eq!(&rows[0][4], "40");
+    assert_eq!(&rows[1][0], "4");
+    assert_eq!(&rows[1][4], "60");
+    assert_eq!(&rows[2][0], "5");
+    assert_eq!(&rows[2][2], "error");
+    assert!(!rows[2][7].is_empty());
+    for line in [2, 4] {
+        let schedule = format!("{out}/run_{line:04}/schedule.csv");
+        assert!(fs::metadata(&schedule).is_ok(), "{schedule}");
+    }
+    assert!(fs::metadata(format!("{out}/batch_summary.csv.tmp")).is_err());
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn split_output_shards_match_the_single_schedule() {
+    let run = |out: &str, split: Option<&str>| {
+        let mut args = vec![
+            "run",
+            "--release",
+            "--",
+            "--bins",
+            "25",
+            "--r0",
+            "100.0",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ];
+        if let Some(rows) = split {
+            args.extend(["--split-output", rows]);
+        }
+        let status = Command::new("cargo")
+            .args(args)
+            .status()
+            .expect("run bcurve");
+        assert!(status.success());
+    };
+    let (single, split) = ("out_split_single_test", "out_split_output_test");
+    run(single, None);
+    run(split, Some("10"));
+
+    let data_rows = |path: String| -> Vec<String> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .filter(|l| l.starts_with(|c: char| c.is_ascii_digit()))
+            .map(str::to_string)
+            .collect()
+    };
+    let mut sharded = Vec::new();
+    for (k, rows) in [10, 10, 5].into_iter().enumerate() {
+        let path = format!("{split}/schedule_{k}.csv");
+        let s = fs::read_to_string(&path).unwrap();
+        assert!(s.lines().any(|l| l.starts_with("bin,price,")), "{path}");
+        let shard = data_rows(path);
+        assert_eq!(shard.len(), rows);
+        sharded.extend(shard);
+    }
+    assert!(fs::metadata(format!("{split}/schedule_3.csv")).is_err());
+    assert!(fs::metadata(format!("{split}/schedule.csv")).is_err());
+    // Cumulative columns continue across the seams bit for bit
+    assert_eq!(sharded, data_rows(format!("{single}/schedule.csv")));
+
+    let index: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(format!("{split}/schedule_index.json")).unwrap())
+            .unwrap();
+    assert_eq!(index["rows_per_file"], 10);
+    let shards = index["shards"].as_array().unwrap();
+    assert_eq!(shards.len(), 3);
+    assert_eq!(shards[1]["file"], "schedule_1.csv");
+    assert_eq!(shards[1]["first_bin"], 10);
+    assert_eq!(shards[1]["last_bin"], 19);
+    assert_eq!(shards[2]["rows"], 5);
+
+    let _ = fs::remove_dir_all(single);
+    let _ = fs::remove_dir_all(split);
+}
+
+#[test]
+fn montecarlo_writes_reproducible_percentiles() {
+    let run = |out: &str| {
+        let status = Command::new("cargo")
+            .args([
+                "run",
+                "--release",
+                "--",
+                "--bins",
+                "200",
+                "--r0",
+                "100.0",
+                "--tau-ramp-secs",
+                "60",
+                "--montecarlo",
+                "--seed",
+                "9",
+                "--mc-runs",
+                "50",
+                "--out-dir",
+                out,
+                "--no-draw",
+            ])
+            .status()
+            .expect("run bcurve");
+        assert!(status.success());
+        fs::read_to_string(format!("{out}/montecarlo.csv")).unwrap()
+    };
+    let (a, b) = ("out_montecarlo_a_test", "out_montecarlo_b_test");
+    let first = run(a);
+    assert_eq!(first, run(b));
+    let mut lines = first.lines();
+    assert_eq!(lines.next(), Some("metric,mean,min,p5,p50,p95,max"));
+    let metrics: Vec<&str> = lines.map(|l| l.split(',').next().unwrap()).collect();
+    assert_eq!(
+        metrics,
+        [
+            "fee_revenue",
+            "surcharge_revenue",
+            "supply_sold",
+            "final_active_bin"
+        ]
+    );
+    let _ = fs::remove_dir_all(a);
+    let _ = fs::remove_dir_all(b);
+}
+
+#[test]
+fn preview_prints_an_aligned_head_and_tail() {
+    let out = "out_preview_test";
+    let preview = |bins: &str| {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "--release",
+                "--",
+                "--bins",
+                bins,
+                "--r0",
+                "100.0",
+                "--preview",
+                "3",
+                "--out-dir",
+                out,
+                "--no-draw",
+            ])
+            .output()
+            .expect("run bcurve");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let stdout = preview("30");
+    let table: Vec<&str> = stdout
+        .lines()
+        .skip_while(|l| !l.trim_start().starts_with("bin"))
+        .take(9)
+        .collect();
+    assert_eq!(table.len(), 9, "{stdout}");
+    assert!(table[0].ends_with("fee_total"));
+    assert!(table[1].chars().all(|c| c == '-'));
+    let width = table[0].chars().count();
+    assert!(table.iter().all(|l| l.chars().count() == width), "{stdout}");
+    let bins: Vec<&str> = table[2..]
+        .iter()
+        .map(|l| l.split_whitespace().next().unwrap())
+        .collect();
+    assert_eq!(bins, ["0", "1", "2", "…", "27", "28", "29"]);
+
+    // Six rows or fewer are shown whole
+    let stdout = preview("5");
+    assert!(!stdout.contains('…'));
+    assert!(stdout.lines().any(|l| l.trim_start().starts_with("4 ")));
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn output_stdout_pipes_the_schedule_and_keeps_logs_off_stdout() {
+    let out = "out_stdout_test";
+    let _ = fs::remove_dir_all(out);
+    let run = |extra: &[&str]| {
+        let mut args = vec![
+            "run",
+            "--release",
+            "--",
+            "--bins",
+            "40",
+            "--r0",
+            "100.0",
+            "--out-dir",
+            out,
+            "--verbose",
+            "--preview",
+            "2",
+            "--output-stdout",
+        ];
+        args.extend(extra);
+        let output = Command::new("cargo")
+            .args(&args)
+            .output()
+            .expect("run bcurve");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        (
+            String::from_utf8(output.stdout).unwrap(),
+            String::from_utf8(output.stderr).unwrap(),
+        )
+    };
+
+    let (stdout, stderr) = run(&[]);
+    let rows: Vec<&str> = stdout
+        .lines()
+        .filter(|l| !l.starts_with('#') && !l.is_empty())
+        .collect();
+    assert!(
+        rows[0].starts_with("bin,price,delta_x,supply_cum"),
+        "{stdout}"
+    );
+    assert_eq!(rows.len(), 41, "{stdout}");
+    let width = rows[0].split(',').count();
+    assert!(rows.iter().all(|r| r.split(',').count() == width));
+    // Verbose lines and the preview went to stderr, and nothing touched --out-dir
+    assert!(stderr.contains("Wrote 40 bins"), "{stderr}");
+    assert!(stderr.contains("fee_total\n"), "{stderr}");
+    assert!(!std::path::Path::new(out).exists());
+
+    let (stdout, _) = run(&["--format", "solidity"]);
+    assert!(stdout.contains("library ScheduleChunk0"), "{stdout}");
+    assert!(!std::path::Path::new(out).exists());
+}
+
+#[test]
+fn list_modes_prints_every_mode_with_its_requirements() {
+    let output = Command::new("cargo")
+        .args(["run", "--release", "--", "--list-modes"])
+        .output()
+        .expect("run bcurve");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    for kind in CurveKind::all() {
+        assert!(
+            stdout
+                .lines()
+                .any(|l| l.starts_with(kind.name()) && l.contains(kind.summary())),
+            "{stdout}"
+        );
+    }
+    assert_eq!(stdout.matches("requires ").count(), CurveKind::all().len());
+    assert!(
+        stdout.contains("requires --r0 or --target-supply"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn print_config_scenario_replays_the_same_schedule() {
+    let out = "out_scenario_test";
+    let _ = fs::remove_dir_all(out);
+    let bcurve = |extra: &[&str]| {
+        let mut args = vec![
+            "run",
+            "--release",
+            "--",
+            "--mode",
+            "logistic",
+            "--p-max",
+            "0.05",
+            "--end-price",
+            "0.03",
+            "--account-surcharge",
+        ];
+        args.extend(extra);
+        let output = Command::new("cargo")
+            .args(&args)
+            .output()
+            .expect("run bcurve");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    // Only the scenario reaches stdout, and nothing is written
+    let scenario = bcurve(&["--print-config", "--verbose", "--out-dir", out]);
+    assert!(!std::path::Path::new(out).exists());
+    let json: serde_json::Value = serde_json::from_str(&scenario).expect("scenario JSON");
+    assert_eq!(json["schedule"]["bin_source"], "end_price");
+    let curve = &json["schedule"]["curve"];
+    assert_eq!(curve["kind"], "logistic");
+    // s_mid was derived from p0, not left at the CLI default of 0
+    assert!(curve["s_mid"].as_f64().unwrap() > 0.0, "{scenario}");
+    assert_eq!(json["schedule"]["bins"], curve["bins"]);
+
+    fs::create_dir_all(out).unwrap();
+    let path = format!("{out}/scenario.json");
+    fs::write(&path, &scenario).unwrap();
+    let direct = format!("{out}/direct");
+    let replayed = format!("{out}/replayed");
+    bcurve(&["--out-dir", &direct, "--no-draw"]);
+    // The replay ignores curve flags, even ones that would change the schedule
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--replay",
+            &path,
+            "--p-max",
+            "0.09",
+            "--out-dir",
+            &replayed,
+            "--no-draw",
+        ])
+        .status()
+        .expect("run bcurve");
+    assert!(status.success());
+    assert_eq!(
+        fs::read_to_string(format!("{direct}/schedule.csv")).unwrap(),
+        fs::read_to_string(format!("{replayed}/schedule.csv")).unwrap()
+    );
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn exponential_mode_hits_target_supply_and_round_trips() {
+    let out = "out_exponential_test";
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--mode",
+            "exponential",
+            "--exp-b",
+            "-0.002",
+            "--target-supply",
+            "1000000",
+            "--bins",
+            "300",
+            "--round-trip-test",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ])
+        .status()
+        .expect("run bcurve");
+    assert!(status.success());
+    let csv = fs::read_to_string(format!("{out}/schedule.csv")).unwrap();
+    let last = csv.lines().next_back().unwrap();
+    let fields: Vec<&str> = last.split(',').collect();
+    assert_eq!(fields[0], "299");
+    let supply: f64 = fields[3].parse().unwrap();
+    assert!((supply / 1e6 - 1.0).abs() < 1e-12, "{last}");
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn linear_mode_rejects_allocations_that_go_negative() {
+    let out = "out_linear_test";
+    let run = |slope: &str| {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--release",
+                "--",
+                "--mode",
+                "linear",
+                "--base",
+                "1000",
+                "--slope",
+                slope,
+                "--bins",
+                "400",
+                "--out-dir",
+                out,
+                "--no-draw",
+            ])
+            .output()
+            .expect("run bcurve")
+    };
+    assert!(run("-2").status.success());
+    let csv = fs::read_to_string(format!("{out}/schedule.csv")).unwrap();
+    let last: Vec<&str> = csv.lines().next_back().unwrap().split(',').collect();
+    assert_eq!(last[2], "202.0");
+    assert_eq!(last[3], "240400.0");
+
+    let negative = run("-3");
+    assert!(!negative.status.success());
+    let stderr = String::from_utf8_lossy(&negative.stderr);
+    assert!(
+        stderr.contains("ΔX_399 = base + slope·399 = -197 < 0"),
+        "{stderr}"
+    );
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn config_file_sets_options_and_cli_flags_override_it() {
+    let print_config = |extra: &[&str]| -> serde_json::Value {
+        let mut args = vec![
+            "run",
+            "--release",
+            "--",
+            "--config",
+            "config_example.toml",
+            "--print-config",
+        ];
+        args.extend(extra);
+        let output = Command::new("cargo")
+            .args(&args)
+            .output()
+            .expect("run bcurve");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        serde_json::from_slice(&output.stdout).expect("scenario JSON")
+    };
+
+    let json = print_config(&[]);
+    let schedule = &json["schedule"];
+    assert_eq!(
+        schedule["curve"]["grid"],
+        serde_json::json!({"p0": 0.02, "bin_step_bps": 25.0, "spacing": "price"})
+    );
+    assert_eq!(schedule["curve"]["theta"], 0.5);
+    assert_eq!(schedule["bins"], 400);
+    // Fee options left out of the file keep the CLI defaults
+    assert_eq!(
+        schedule["fees"],
+        serde_json::json!({
+            "base_factor": 10000.0,
+            "bin_step_bps": 25.0,
+            "variable_fee_control": 40.0,
+            "max_fee_rate": 0.05,
+            "min_fee_rate": 0.001,
+            "cap_softness": 0.0,
+            "distance_fee_delta": 0.0,
+            "protocol_share": 0.0
+        })
+    );
+    assert_eq!(schedule["policy"]["tau_start_pct"], 40.0);
+    assert_eq!(schedule["policy"]["allowlist"].as_array().unwrap().len(), 3);
+
+    let json = print_config(&["--bin-step-bps", "50", "--theta", "0.7"]);
+    let schedule = &json["schedule"];
+    assert_eq!(schedule["curve"]["grid"]["bin_step_bps"], 50.0);
+    assert_eq!(schedule["fees"]["bin_step_bps"], 50.0);
+    assert_eq!(schedule["curve"]["theta"], 0.7);
+    assert_eq!(schedule["fees"]["max_fee_rate"], 0.05);
+
+    let dir = "out_config_test";
+    fs::create_dir_all(dir).unwrap();
+    let path = format!("{dir}/bad.toml");
+    fs::write(&path, "p0 = 0.01\nthetaa = 0.5\n").unwrap();
+    let output = Command::new("cargo")
+        .args(["run", "--release", "--", "--config", &path])
+        .output()
+        .expect("run bcurve");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown option \"thetaa\""));
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn config_file_values_yield_to_conflicting_cli_flags() {
+    let dir = "out_config_precedence_test";
+    fs::create_dir_all(dir).unwrap();
+    let bcurve = |config: &str, extra: &[&str]| {
+        let path = format!("{dir}/run.toml");
+        fs::write(&path, config).unwrap();
+        let mut args = vec![
+            "run",
+            "--release",
+            "--",
+            "--config",
+            &path,
+            "--print-config",
+        ];
+        args.extend(extra);
+        Command::new("cargo")
+            .args(&args)
+            .output()
+            .expect("run bcurve")
+    };
+    let config = "p0 = 0.02\nbin_step_bps = 25\ntheta = 0.5\nbins = 100\nr0 = 100.0\n";
+
+    // The same flag on the command line replaces the file value
+    let output = bcurve(config, &["--theta", "0.7", "--bins", "50"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["schedule"]["curve"]["theta"], 0.7);
+    assert_eq!(json["schedule"]["bins"], 50);
+
+    // A command-line flag conflicting with a file value drops the file value
+    let output = bcurve(config, &["--bins-per-decade", "100"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let step = json["schedule"]["curve"]["grid"]["bin_step_bps"]
+        .as_f64()
+        .unwrap();
+    assert!(
+        (step - (10f64.powf(0.01) - 1.0) * 10_000.0).abs() < 1e-9,
+        "{step}"
+    );
+    assert_eq!(json["schedule"]["curve"]["grid"]["p0"], 0.02);
+
+    // Conflicting values within the file are still an error
+    let output = bcurve(&format!("{config}bins_per_decade = 100\n"), &[]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("cannot be used with"), "{stderr}");
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn diff_compares_a_regenerated_schedule_in_place() {
+    let out = "out_diff_test";
+    let bcurve = |extra: &[&str]| -> String {
+        let mut args = vec![
+            "run",
+            "--release",
+            "--",
+            "--r0",
+            "100.0",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ];
+        args.extend(extra);
+        let output = Command::new("cargo")
+            .args(&args)
+            .output()
+            .expect("run bcurve");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    };
+    bcurve(&["--bins", "5"]);
+    // The old schedule is read before the new run replaces it
+    let path = format!("{out}/schedule.csv");
+    let stdout = bcurve(&["--bins", "7", "--theta", "0.5", "--diff", &path]);
+    assert!(
+        stdout.contains("5 bins matched, 0 only in old, 2 only in new, 6 changed"),
+        "{stdout}"
+    );
+
+    let diff = fs::read_to_string(format!("{out}/diff.csv")).unwrap();
+    let mut lines = diff.lines();
+    assert_eq!(
+        lines.next().unwrap(),
+        "bin,price_old,price_new,price_change,delta_x_old,delta_x_new,delta_x_change,revenue_old,revenue_new,revenue_change"
+    );
+    let rows: Vec<Vec<&str>> = lines.map(|l| l.split(',').collect()).collect();
+    assert_eq!(rows.len(), 7);
+    // Bin 0 holds R₀/P₀ under either θ; bins 5 and 6 are new
+    assert_eq!(rows[0][6], "0.0");
+    assert_eq!(rows[6][0], "6");
+    assert_eq!(rows[6][1], "");
+    assert_eq!(rows[6][3], "");
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn fee_cap_and_coarse_bins_raise_warnings() {
+    let out = "out_fee_cap_warning_test";
+    let run = |extra: &[&str]| {
+        let mut args = vec![
+            "run",
+            "--release",
+            "--",
+            "--bins",
+            "50",
+            "--fail-on-warning",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ];
+        args.extend_from_slice(extra);
+        let output = Command::new("cargo")
+            .args(&args)
+            .output()
+            .expect("run bcurve");
+        (
+            output.status.success(),
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        )
+    };
+    // Base fee 1%, growing 100% per bin: 11% > the 10% cap from 10 bins out
+    let (ok, stderr) = run(&[
+        "--r0",
+        "100.0",
+        "--base-factor",
+        "10",
+        "--distance-fee",
+        "1",
+    ]);
+    assert!(!ok);
+    assert!(
+        stderr.contains("capped at max_fee_rate=0.1 in bins 10 or more from the active bin"),
+        "{stderr}"
+    );
+    let (ok, stderr) = run(&["--r0", "100.0", "--base-factor", "10"]);
+    assert!(ok, "{stderr}");
+
+    let logistic = ["--mode", "logistic", "--p-min", "0.005", "--p-max", "0.05"];
+    let (ok, stderr) = run(&[&logistic[..], &["--bin-step-bps", "200"]].concat());
+    assert!(!ok);
+    assert!(
+        stderr.contains("the 200 bps bins are too coarse for this curve"),
+        "{stderr}"
+    );
+    let (ok, stderr) = run(&logistic);
+    assert!(ok, "{stderr}");
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn guard_metadata_keeps_each_mode_format() {
+    let out = "out_guard_format_test";
+    let run = |mode_args: &[&str]| {
+        let mut args = vec![
+            "run",
+            "--release",
+            "--",
+            "--bins",
+            "40",
+            "--price-guard-bps",
+            "50",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ];
+        args.extend_from_slice(mode_args);
+        let status = Command::new("cargo")
+            .args(&args)
+            .status()
+            .expect("run bcurve");
+        assert!(status.success());
+        let s = fs::read_to_string(format!("{out}/schedule.csv")).unwrap();
+        let guards: Vec<String> = s
+            .lines()
+            .filter(|l| l.starts_with("# Guard @ "))
+            .map(str::to_string)
+            .collect();
+        assert_eq!(guards.len(), 3, "{s}");
+        guards
+    };
+    let geometric = run(&["--r0", "100.0"]);
+    assert!(geometric[0].starts_with("# Guard @ bin 0 (P="));
+    assert!(geometric[2].starts_with("# Guard @ bin 39 (P="));
+    // Two-sided schedules guard from their first bin below P₀
+    let two_sided = run(&["--r0", "100.0", "--below-bins", "10"]);
+    assert!(two_sided[0].starts_with("# Guard @ start (bin -10, P="));
+    assert!(two_sided[1].starts_with("# Guard @ mid (bin 15, P="));
+    assert!(two_sided[2].starts_with("# Guard @ end (bin 39, P="));
+
+    let logistic = run(&["--mode", "logistic", "--p-min", "0.005", "--p-max", "0.05"]);
+    assert!(logistic[0].starts_with("# Guard @ start (bin 0, P="));
+    assert!(logistic[2].starts_with("# Guard @ end (bin 39, P="));
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn logistic_report_and_overshoot_warning_survive_every_grid() {
+    let out = "out_logistic_report_test";
+    let run = |extra: &[&str]| {
+        let mut args = vec![
+            "run",
+            "--release",
+            "--",
+            "--mode",
+            "logistic",
+            "--p-min",
+            "0.005",
+            "--p-max",
+            "0.05",
+            "--verify-json",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ];
+        args.extend_from_slice(extra);
+        let output = Command::new("cargo")
+            .args(&args)
+            .output()
+            .expect("run bcurve");
+        assert!(output.status.success());
+        let report: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(format!("{out}/verify.json")).unwrap())
+                .unwrap();
+        (String::from_utf8_lossy(&output.stderr).into_owned(), report)
+    };
+    // Bin 1611 of the default 10 bps grid is the first at or past p_max
+    let (stderr, report) = run(&["--bins", "2000", "--geometric-from-logistic"]);
+    assert!(
+        stderr.contains("grid overshoots the asymptote: bin 1611"),
+        "{stderr}"
+    );
+    assert_eq!(report["asymptote"]["first_overshoot_bin"], 1611);
+
+    let (_, report) = run(&["--bins", "1000", "--adaptive-bins", "300"]);
+    assert_eq!(report["asymptote"]["approaches_asymptote_ok"], true);
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn fee_tradeoff_rejects_a_zero_base_fee() {
+    let out = "out_fee_tradeoff_zero_test";
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--bins",
+            "5",
+            "--r0",
+            "100.0",
+            "--base-factor",
+            "0",
+            "--fee-tradeoff",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ])
+        .output()
+        .expect("run bcurve");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("base fee, which is 0"), "stderr: {stderr}");
+    assert!(fs::metadata(format!("{out}/fee_tradeoff.csv")).is_err());
+
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn log_return_column_is_empty_on_the_first_bin() {
+    let out = "out_log_return_test";
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--bins",
+            "10",
+            "--r0",
+            "100.0",
+            "--bin-step-bps",
+            "25",
+            "--below-bins",
+            "3",
+            "--log-columns",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ])
+        .status()
+        .expect("run bcurve");
+    assert!(status.success());
+
+    let s = fs::read_to_string(format!("{out}/schedule.csv")).unwrap();
+    let header: Vec<&str> = s
+        .lines()
+        .find(|l| l.starts_with("bin,"))
+        .unwrap()
+        .split(',')
+        .collect();
+    let col = |name: &str| header.iter().position(|&h| h == name).unwrap();
+    let (bin, price, log_ret) = (col("bin"), col("price"), col("log_return_from_prev"));
+    let rows: Vec<Vec<&str>> = s
+        .lines()
+        .filter(|l| l.starts_with(|c: char| c == '-' || c.is_ascii_digit()))
+        .map(|l| l.split(',').collect())
+        .collect();
+    assert_eq!(rows[0][bin], "-3");
+    assert_eq!(rows[0][log_ret], "");
+    for pair in rows.windows(2) {
+        let (prev, row) = (&pair[0], &pair[1]);
+        let p_prev: f64 = prev[price].parse().unwrap();
+        let p: f64 = row[price].parse().unwrap();
+        let r: f64 = row[log_ret].parse().unwrap();
+        assert!((r - (p / p_prev).ln()).abs() < 1e-12, "{r}");
+        assert!((r - 1.0025f64.ln()).abs() < 1e-12, "{r}");
+    }
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn progress_reports_increasing_percentages_up_to_completion() {
+    let out = "out_progress_test";
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--bins",
+            "1000",
+            "--r0",
+            "100.0",
+            "--progress",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ])
+        .output()
+        .expect("run bcurve");
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let pcts: Vec<i64> = stderr
+        .split(['\r', '\n'])
+        .filter_map(|s| s.trim().strip_suffix('%'))
+        .map(|s| s.parse().unwrap())
+        .collect();
+    // Every 10 bins (1%) plus once at completion
+    assert_eq!(pcts.len(), 101, "{stderr}");
+    assert_eq!(pcts[0], 0);
+    assert_eq!(pcts[100], 100);
+    assert!(pcts.windows(2).all(|w| w[0] < w[1]), "{pcts:?}");
+    let _ = fs::remove_dir_all(out);
+}
@@ -0,0 +1,616 @@
+use bcurve::curves::{
+    CompensatedSum, Curve, CurveClone, CurveKind, Geometric, Grid, GridSpacing, Linear, LogisticS,
+    MaskedCurve, StepCurve, TwoSided,
+};
+
+#[test]
+fn boxed_curves_clone_and_dispatch() {
+    let grid = Grid {
+        p0: 0.01,
+        bin_step_bps: 25.0,
+        spacing: GridSpacing::Price,
+    };
+    let geo = Geometric {
+        grid,
+        theta: 0.6,
+        r0_quote: 100.0,
+    };
+    let logistic = LogisticS {
+        grid,
+        p_min: 0.005,
+        p_max: 0.05,
+        k: 1e-5,
+        s_mid: 2e5,
+        bins: 100,
+    };
+    let curves: Vec<Box<dyn CurveClone>> = vec![
+        Box::new(geo),
+        Box::new(logistic),
+        Box::new(MaskedCurve {
+            inner: geo,
+            excluded: 10..20,
+        }),
+    ];
+    let copies = curves.clone();
+    for (a, b) in curves.iter().zip(&copies) {
+        assert_eq!(a.name(), b.name());
+        for i in 0..100 {
+            assert_eq!(a.price_of_bin(i), b.price_of_bin(i));
+            assert_eq!(a.delta_x_of_bin(i), b.delta_x_of_bin(i));
+        }
+    }
+    assert_eq!(copies[0].cumulative_supply(50), geo.cumulative_supply(50));
+
+    // Box<dyn Curve> composes with generic wrappers
+    let masked = MaskedCurve {
+        inner: copies[1].clone_boxed(),
+        excluded: 0..50,
+    };
+    assert_eq!(masked.delta_x_of_bin(10), 0.0);
+    assert_eq!(masked.delta_x_of_bin(60), logistic.delta_x_of_bin(60));
+}
+
+/// Borrows its allocations, so it is neither `Clone`-able into a box nor `'static`
+struct Borrowed<'a> {
+    alloc: &'a [f64],
+}
+impl Curve for Borrowed<'_> {
+    fn name(&self) -> &'static str {
+        "borrowed"
+    }
+    fn price_of_bin(&self, i: i64) -> f64 {
+        1.0 + i as f64
+    }
+    fn delta_x_of_bin(&self, i: i64) -> f64 {
+        self.alloc[i as usize]
+    }
+}
+
+#[test]
+fn curves_need_not_be_clone_or_static() {
+    let alloc = vec![1.0, 2.0, 3.0];
+    let boxed: Box<dyn Curve + '_> = Box::new(Borrowed { alloc: &alloc });
+    assert_eq!(boxed.cumulative_supply(3), 6.0);
+    let masked = MaskedCurve {
+        inner: Borrowed { alloc: &alloc },
+        excluded: 1..2,
+    };
+    assert_eq!(masked.cumulative_supply(3), 4.0);
+}
+
+#[test]
+fn from_endpoints_hits_end_price_and_supply() {
+    let c = Geometric::from_endpoints(0.01, 0.03, 400, 0.6, 1e8).unwrap();
+    assert!((c.price_of_bin(400) / 0.03 - 1.0).abs() < 1e-12);
+    assert!((c.cumulative_supply(400) / 1e8 - 1.0).abs() < 1e-9);
+    assert!((c.grid.q().powi(400) - 3.0).abs() < 1e-12);
+
+    assert!(Geometric::from_endpoints(0.03, 0.01, 400, 0.6, 1e8).is_err());
+    assert!(Geometric::from_endpoints(0.01, 0.03, 0, 0.6, 1e8).is_err());
+    assert!(Geometric::from_endpoints(0.01, 0.03, 400, 0.6, -1.0).is_err());
+}
+
+#[test]
+fn supply_between_prices_matches_filtering_the_schedule() {
+    let geo = Geometric {
+        grid: Grid {
+            p0: 0.01,
+            bin_step_bps: 25.0,
+            spacing: GridSpacing::Price,
+        },
+        theta: 0.6,
+        r0_quote: 100.0,
+    };
+    let two_sided = TwoSided {
+        core: geo,
+        below_bins: 40,
+    };
+    let bins = 300;
+    let brute = |c: &dyn Curve, lo: f64, hi: f64| -> f64 {
+        (c.first_bin()..bins)
+            .filter(|&i| (lo..=hi).contains(&c.price_of_bin(i)))
+            .map(|i| c.delta_x_of_bin(i))
+            .sum()
+    };
+    for (lo, hi) in [
+        (1e-9, 1.0),
+        (0.012, 0.015),
+        (geo.price_of_bin(10), geo.price_of_bin(20)),
+        (0.005, 0.0101),
+        (0.5, 0.6),
+    ] {
+        let expected = brute(&geo, lo, hi);
+        assert!(
+            (geo.supply_between_prices(lo, hi, bins).unwrap() - expected).abs()
+                <= 1e-9 * expected.max(1.0),
+            "[{lo}, {hi}]"
+        );
+        let expected = brute(&two_sided, lo, hi);
+        assert!(
+            (two_sided.supply_between_prices(lo, hi, bins).unwrap() - expected).abs()
+                <= 1e-9 * expected.max(1.0),
+            "two-sided [{lo}, {hi}]"
+        );
+    }
+}
+
+#[test]
+fn supply_between_prices_rejects_an_inverted_band() {
+    let geo = Geometric {
+        grid: Grid {
+            p0: 0.01,
+            bin_step_bps: 25.0,
+            spacing: GridSpacing::Price,
+        },
+        theta: 0.6,
+        r0_quote: 100.0,
+    };
+    let err = geo.supply_between_prices(0.02, 0.01, 100).unwrap_err();
+    assert!(err.to_string().contains("need p_lo < p_hi"), "{err}");
+    for (lo, hi) in [
+        (0.0, 1.0),
+        (-1.0, 1.0),
+        (0.01, f64::INFINITY),
+        (f64::NAN, 1.0),
+    ] {
+        let err = geo.supply_between_prices(lo, hi, 100).unwrap_err();
+        assert!(err.to_string().contains("must be finite and > 0"), "{err}");
+        // The default bisection path validates the same way
+        let two_sided = TwoSided {
+            core: geo,
+            below_bins: 10,
+        };
+        assert!(two_sided.supply_between_prices(lo, hi, 100).is_err());
+    }
+}
+
+#[test]
+fn bins_iter_yields_index_price_and_allocation() {
+    let geo = Geometric {
+        grid: Grid {
+            p0: 0.01,
+            bin_step_bps: 25.0,
+            spacing: GridSpacing::Price,
+        },
+        theta: 0.6,
+        r0_quote: 100.0,
+    };
+    let two_sided = TwoSided {
+        core: geo,
+        below_bins: 3,
+    };
+    let rows: Vec<_> = two_sided.bins_iter(-3..4).collect();
+    assert_eq!(rows.len(), 7);
+    for (i, p, dx) in rows {
+        assert_eq!(p, two_sided.price_of_bin(i));
+        assert_eq!(dx, two_sided.delta_x_of_bin(i));
+    }
+    // Also available through a boxed curve
+    let boxed: Box<dyn Curve> = Box::new(geo);
+    let total: f64 = boxed.bins_iter(0..50).map(|(_, _, dx)| dx).sum();
+    assert!((total / geo.cumulative_supply(50) - 1.0).abs() < 1e-12);
+}
+
+/// Neumaier-compensated Σ ΔX_i over bins `[0, n)`
+fn compensated_supply(c: &Geometric, n: i64) -> f64 {
+    let (mut sum, mut comp) = (0.0_f64, 0.0_f64);
+    for i in 0..n {
+        let x = c.delta_x_of_bin(i);
+        let t = sum + x;
+        comp += if sum.abs() >= x.abs() {
+            (sum - t) + x
+        } else {
+            (x - t) + sum
+        };
+        sum = t;
+    }
+    sum + comp
+}
+
+fn geometric_with(bin_step_bps: f64, theta: f64) -> Geometric {
+    Geometric {
+        grid: Grid {
+            p0: 0.01,
+            bin_step_bps,
+            spacing: GridSpacing::Price,
+        },
+        theta,
+        r0_quote: 100.0,
+    }
+}
+
+#[test]
+fn closed_form_supply_is_exact_as_r_approaches_one() {
+    // θ = 1 - 1e-13 puts r within 1e-12 of 1, where a linear n·ΔX_0 shortcut
+    // is off by ≈ n·|r-1|/2; θ = 1 - 1e-10 sits just outside that window.
+    for (step, theta) in [
+        (25.0, 1.0 - 1e-13),
+        (100.0, 1.0 - 1e-13),
+        (25.0, 1.0 - 1e-10),
+        (100.0, 1.0 - 1e-10),
+        (1.0, 1.0 - 1e-9),
+        (25.0, 1.0),
+    ] {
+        let g = geometric_with(step, theta);
+        for n in [1, 10, 2_000, 100_000] {
+            let numeric = compensated_supply(&g, n);
+            let rel = (g.s_n_closed(n) / numeric - 1.0).abs();
+            assert!(rel < 1e-12, "step={step} θ={theta} n={n}: rel err {rel:e}");
+        }
+        let r0 = g.solve_r0_from_supply(1e8, 100_000);
+        let solved = Geometric { r0_quote: r0, ..g };
+        assert!((compensated_supply(&solved, 100_000) / 1e8 - 1.0).abs() < 1e-12);
+    }
+    assert!((geometric_with(25.0, 1.0 - 1e-13).r() - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn geometric_cumulative_supply_is_the_closed_form() {
+    for theta in [0.0, 0.6, 1.0, 1.4] {
+        let g = geometric_with(25.0, theta);
+        let numeric = compensated_supply(&g, 100_000);
+        let rel = (g.cumulative_supply(100_000) / numeric - 1.0).abs();
+        assert!(rel < 1e-9, "θ={theta}: rel err {rel:e}");
+        assert_eq!(g.cumulative_supply(0), 0.0);
+        assert_eq!(g.cumulative_supply(-3), 0.0);
+    }
+    // A thousand 10M-bin totals would be 10^10 bin evaluations without the override
+    let g = geometric_with(0.01, 0.6);
+    let total: f64 = (0..1_000)
+        .map(|k| g.cumulative_supply(10_000_000 - k))
+        .sum();
+    assert!(total.is_finite() && total > 0.0);
+}
+
+#[test]
+fn closed_form_supply_matches_at_theta_zero_and_clamp_bounds() {
+    for theta in [0.0, -2.0, 2.0] {
+        for step in [1.0, 25.0, 100.0] {
+            let g = geometric_with(step, theta);
+            // θ = 2 grows as q^n; 20k bins of 1% stay finite
+            for n in [1, 10, 2_000, 20_000] {
+                let numeric = compensated_supply(&g, n);
+                let rel = (g.s_n_closed(n) / numeric - 1.0).abs();
+                assert!(rel < 1e-10, "step={step} θ={theta} n={n}: rel err {rel:e}");
+            }
+        }
+    }
+}
+
+#[test]
+fn step_curve_tiers_and_closed_form_supply() {
+    let grid = Grid {
+        p0: 0.01,
+        bin_step_bps: 25.0,
+        spacing: GridSpacing::Price,
+    };
+    let c = StepCurve::new(
+        grid,
+        vec![(0, 1000.0), (10, 500.0), (25, 0.0), (30, 200.0)],
+        50,
+    )
+    .unwrap();
+    assert_eq!(c.delta_x_of_bin(-1), 0.0);
+    assert_eq!(c.delta_x_of_bin(0), 1000.0);
+    assert_eq!(c.delta_x_of_bin(9), 1000.0);
+    assert_eq!(c.delta_x_of_bin(10), 500.0);
+    assert_eq!(c.delta_x_of_bin(27), 0.0);
+    assert_eq!(c.delta_x_of_bin(49), 200.0);
+    assert_eq!(c.price_of_bin(7), grid.price_of_bin(7));
+    for n in [0, 1, 10, 11, 26, 30, 50, 80] {
+        let summed: f64 = (0..n).map(|i| c.delta_x_of_bin(i)).sum();
+        assert_eq!(c.cumulative_supply(n), summed, "n={n}");
+    }
+    assert_eq!(c.cumulative_supply(50), 10_000.0 + 7_500.0 + 4_000.0);
+
+    assert!(StepCurve::new(grid, vec![], 50).is_err());
+    assert!(StepCurve::new(grid, vec![(1, 1.0)], 50).is_err());
+    assert!(StepCurve::new(grid, vec![(0, 1.0), (20, 2.0), (20, 3.0)], 50).is_err());
+    assert!(StepCurve::new(grid, vec![(0, 1.0), (50, 2.0)], 50).is_err());
+    assert!(StepCurve::new(grid, vec![(0, -1.0)], 50).is_err());
+}
+
+#[test]
+fn linear_new_keeps_every_allocation_non_negative() {
+    let grid = Grid::new(0.01, 25.0);
+    let c = Linear::new(grid, 100.0, -2.0, 51).unwrap();
+    assert_eq!(c.delta_x_of_bin(50), 0.0);
+    assert!(Linear::new(grid, 100.0, -2.0, 52).is_err());
+    assert!(Linear::new(grid, -1.0, 1.0, 10).is_err());
+    assert!(Linear::new(grid, f64::NAN, 0.0, 10).is_err());
+    assert!(Linear::new(grid, f64::INFINITY, 0.0, 10).is_err());
+    assert!(Linear::new(grid, 1.0, f64::NAN, 10).is_err());
+}
+
+#[test]
+fn curve_kinds_round_trip_through_their_names() {
+    assert!(!CurveKind::all().is_empty());
+    for &kind in CurveKind::all() {
+        assert_eq!(kind.name().parse::<CurveKind>().unwrap(), kind);
+        assert_eq!(kind.to_string(), kind.name());
+    }
+    let err = "gompertz".parse::<CurveKind>().unwrap_err().to_string();
+    for kind in CurveKind::all() {
+        assert!(err.contains(kind.name()), "{err}");
+    }
+    assert!("Geometric".parse::<CurveKind>().is_err());
+}
+
+#[test]
+fn price_at_supply_is_the_price_of_the_bin_holding_s() {
+    let grid = Grid {
+        p0: 0.01,
+        bin_step_bps: 25.0,
+        spacing: GridSpacing::Price,
+    };
+    let geo = Geometric {
+        grid,
+        theta: 0.6,
+        r0_quote: 100.0,
+    };
+    let bins = 200;
+    for i in [0, 1, 17, 199] {
+        // The schedule's supply_cum, which the closed form only matches to rounding
+        let start = (0..i)
+            .map(|j| geo.delta_x_of_bin(j))
+            .sum::<CompensatedSum>()
+            .value();
+        let mid = start + 0.5 * geo.delta_x_of_bin(i);
+        assert_eq!(
+            geo.price_at_supply(mid, bins),
+            geo.price_of_bin(i),
+            "bin {i}"
+        );
+        // A bin starts exactly where the previous one's supply ends
+        assert_eq!(
+            geo.price_at_supply(start, bins),
+            geo.price_of_bin(i),
+            "bin {i}"
+        );
+    }
+    assert_eq!(geo.price_at_supply(-5.0, bins), geo.price_of_bin(0));
+    let total = geo.cumulative_supply(bins);
+    assert_eq!(
+        geo.price_at_supply(total * 2.0, bins),
+        geo.price_of_bin(199)
+    );
+    assert!(geo.price_at_supply(f64::NAN, bins).is_nan());
+
+    // Empty tiers never hold the s-th token
+    let step = StepCurve::new(grid, vec![(0, 10.0), (5, 0.0), (8, 20.0)], 12).unwrap();
+    assert_eq!(step.price_at_supply(49.0, 12), grid.price_of_bin(4));
+    assert_eq!(step.price_at_supply(50.0, 12), grid.price_of_bin(8));
+
+    // Starts from first_bin on two-sided schedules
+    let two_sided = TwoSided {
+        core: geo,
+        below_bins: 3,
+    };
+    assert_eq!(two_sided.price_at_supply(0.0, bins), geo.price_of_bin(-3));
+    let boxed: Box<dyn Curve> = Box::new(two_sided);
+    let into_bin_0 = two_sided.supply_below() + 0.5 * geo.delta_x_of_bin(0);
+    assert_eq!(boxed.price_at_supply(into_bin_0, bins), geo.price_of_bin(0));
+}
+
+#[test]
+fn price_of_supply_bisection_agrees_with_the_geometric_inverse() {
+    let bins = 300;
+    for theta in [0.0, 0.6, 1.0, 1.4] {
+        let geo = geometric_with(25.0, theta);
+        // An empty mask keeps the trait's default bisection path
+        let bisected = MaskedCurve {
+            inner: geo,
+            excluded: 0..0,
+        };
+        for i in [0, 1, 50, 298] {
+            let start = geo.s_n_closed(i);
+            let rel = (geo.price_of_supply(start, bins) / geo.price_of_bin(i) - 1.0).abs();
+            assert!(rel < 1e-12, "θ={theta} bin {i}: rel err {rel:e}");
+            let rel = (bisected.price_of_supply(start, bins) / geo.price_of_bin(i) - 1.0).abs();
+            assert!(rel < 1e-12, "θ={theta} bin {i}: rel err {rel:e}");
+        }
+        let total = geo.cumulative_supply(bins);
+        let mut prev = 0.0;
+        for k in 1..200 {
+            let s = total * k as f64 / 200.0;
+            let (analytic, numeric) = (
+                geo.price_of_supply(s, bins),
+                bisected.price_of_supply(s, bins),
+            );
+            // The two only differ in how supply is spread inside one bin
+            assert!(
+                (analytic / numeric - 1.0).abs() < 1e-6,
+                "θ={theta} s={s}: {analytic} vs {numeric}"
+            );
+            assert!(analytic >= prev, "θ={theta}: not monotone at s={s}");
+            prev = analytic;
+        }
+        for c in [&geo as &dyn Curve, &bisected] {
+            assert_eq!(c.price_of_supply(0.0, bins), 0.01);
+            assert_eq!(c.price_of_supply(-1.0, bins), 0.01);
+            assert_eq!(c.price_of_supply(f64::NEG_INFINITY, bins), 0.01);
+            assert_eq!(c.price_of_supply(total * 2.0, bins), geo.price_of_bin(299));
+            assert_eq!(
+                c.price_of_supply(f64::INFINITY, bins),
+                geo.price_of_bin(299)
+            );
+            assert!(c.price_of_supply(f64::NAN, bins).is_nan());
+        }
+    }
+    // r < 1 can never sell past ΔX_0/(1 - r), however many bins are requested
+    let decaying = geometric_with(100.0, 0.0);
+    let limit = decaying.delta_x0() / (1.0 - decaying.r());
+    assert_eq!(
+        decaying.price_of_supply(limit * 1.5, 10_000),
+        decaying.price_of_bin(9_999)
+    );
+}
+
+#[test]
+fn bin_of_price_rejects_prices_off_the_grid() {
+    let grid = Grid {
+        p0: 0.01,
+        bin_step_bps: 25.0,
+        spacing: GridSpacing::Price,
+    };
+    assert_eq!(grid.bin_of_price(0.01).unwrap(), 0);
+    assert_eq!(grid.bin_of_price(0.0099).unwrap(), -5);
+    for p in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        assert!(grid.bin_of_price(p).is_err(), "p={p}");
+    }
+    let flat = Grid {
+        p0: 0.01,
+        bin_step_bps: 0.0,
+        spacing: GridSpacing::Price,
+    };
+    assert!(flat.bin_of_price(0.02).is_err());
+}
+
+#[test]
+fn curve_bin_of_price_stays_within_the_schedule() {
+    let geo = geometric_with(25.0, 0.6);
+    for i in [0, 1, 57, 99] {
+        let p = geo.price_of_bin(i);
+        assert_eq!(geo.bin_of_price(p, 100), Some(i));
+        assert_eq!(geo.bin_of_price(p * 1.0001, 100), Some(i));
+        assert_eq!(
+            geo.bin_of_price(p, 100).unwrap(),
+            geo.grid.bin_of_price(p).unwrap()
+        );
+    }
+    assert_eq!(geo.bin_of_price(geo.price_of_bin(0) * 0.999, 100), None);
+    assert_eq!(geo.bin_of_price(f64::NAN, 100), None);
+    assert_eq!(geo.bin_of_price(1e9, 100), Some(99));
+    assert_eq!(geo.bin_of_price(1e9, 0), None);
+    let boxed: Box<dyn Curve> = Box::new(geo);
+    assert_eq!(boxed.bin_of_price(geo.price_of_bin(57), 100), Some(57));
+}
+
+#[test]
+fn bin_indices_past_i32_max_keep_their_exponent() {
+    // q = 1 + 1e-10 keeps q^(3·10⁹) finite
+    let g = geometric_with(1e-6, 0.6);
+    let (i, j) = (2_000_000_000_i64, 3_000_000_000_i64);
+    assert!(g.price_of_bin(j) > g.price_of_bin(i));
+    assert!(g.price_of_bin(j) > g.price_of_bin(i64::from(i32::MAX)));
+    assert!((g.price_of_bin(j) / (0.01 * 0.3_f64.exp()) - 1.0).abs() < 1e-6);
+    assert!(g.delta_x_of_bin(j) < g.delta_x_of_bin(i));
+    assert!(g.s_n_closed(j) > g.s_n_closed(i));
+    assert_eq!(g.grid.bin_of_price(g.price_of_bin(j)).unwrap(), j);
+}
+
+/// P_i = 4^i and ΔX_i = 8·2^-i (θ = ½), so ΔX = 8, 4, 2, 1 for a quote cost of 8, 16, 32, 64
+fn quadrupling_geometric() -> Geometric {
+    Geometric {
+        grid: Grid {
+            p0: 1.0,
+            bin_step_bps: 30_000.0,
+            spacing: GridSpacing::Price,
+        },
+        theta: 0.5,
+        r0_quote: 8.0,
+    }
+}
+
+#[test]
+fn tokens_for_quote_fills_bins_then_part_of_the_last() {
+    let geo = quadrupling_geometric();
+    // 8 buys bin 0 whole; the last 5 of quote buys 5/4 of bin 1's 4 tokens at P = 4
+    assert_eq!(geo.tokens_for_quote(0, 13.0, 4), (9.25, 1));
+    // A budget ending exactly on a bin boundary stops at the next bin, untouched
+    assert_eq!(geo.tokens_for_quote(0, 24.0, 4), (12.0, 2));
+    assert_eq!(geo.tokens_for_quote(1, 40.0, 4), (5.5, 2));
+    assert_eq!(geo.tokens_for_quote(0, 3.0, 4), (3.0, 0));
+
+    // More quote than the curve holds buys every remaining token
+    assert_eq!(geo.tokens_for_quote(0, 1e9, 4), (15.0, 4));
+    assert_eq!(geo.tokens_for_quote(2, 1e9, 4), (3.0, 4));
+    assert_eq!(geo.tokens_for_quote(4, 1e9, 4), (0.0, 4));
+
+    assert_eq!(geo.tokens_for_quote(1, 0.0, 4), (0.0, 1));
+    assert_eq!(geo.tokens_for_quote(1, f64::NAN, 4), (0.0, 1));
+    let boxed: Box<dyn Curve> = Box::new(geo);
+    assert_eq!(boxed.tokens_for_quote(0, 13.0, 4), (9.25, 1));
+}
+
+#[test]
+fn quote_to_buy_range_sums_bin_revenue_and_rejects_reversed_ranges() {
+    let geo = quadrupling_geometric();
+    // R_i = P_i·ΔX_i = 8·2^i; the closed form is within an ulp or two
+    for (lo, hi, quote) in [(0, 4, 120.0), (1, 3, 48.0)] {
+        let got = geo.quote_to_buy_range(lo, hi).unwrap();
+        assert!((got - quote).abs() <= 1e-15 * quote, "[{lo}, {hi}): {got}");
+    }
+    assert_eq!(geo.quote_to_buy_range(2, 2).unwrap(), 0.0);
+    let boxed: Box<dyn Curve> = Box::new(MaskedCurve {
+        inner: geo,
+        excluded: 0..0,
+    });
+    assert_eq!(boxed.quote_to_buy_range(0, 4).unwrap(), 120.0);
+    let err = geo.quote_to_buy_range(3, 1).unwrap_err();
+    assert!(err.to_string().contains("start_bin ≤ end_bin"), "{err}");
+
+    // The closed form agrees with the compensated bin-by-bin sum
+    let geo = Geometric {
+        grid: Grid {
+            p0: 0.01,
+            bin_step_bps: 25.0,
+            spacing: GridSpacing::Price,
+        },
+        theta: 0.6,
+        r0_quote: 100.0,
+    };
+    // An empty mask keeps the trait's default summation
+    let masked = MaskedCurve {
+        inner: geo,
+        excluded: 0..0,
+    };
+    for (lo, hi) in [(0, 1), (0, 500), (137, 2000), (-20, 20)] {
+        let closed = geo.quote_to_buy_range(lo, hi).unwrap();
+        let summed = masked.quote_to_buy_range(lo, hi).unwrap();
+        assert!(
+            (closed - summed).abs() <= 1e-12 * summed,
+            "[{lo}, {hi}): {closed} vs {summed}"
+        );
+    }
+    assert!(masked.quote_to_buy_range(1, 0).is_err());
+    assert_eq!(
+        geo.quote_to_buy_range(0, 500).unwrap(),
+        geo.revenue_closed(500)
+    );
+}
+
+#[test]
+fn sqrt_price_grid_steps_the_square_root() {
+    let sqrt_grid = Grid {
+        p0: 1.0,
+        bin_step_bps: 10_000.0,
+        spacing: GridSpacing::SqrtPrice,
+    };
+    // √P doubles per bin, so P quadruples
+    assert_eq!(sqrt_grid.q_sqrt(), 2.0);
+    assert_eq!(sqrt_grid.q(), 4.0);
+    assert_eq!(sqrt_grid.price_step_bps(), 30_000.0);
+    let prices: Vec<f64> = (-1..3).map(|i| sqrt_grid.price_of_bin(i)).collect();
+    assert_eq!(prices, [0.25, 1.0, 4.0, 16.0]);
+    assert_eq!(sqrt_grid.bin_of_price(15.9).unwrap(), 1);
+    assert_eq!(sqrt_grid.bins_for_price_range(15.9), 2);
+
+    // Price spacing is the default and keeps q = 1 + step
+    let grid: Grid = serde_json::from_str(r#"{"p0": 1.0, "bin_step_bps": 25.0}"#).unwrap();
+    assert_eq!(grid.spacing, GridSpacing::Price);
+    assert_eq!(grid.q(), 1.0025);
+    assert_eq!(grid.price_step_bps(), 25.0);
+    assert_eq!(grid.price_of_bin(3), 1.0025_f64.powi(3));
+    assert_eq!(Grid::new(1.0, 25.0), grid);
+    assert_eq!(
+        Grid::new(1.0, 10_000.0).with_spacing(GridSpacing::SqrtPrice),
+        sqrt_grid
+    );
+
+    assert_eq!(
+        "sqrt-price".parse::<GridSpacing>().unwrap(),
+        GridSpacing::SqrtPrice
+    );
+    assert!("log".parse::<GridSpacing>().is_err());
+}
@@ -0,0 +1,77 @@
+use bcurve::curves::{Geometric, Grid, GridSpacing};
+use bcurve::diff::{diff_schedules, read_schedule_values, schedule_values, BinValues};
+use std::fs;
+
+fn row(bin: i64, price: f64, delta_x: f64) -> BinValues {
+    BinValues {
+        bin,
+        price,
+        delta_x,
+        revenue_bin: price * delta_x,
+    }
+}
+
+#[test]
+fn diff_aligns_on_bin_across_row_counts() {
+    let old = [row(0, 1.0, 10.0), row(1, 2.0, 5.0), row(2, 4.0, 2.0)];
+    let new = [row(1, 2.0, 6.0), row(2, 4.0, 2.0), row(3, 8.0, 1.0)];
+    let (diffs, summary) = diff_schedules(&old, &new);
+
+    assert_eq!(
+        diffs.iter().map(|d| d.bin).collect::<Vec<_>>(),
+        [0, 1, 2, 3]
+    );
+    assert_eq!(
+        (summary.matched, summary.only_old, summary.only_new),
+        (2, 1, 1)
+    );
+    // Bins 0 and 3 exist on one side only, bin 1 moved, bin 2 is identical
+    assert_eq!(summary.changed, 3);
+    assert!(!diffs[2].is_change());
+
+    assert_eq!(diffs[0].price_new, None);
+    assert_eq!(diffs[0].price_change, None);
+    assert_eq!(diffs[1].delta_x_change, Some(1.0));
+    assert_eq!(diffs[1].revenue_change, Some(2.0));
+    assert_eq!(diffs[3].price_old, None);
+
+    assert_eq!(summary.max_abs_price_change, 0.0);
+    assert_eq!(summary.max_abs_delta_x_change, 1.0);
+    assert_eq!(summary.max_abs_revenue_change, 2.0);
+    // 17 tokens before, 9 after
+    assert_eq!(summary.supply_change, -8.0);
+}
+
+#[test]
+fn schedule_csv_reads_data_rows_only() {
+    let dir = "out_diff_read_test";
+    fs::create_dir_all(dir).unwrap();
+    let path = format!("{dir}/schedule.csv");
+    fs::write(
+        &path,
+        "# Mode: Geometric, θ=0.6\n\
+         # Bins: 2 (source: explicit)\n\
+         bin,price,delta_x,supply_cum,revenue_bin,revenue_cum,fee_base,fee_var,fee_total,fee_total_bps\n\
+         0,0.01,100,100,1,1,0,0,0,0\n\
+         1,0.02,50,150,1,2,0,0,0,0\n",
+    )
+    .unwrap();
+    let rows = read_schedule_values(&path).unwrap();
+    assert_eq!(rows, [row(0, 0.01, 100.0), row(1, 0.02, 50.0)]);
+
+    // A curve diffed against its own schedule values changes nothing
+    let c = Geometric {
+        grid: Grid {
+            p0: 0.01,
+            bin_step_bps: 10.0,
+            spacing: GridSpacing::Price,
+        },
+        theta: 0.6,
+        r0_quote: 100.0,
+    };
+    let values = schedule_values(&c, 50);
+    let (_, summary) = diff_schedules(&values, &values);
+    assert_eq!((summary.matched, summary.changed), (50, 0));
+
+    let _ = fs::remove_dir_all(dir);
+}
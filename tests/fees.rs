@@ -0,0 +1,313 @@
+use bcurve::curves::{
+    AdaptiveLogistic, Curve, Geometric, Grid, GridSpacing, LogisticS, MaskedCurve, TwoSided,
+};
+use bcurve::dlmm::{
+    depth_chart, estimate_bin_apr, fee_rate_at_price, fee_revenue_tradeoff, rate_to_bps,
+    DlmmFeeParams, FeeParamsError, FEE_TRADEOFF_STEPS,
+};
+
+fn fees(variable_fee_control: f64) -> DlmmFeeParams {
+    DlmmFeeParams {
+        base_factor: 0.5,
+        bin_step_bps: 100.0,
+        variable_fee_control,
+        max_fee_rate: 0.05,
+        min_fee_rate: 0.0,
+        cap_softness: 0.0,
+        distance_fee_delta: 0.0,
+        protocol_share: 0.0,
+    }
+}
+
+#[test]
+fn total_fee_rate_pins_cap_at_extreme_volatility() {
+    let f = fees(1.0);
+    assert_eq!(f.total_fee_rate(1e12), 0.05);
+
+    // (va·s)^2 overflows to +Inf; the cap must still be returned
+    assert!(f.variable_fee_rate(f64::MAX).is_infinite());
+    assert_eq!(f.total_fee_rate(f64::MAX), 0.05);
+    assert_eq!(f.total_fee_rate(f64::INFINITY), 0.05);
+    assert_eq!(f.total_fee_rate(f64::NAN), 0.05);
+}
+
+#[test]
+fn total_fee_rate_is_uncapped_below_cap() {
+    let f = fees(1.0);
+    // f_b = 0.5 · 0.01 = 0.005, f_v = (1 · 0.01)^2 = 0.0001
+    assert!((f.total_fee_rate(1.0) - 0.0051).abs() < 1e-15);
+}
+
+#[test]
+fn fee_models_are_pluggable() {
+    use bcurve::dlmm::{FeeModel, LinearFee};
+
+    let linear = LinearFee {
+        base: 0.001,
+        slope: 0.0005,
+        max_fee_rate: 0.01,
+    };
+    let dlmm = fees(1.0);
+    let models: [&dyn FeeModel; 2] = [&linear, &dlmm];
+
+    assert!((models[0].total_fee_rate(4.0) - 0.003).abs() < 1e-15);
+    assert_eq!(models[0].total_fee_rate(1e6), 0.01);
+    assert_eq!(models[0].base_fee_rate(), 0.001);
+    assert!((models[0].variable_fee_rate(4.0) - 0.002).abs() < 1e-15);
+
+    // The DLMM impl delegates to its inherent formula
+    assert_eq!(models[1].total_fee_rate(1.0), dlmm.total_fee_rate(1.0));
+    assert_eq!(
+        models[1].variable_fee_rate(3.0),
+        dlmm.variable_fee_rate(3.0)
+    );
+}
+
+#[test]
+fn soft_cap_ramps_into_the_cap() {
+    let f = DlmmFeeParams {
+        cap_softness: 0.2,
+        ..fees(1.0)
+    };
+    // raw = 0.005 + (va·0.01)^2; knee at 0.04, cap reached at raw 0.06
+    assert_eq!(f.total_fee_rate(10.0), fees(1.0).total_fee_rate(10.0));
+    let va = (0.045_f64).sqrt() * 100.0; // raw = 0.05
+    assert!((f.total_fee_rate(va) - 0.045).abs() < 1e-12);
+    assert_eq!(fees(1.0).total_fee_rate(va), 0.05);
+    assert_eq!(f.total_fee_rate(1e12), 0.05);
+}
+
+#[test]
+fn fee_floor_holds_at_zero_volatility() {
+    // Base fee alone is 0.005
+    let f = fees(1.0).with_min_fee_rate(0.008).unwrap();
+    assert_eq!(f.total_fee_rate(0.0), 0.008);
+    assert_eq!(f.total_fee_rate_at_bin(0.0, 3), 0.008);
+    // Above the floor and at the cap nothing changes
+    assert_eq!(f.total_fee_rate(10.0), fees(1.0).total_fee_rate(10.0));
+    assert_eq!(f.total_fee_rate(1e12), 0.05);
+    // A floor at the cap pins every fee to it
+    let pinned = fees(1.0).with_min_fee_rate(0.05).unwrap();
+    assert_eq!(pinned.total_fee_rate(0.0), 0.05);
+}
+
+#[test]
+fn bin_apr_is_fee_income_over_tvl_and_skips_empty_bins() {
+    let geo = Geometric {
+        grid: Grid {
+            p0: 0.01,
+            bin_step_bps: 100.0,
+            spacing: GridSpacing::Price,
+        },
+        theta: 0.6,
+        r0_quote: 100.0,
+    };
+    let c = MaskedCurve {
+        inner: geo,
+        excluded: 2..3,
+    };
+    let f = fees(1.0);
+    let aprs = estimate_bin_apr(&c, 4, &f, 1.0, 50.0);
+    assert_eq!(aprs.len(), 4);
+    // Bin 0 holds R_0 = 100 quote of value
+    let expected = 50.0 * f.total_fee_rate(1.0) * 365.0 / 100.0;
+    assert_eq!(aprs[0].0, 0);
+    assert!((aprs[0].1.unwrap() - expected).abs() < 1e-12);
+    assert!(aprs[1].1.unwrap() < expected, "TVL grows as q^θ per bin");
+    assert_eq!(aprs[2], (2, None));
+}
+
+#[test]
+fn distance_fee_grows_the_base_away_from_the_active_bin() {
+    let flat = fees(1.0);
+    let f = DlmmFeeParams {
+        distance_fee_delta: 0.1,
+        ..flat
+    };
+    assert_eq!(flat.base_fee_rate_at_bin(50), flat.base_fee_rate());
+    assert_eq!(f.base_fee_rate_at_bin(0), f.base_fee_rate());
+    // f_b = 0.005 · (1 + 0.1·10)
+    assert!((f.base_fee_rate_at_bin(10) - 0.01).abs() < 1e-15);
+    assert_eq!(f.base_fee_rate_at_bin(-10), f.base_fee_rate_at_bin(10));
+    assert!((f.total_fee_rate_at_bin(1.0, 10) - 0.0101).abs() < 1e-15);
+    // The cap still applies far from the active bin
+    assert_eq!(f.total_fee_rate_at_bin(0.0, 1_000), 0.05);
+}
+
+#[test]
+fn validated_constructor_rejects_nonsensical_schedules() {
+    let f = DlmmFeeParams::new(0.5, 100.0, 1.0, 0.05).unwrap();
+    assert_eq!(f, fees(1.0));
+
+    assert_eq!(
+        DlmmFeeParams::new(0.5, 100.0, 1.0, 1.5),
+        Err(FeeParamsError::MaxFeeRate(1.5))
+    );
+    assert!(DlmmFeeParams::new(0.5, 100.0, 1.0, f64::NAN).is_err());
+    assert_eq!(
+        DlmmFeeParams::new(-0.1, 100.0, 1.0, 0.05),
+        Err(FeeParamsError::BaseFactor(-0.1))
+    );
+    assert_eq!(
+        DlmmFeeParams::new(0.5, 100.0, -1.0, 0.05),
+        Err(FeeParamsError::VariableFeeControl(-1.0))
+    );
+    assert!(DlmmFeeParams::new(0.5, 0.0, 1.0, 0.05).is_err());
+
+    let soft = DlmmFeeParams {
+        cap_softness: 1.0,
+        ..f
+    };
+    assert_eq!(soft.validate(), Err(FeeParamsError::CapSoftness(1.0)));
+    let share = DlmmFeeParams {
+        protocol_share: 1.5,
+        ..f
+    };
+    assert_eq!(share.validate(), Err(FeeParamsError::ProtocolShare(1.5)));
+    assert_eq!(
+        f.with_min_fee_rate(0.06),
+        Err(FeeParamsError::MinFeeRate(0.06))
+    );
+    assert!(f.with_min_fee_rate(-0.01).is_err());
+    assert!(FeeParamsError::MaxFeeRate(1.5)
+        .to_string()
+        .contains("max_fee_rate"));
+}
+
+#[test]
+fn depth_chart_accumulates_away_from_the_active_bin() {
+    let geo = Geometric {
+        grid: Grid {
+            p0: 0.01,
+            bin_step_bps: 25.0,
+            spacing: GridSpacing::Price,
+        },
+        theta: 0.6,
+        r0_quote: 100.0,
+    };
+    let two_sided = TwoSided {
+        core: geo,
+        below_bins: 5,
+    };
+    let depth = depth_chart(&two_sided, 0, 10);
+    assert_eq!(depth.len(), 15);
+    assert!(depth.windows(2).all(|w| w[0].0 < w[1].0));
+    // Bids: deepest at the lowest price, nearest bin holds just ΔX_{-1}
+    assert!((depth[0].1 - two_sided.supply_below()).abs() < 1e-9);
+    assert_eq!(depth[4].1, two_sided.delta_x_of_bin(-1));
+    // Asks start over at the active bin and end at the upside supply
+    assert_eq!(depth[5], (geo.price_of_bin(0), geo.delta_x_of_bin(0)));
+    assert!((depth[14].1 / two_sided.supply_above(10) - 1.0).abs() < 1e-12);
+
+    // One-sided curves have no bids
+    let asks = depth_chart(&geo, 0, 10);
+    assert_eq!(asks, depth[5..].to_vec());
+}
+
+#[test]
+fn fee_bps_round_to_nearest_and_saturate() {
+    assert_eq!(rate_to_bps(0.0025), 25);
+    assert_eq!(rate_to_bps(0.00254), 25);
+    assert_eq!(rate_to_bps(0.00255), 26);
+    assert_eq!(rate_to_bps(-0.01), 0);
+    assert_eq!(rate_to_bps(f64::NAN), 0);
+    assert_eq!(rate_to_bps(1.5), 10_000);
+    assert_eq!(rate_to_bps(f64::INFINITY), 10_000);
+
+    let f = fees(0.0);
+    assert_eq!(f.base_fee_bps(), rate_to_bps(f.base_fee_rate()));
+    assert_eq!(f.variable_fee_bps(5.0), 0);
+    let f = fees(10_000.0);
+    for va in [0.0, 1.0, 5.0, 50.0, 1e6] {
+        let bps = f.total_fee_bps(va);
+        assert_eq!(
+            bps,
+            (f.total_fee_rate(va) * 10_000.0).round() as u32,
+            "va={va}"
+        );
+        assert!(bps <= 10_000);
+    }
+}
+
+#[test]
+fn fee_at_price_interpolates_between_bins() {
+    let geo = Geometric {
+        grid: Grid {
+            p0: 0.01,
+            bin_step_bps: 100.0,
+            spacing: GridSpacing::Price,
+        },
+        theta: 0.6,
+        r0_quote: 100.0,
+    };
+    let flat = fees(10_000.0);
+    for price in [0.005, 0.01, 0.0123, geo.price_of_bin(40), 5.0] {
+        assert_eq!(
+            fee_rate_at_price(&geo, price, 100, &flat, 2.0),
+            flat.total_fee_rate(2.0)
+        );
+    }
+
+    let distance = DlmmFeeParams {
+        distance_fee_delta: 0.1,
+        ..fees(0.0)
+    };
+    for i in [0, 1, 7, 30] {
+        let at_bin = fee_rate_at_price(&geo, geo.price_of_bin(i), 100, &distance, 0.0);
+        assert!((at_bin - distance.total_fee_rate_at_bin(0.0, i)).abs() < 1e-15);
+    }
+    // Halfway between bins 3 and 4 in log price
+    let mid = (geo.price_of_bin(3) * geo.price_of_bin(4)).sqrt();
+    let expected =
+        0.5 * (distance.total_fee_rate_at_bin(0.0, 3) + distance.total_fee_rate_at_bin(0.0, 4));
+    assert!((fee_rate_at_price(&geo, mid, 100, &distance, 0.0) - expected).abs() < 1e-15);
+    // Below the schedule the first bin applies
+    assert_eq!(
+        fee_rate_at_price(&geo, 0.001, 100, &distance, 0.0),
+        distance.base_fee_rate()
+    );
+    assert!(fee_rate_at_price(&geo, f64::NAN, 100, &distance, 0.0).is_nan());
+
+    // Past the top of a bounded curve the top bin applies
+    let target = LogisticS {
+        grid: geo.grid,
+        p_min: 0.005,
+        p_max: 0.05,
+        k: 1e-5,
+        s_mid: 2e5,
+        bins: 50,
+    };
+    let adaptive = AdaptiveLogistic::new(target, 0.011, 0.04, 50).unwrap();
+    assert_eq!(
+        fee_rate_at_price(&adaptive, 1.0, 50, &distance, 0.0),
+        distance.total_fee_rate_at_bin(0.0, 49)
+    );
+}
+
+#[test]
+fn fee_revenue_tradeoff_sweeps_multiples_of_the_base_fee() {
+    // base fee 0.5·1% = 0.005, swept up to 4× = 0.02
+    let rows = fee_revenue_tradeoff(&fees(0.0), 40.0, 1000.0);
+    assert_eq!(rows.len(), FEE_TRADEOFF_STEPS + 1);
+    assert_eq!(rows[0], (0.0, 0.0));
+    let (fee, revenue) = rows[10];
+    assert!((fee - 0.005).abs() < 1e-15, "{fee}");
+    assert!((revenue - 0.005 * 1000.0 * (1.0 - 0.005 * 40.0)).abs() < 1e-9);
+    assert!((rows[FEE_TRADEOFF_STEPS].0 - 0.02).abs() < 1e-15);
+
+    // revenue fee·V·(1 - 40·fee) peaks at fee = 1/80, i.e. multiplier 2.5
+    let best = (0..rows.len())
+        .max_by(|&a, &b| rows[a].1.total_cmp(&rows[b].1))
+        .unwrap();
+    assert_eq!(best, 25);
+    assert!((rows[best].1 - 6.25).abs() < 1e-9);
+
+    // fees above max_fee_rate are capped
+    let capped = DlmmFeeParams {
+        max_fee_rate: 0.01,
+        ..fees(0.0)
+    };
+    let rows = fee_revenue_tradeoff(&capped, 40.0, 1000.0);
+    assert!(rows.iter().all(|&(fee, _)| fee <= 0.01));
+    assert_eq!(rows[FEE_TRADEOFF_STEPS].0, 0.01);
+}
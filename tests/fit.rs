@@ -0,0 +1,119 @@
+use approx::assert_relative_eq;
+use bcurve::curves::{Curve, Geometric, Grid, GridSpacing, LogisticS};
+use bcurve::fit::curve_for_fdv_path;
+
+fn logistic(p_min: f64, p_max: f64, k: f64, s_mid: f64, s: f64) -> f64 {
+    p_min + (p_max - p_min) / (1.0 + (-k * (s - s_mid)).exp())
+}
+
+#[test]
+fn fit_recovers_logistic_parameters() {
+    let (p_min, p_max, k, s_mid) = (0.001, 0.05, 8e-8, 5e7);
+    let points: Vec<(f64, f64)> = (0..=20)
+        .map(|j| {
+            let s = j as f64 * 5e6;
+            (s, logistic(p_min, p_max, k, s_mid, s))
+        })
+        .collect();
+    let grid = Grid {
+        p0: 0.0015,
+        bin_step_bps: 10.0,
+        spacing: GridSpacing::Price,
+    };
+    let fit = LogisticS::fit(&points, grid, 500).expect("fit converges");
+    assert_relative_eq!(fit.curve.p_min, p_min, max_relative = 1e-4);
+    assert_relative_eq!(fit.curve.p_max, p_max, max_relative = 1e-4);
+    assert_relative_eq!(fit.curve.k, k, max_relative = 1e-4);
+    assert_relative_eq!(fit.curve.s_mid, s_mid, max_relative = 1e-4);
+    assert!(fit.rms_residual < 1e-8, "residual {}", fit.rms_residual);
+    assert_eq!(fit.curve.bins, 500);
+}
+
+#[test]
+fn fit_rejects_too_few_points() {
+    let grid = Grid {
+        p0: 0.01,
+        bin_step_bps: 10.0,
+        spacing: GridSpacing::Price,
+    };
+    assert!(LogisticS::fit(&[(0.0, 0.01), (1.0, 0.02), (2.0, 0.03)], grid, 10).is_err());
+}
+
+#[test]
+fn fdv_path_hits_supply_targets_with_snapped_prices() {
+    let grid = Grid {
+        p0: 0.01,
+        bin_step_bps: 25.0,
+        spacing: GridSpacing::Price,
+    };
+    // price = fdv / supply: 0.012, 0.015, 0.02
+    let points = [(1e6, 12_000.0), (3e6, 45_000.0), (6e6, 120_000.0)];
+    let fit = curve_for_fdv_path(grid, &points).unwrap();
+    assert_eq!(fit.curve.tiers.len(), 3);
+    // Tier k ends on point k's bin, where cumulative supply meets the target
+    for (k, &(supply, fdv)) in points.iter().enumerate() {
+        let end = fit.curve.tiers.get(k + 1).map_or(fit.bins, |t| t.0);
+        assert_relative_eq!(
+            fit.curve.cumulative_supply(end),
+            supply,
+            max_relative = 1e-12
+        );
+        let p = fit.curve.price_of_bin(end - 1);
+        assert!((p / (fdv / supply) - 1.0).abs() <= fit.max_snap_error + 1e-15);
+    }
+    // Snapping to the nearest bin errs by at most half a bin step
+    assert!(fit.max_snap_error > 0.0 && fit.max_snap_error < 0.5 * 25.0 / 10_000.0);
+
+    // Prices must climb at least one bin between points
+    assert!(curve_for_fdv_path(grid, &[(1e6, 12_000.0), (2e6, 24_000.0)]).is_err());
+    assert!(curve_for_fdv_path(grid, &[(2e6, 30_000.0), (1e6, 20_000.0)]).is_err());
+    assert!(curve_for_fdv_path(grid, &[(1e6, -1.0)]).is_err());
+    assert!(curve_for_fdv_path(grid, &[]).is_err());
+}
+
+#[test]
+fn best_fit_recovers_a_geometric_and_approximates_a_logistic() {
+    let grid = Grid {
+        p0: 0.01,
+        bin_step_bps: 25.0,
+        spacing: GridSpacing::Price,
+    };
+    let exact = Geometric {
+        grid,
+        theta: 0.37,
+        r0_quote: 250.0,
+    };
+    let fit = Geometric::best_fit_to(&exact, 400).unwrap();
+    assert!((fit.curve.theta - 0.37).abs() < 1e-6, "{}", fit.curve.theta);
+    assert!((fit.curve.r0_quote / 250.0 - 1.0).abs() < 1e-6);
+    assert!((fit.curve.grid.q() / grid.q() - 1.0).abs() < 1e-12);
+    assert!(fit.max_bin_error <= 1e-6 * exact.delta_x0());
+
+    let logistic = LogisticS {
+        grid,
+        p_min: 0.005,
+        p_max: 0.05,
+        k: 1e-5,
+        s_mid: 2e5,
+        bins: 300,
+    };
+    let fit = Geometric::best_fit_to(&logistic, 300).unwrap();
+    let sse = |c: &Geometric| -> f64 {
+        (0..300)
+            .map(|i| (c.delta_x_of_bin(i) - logistic.delta_x_of_bin(i)).powi(2))
+            .sum()
+    };
+    assert!((fit.rms_residual - (sse(&fit.curve) / 300.0).sqrt()).abs() <= 1e-9 * fit.rms_residual);
+    assert!(fit.max_bin_error >= fit.rms_residual);
+    // No nearby θ or R_0 does better
+    for (dt, dr) in [(1e-3, 1.0), (-1e-3, 1.0), (0.0, 1.001), (0.0, 0.999)] {
+        let nudged = Geometric {
+            theta: fit.curve.theta + dt,
+            r0_quote: fit.curve.r0_quote * dr,
+            ..fit.curve
+        };
+        assert!(sse(&nudged) >= sse(&fit.curve));
+    }
+
+    assert!(Geometric::best_fit_to(&logistic, 1).is_err());
+}
@@ -0,0 +1,380 @@
+use bcurve::dlmm::{
+    eip55_checksum, load_allowlist, parse_allowlist, AddressMatch, Allowlist, AllowlistEntry,
+    AllowlistFormat, AllowlistStats, LaunchPhasePolicy, ParsedAllowlist, TauDecay,
+};
+use std::collections::HashSet;
+
+fn policy() -> LaunchPhasePolicy {
+    LaunchPhasePolicy {
+        allowlist: Allowlist::default(),
+        tau_start_pct: 50.0,
+        tau_end_pct: 5.0,
+        ramp_secs: 120.0,
+        tau_decay: TauDecay::Linear,
+    }
+}
+
+/// `plain` addresses plus the entries of `parsed`
+fn allowlist(plain: &[&str], parsed: ParsedAllowlist) -> Allowlist {
+    let mut allowlist: Allowlist = plain.iter().collect();
+    for entry in parsed.entries.into_values() {
+        allowlist.insert_entry(entry);
+    }
+    allowlist
+}
+
+#[test]
+fn tau_by_volume_ramps_over_target_volume() {
+    let p = policy();
+    assert_eq!(p.tau_by_volume(0.0, 1_000_000.0), 50.0);
+    assert_eq!(p.tau_by_volume(500_000.0, 1_000_000.0), 27.5);
+    assert_eq!(p.tau_by_volume(1_000_000.0, 1_000_000.0), 5.0);
+    assert_eq!(p.tau_by_volume(5_000_000.0, 1_000_000.0), 5.0);
+}
+
+#[test]
+fn exponential_tau_starts_at_tau_start_and_decays_to_tau_end() {
+    let p = LaunchPhasePolicy {
+        tau_decay: TauDecay::Exponential {
+            half_life_secs: 10.0,
+        },
+        ..policy()
+    };
+    assert_eq!(p.tau(0.0), 50.0);
+    assert_eq!(p.tau(10.0), 27.5);
+    assert_eq!(p.tau(20.0), 16.25);
+    // Past the linear ramp the exponential is still above τ_end, then underflows onto it
+    assert!(p.tau(120.0) > 5.0);
+    assert!((p.tau(300.0) - 5.0).abs() < 1e-6);
+    assert_eq!(p.tau(1e6), 5.0);
+
+    // The linear default is unchanged
+    assert_eq!(policy().tau(60.0), 27.5);
+    assert_eq!(policy().tau(120.0), 5.0);
+
+    assert_eq!("linear".parse::<TauDecay>(), Ok(TauDecay::Linear));
+    assert_eq!(
+        "exponential:30".parse::<TauDecay>(),
+        Ok(TauDecay::Exponential {
+            half_life_secs: 30.0
+        })
+    );
+    assert!("exponential:0".parse::<TauDecay>().is_err());
+    assert!("exponential".parse::<TauDecay>().is_err());
+}
+
+#[test]
+fn load_allowlist_validates_and_counts() {
+    let good = "0x52908400098527886E0F7030069857D2E4169EE7";
+    let text = format!("{good}\n\nvitalik.eth\n {good} \n0x1234\nnot an address\n");
+
+    let (set, stats) = load_allowlist(&text, AllowlistFormat::Evm, AddressMatch::Exact);
+    assert_eq!(set.len(), 1);
+    assert!(set.contains(good));
+    assert_eq!(
+        stats,
+        AllowlistStats {
+            total_lines: 6,
+            valid: 1,
+            duplicates: 1,
+            malformed: vec![3, 5, 6],
+        }
+    );
+
+    let (set, stats) = load_allowlist(&text, AllowlistFormat::Any, AddressMatch::Exact);
+    assert_eq!(set.len(), 3);
+    assert_eq!(stats.malformed, vec![6]);
+    assert_eq!("evm".parse::<AllowlistFormat>(), Ok(AllowlistFormat::Evm));
+    assert!("ens".parse::<AllowlistFormat>().is_err());
+}
+
+#[test]
+fn eip55_checksums_match_the_spec_vectors() {
+    for addr in [
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ] {
+        assert_eq!(eip55_checksum(&addr.to_lowercase()).as_deref(), Some(addr));
+        assert!(AllowlistFormat::EvmChecksum.is_valid(addr));
+    }
+    assert_eq!(eip55_checksum("0x1234"), None);
+    assert_eq!(
+        eip55_checksum("5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"),
+        None
+    );
+}
+
+#[test]
+fn load_allowlist_normalizes_case_and_checks_eip55() {
+    let checksummed = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+    // One letter flipped: still hex, but the checksum no longer holds
+    let typo = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD";
+    let lower = checksummed.to_lowercase();
+    let upper = format!("0x{}", &checksummed[2..].to_uppercase());
+    let text = format!(
+        "{checksummed}
+{lower}
+{upper}
+{typo}
+"
+    );
+
+    let (set, stats) = load_allowlist(
+        &text,
+        AllowlistFormat::EvmChecksum,
+        AddressMatch::CaseInsensitive,
+    );
+    assert_eq!(set, HashSet::from([lower.clone()]));
+    assert_eq!((stats.duplicates, stats.malformed), (2, vec![4]));
+
+    // Plain evm accepts the typo and, case-insensitively, folds it into the same address
+    let (set, stats) = load_allowlist(&text, AllowlistFormat::Evm, AddressMatch::CaseInsensitive);
+    assert_eq!(set.len(), 1);
+    assert_eq!(stats.duplicates, 3);
+
+    let (set, _) = load_allowlist(&text, AllowlistFormat::Evm, AddressMatch::Exact);
+    assert_eq!(set.len(), 4);
+    assert_eq!(
+        "evm-checksum".parse::<AllowlistFormat>(),
+        Ok(AllowlistFormat::EvmChecksum)
+    );
+}
+
+#[test]
+fn parse_allowlist_reads_json_csv_and_plain_text() {
+    let parse = |text: &str| {
+        parse_allowlist(text, AllowlistFormat::Any, AddressMatch::CaseInsensitive).unwrap()
+    };
+    let json = r#"[
+        {"address": "Team", "tier": "core"},
+        {"address": "seed_fund", "tier": "seed", "expires_at": 60.0},
+        {"address": "bad address"}
+    ]"#;
+    let parsed = parse(json);
+    assert!(parsed.addresses.is_empty());
+    assert_eq!(
+        parsed.entries["team"],
+        AllowlistEntry {
+            address: "Team".to_string(),
+            tier: Some("core".to_string()),
+            expires_at: None,
+            discount: None,
+        }
+    );
+    assert_eq!(parsed.entries["seed_fund"].expires_at, Some(60.0));
+    assert_eq!(parsed.stats.malformed, vec![3]);
+
+    let csv = "address,tier,expires_at\nteam,core,\nseed_fund,seed,60\nTEAM,,\n";
+    let parsed = parse(csv);
+    assert_eq!(parsed.entries.len(), 2);
+    assert_eq!(parsed.stats.duplicates, 1);
+    // The later duplicate wins
+    assert_eq!(parsed.entries["team"].tier, None);
+    assert_eq!(parsed.entries["seed_fund"].expires_at, Some(60.0));
+
+    // No header: the plain newline-separated format
+    let parsed = parse("team\nseed_fund\n");
+    assert!(parsed.entries.is_empty());
+    assert_eq!(
+        parsed.addresses,
+        HashSet::from(["team".into(), "seed_fund".into()])
+    );
+
+    assert!(parse_allowlist(
+        "address,expires_at\nteam,soon\n",
+        AllowlistFormat::Any,
+        AddressMatch::CaseInsensitive
+    )
+    .is_err());
+}
+
+#[test]
+fn expired_entries_lose_their_exemption() {
+    let parsed = parse_allowlist(
+        "address,tier,expires_at\nteam,core,\nseed_fund,seed,60\n",
+        AllowlistFormat::Any,
+        AddressMatch::CaseInsensitive,
+    )
+    .unwrap();
+    let p = LaunchPhasePolicy {
+        allowlist: allowlist(&["mm"], parsed),
+        ..policy()
+    };
+    assert_eq!(p.allowlist_len(), 3);
+    assert!(p.is_allowed_at("seed_fund", 59.9));
+    assert!(!p.is_allowed_at("Seed_Fund", 60.0));
+    assert!(p.is_allowed_at("team", 1e9));
+    assert!(p.is_allowed_at("mm", 1e9));
+    assert!(!p.is_allowed_at("someone", 0.0));
+    // Without a time, entries are checked at launch
+    assert!(p.is_allowed("seed_fund"));
+}
+
+#[test]
+fn allowed_checks_agree_with_effective_tau() {
+    let parsed = parse_allowlist(
+        "address,tier,expires_at,discount\n\
+         presale,seed,0,\n\
+         community,community,,0.25\n\
+         mm,maker,60,\n",
+        AllowlistFormat::Any,
+        AddressMatch::CaseInsensitive,
+    )
+    .unwrap();
+    let p = LaunchPhasePolicy {
+        allowlist: allowlist(&["mm", "team"], parsed),
+        ..policy()
+    };
+    // An entry that has expired by launch is not allowed, with or without a time
+    assert!(!p.is_allowed("presale"));
+    assert!(!p.is_allowed_at("presale", 10.0));
+    assert_eq!(p.effective_tau("presale", 10.0), p.tau(10.0));
+    // A partial discount still pays part of τ(t), so it is not "allowed"
+    assert!(!p.is_allowed_at("community", 10.0));
+    assert!(p.effective_tau("community", 10.0) > 0.0);
+    // On the plain list and with an entry: counted once, and the entry's expiry applies
+    assert_eq!(p.allowlist_len(), 4);
+    assert_eq!(p.allowlist.addresses().count(), 4);
+    assert!(p.is_allowed_at("mm", 59.0));
+    assert!(!p.is_allowed_at("mm", 60.0));
+    assert_eq!(p.effective_tau("mm", 60.0), p.tau(60.0));
+    for (addr, t) in [("team", 1e9), ("mm", 30.0), ("someone", 0.0)] {
+        assert_eq!(p.is_allowed_at(addr, t), p.effective_tau(addr, t) == 0.0);
+    }
+}
+
+#[test]
+fn effective_tau_scales_by_discount_tier() {
+    let parsed = parse_allowlist(
+        "address,tier,expires_at,discount\n\
+         team,core,,\n\
+         seed_fund,seed,,0.5\n\
+         community,community,60,0.25\n\
+         overdrawn,promo,,1.5\n\
+         surcharged,promo,,-1\n",
+        AllowlistFormat::Any,
+        AddressMatch::CaseInsensitive,
+    )
+    .unwrap();
+    let p = LaunchPhasePolicy {
+        allowlist: allowlist(&["mm"], parsed),
+        ..policy()
+    };
+    let t = 30.0;
+    let tau = p.tau(t);
+    assert!(tau > 0.0);
+    // Full exemption, from an entry without a discount or the plain allowlist
+    assert_eq!(p.effective_tau("team", t), 0.0);
+    assert_eq!(p.effective_tau("MM", t), 0.0);
+    // Partial discounts, until the entry expires
+    assert_eq!(p.effective_tau("seed_fund", t), 0.5 * tau);
+    assert_eq!(p.effective_tau("community", t), 0.75 * tau);
+    assert_eq!(p.effective_tau("community", 60.0), p.tau(60.0));
+    // No discount for unknown addresses; out-of-range discounts clamp to [0, 1]
+    assert_eq!(p.effective_tau("someone", t), tau);
+    assert_eq!(p.effective_tau("overdrawn", t), 0.0);
+    assert_eq!(p.effective_tau("surcharged", t), tau);
+    // Pure: the same inputs always give the same answer
+    assert_eq!(
+        p.effective_tau("seed_fund", t),
+        p.effective_tau("seed_fund", t)
+    );
+}
+
+#[cfg(feature = "bloom")]
+#[test]
+fn bloom_allowlist_has_no_false_negatives_and_bounded_false_positives() {
+    use bcurve::dlmm::BloomAllowlist;
+
+    let listed: Vec<String> = (0..20_000).map(|i| format!("0x{i:040x}")).collect();
+    let policy = LaunchPhasePolicy {
+        tau_start_pct: 50.0,
+        tau_end_pct: 5.0,
+        ramp_secs: 120.0,
+        ..LaunchPhasePolicy::with_bloom(&listed, 1e-3).unwrap()
+    };
+    assert_eq!(policy.allowlist.len(), 20_000);
+    assert!(listed.iter().all(|a| policy.is_allowed(a)));
+    let false_positives = (0..100_000)
+        .filter(|i| policy.is_allowed(&format!("unlisted_{i}")))
+        .count();
+    // 1e-3 of 100k is 100 expected
+    assert!(false_positives < 300, "{false_positives}");
+
+    let bloom = policy.allowlist.bloom().unwrap();
+    assert_eq!(bloom.len(), 20_000);
+    // ≈14.4 bits per address, far below the exact set
+    assert!(bloom.size_bytes() < 20_000 * 2);
+
+    // Serialized filters answer the same
+    let json = serde_json::to_string(&policy).unwrap();
+    let back: LaunchPhasePolicy = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, policy);
+
+    for bad in [0.0, 1.0, f64::NAN] {
+        assert!(BloomAllowlist::new(&listed, bad).is_err());
+    }
+    let empty = LaunchPhasePolicy::with_bloom(Vec::<String>::new(), 0.01).unwrap();
+    assert!(!empty.is_allowed("anyone"));
+}
+
+#[test]
+fn allowlist_keys_addresses_however_they_are_inserted() {
+    let checksummed = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+    let mut allowlist = Allowlist::default();
+    assert!(allowlist.insert(checksummed));
+    assert!(!allowlist.insert(&checksummed.to_uppercase()));
+    allowlist.insert_entry(AllowlistEntry {
+        address: "Seed_Fund".to_string(),
+        tier: None,
+        expires_at: None,
+        discount: Some(0.5),
+    });
+    let p = LaunchPhasePolicy {
+        allowlist,
+        ..policy()
+    };
+    for addr in [checksummed, &checksummed.to_lowercase()] {
+        assert!(p.is_allowed(addr), "{addr}");
+    }
+    assert_eq!(p.effective_tau("SEED_FUND", 0.0), 0.5 * p.tau(0.0));
+    assert_eq!(p.allowlist.len(), 2);
+
+    // Keys written by hand in another case are re-keyed when deserialized
+    let json = format!(
+        r#"{{"allowlist": ["{checksummed}"], "tau_start_pct": 50.0, "tau_end_pct": 5.0, "ramp_secs": 120.0}}"#
+    );
+    let back: LaunchPhasePolicy = serde_json::from_str(&json).unwrap();
+    assert!(back.is_allowed(checksummed));
+    assert!(back.is_allowed(&checksummed.to_lowercase()));
+    assert_eq!(
+        serde_json::from_str::<LaunchPhasePolicy>(&serde_json::to_string(&p).unwrap()).unwrap(),
+        p
+    );
+
+    // Exact matching keeps the address as given
+    let mut exact = Allowlist::new(AddressMatch::Exact);
+    exact.insert(checksummed);
+    assert_eq!(exact.addresses().collect::<Vec<_>>(), [checksummed]);
+}
+
+#[test]
+fn allowlist_serializes_addresses_sorted() {
+    let addrs: Vec<String> = (0..64).map(|i| format!("0x{:040x}", 97 * i % 64)).collect();
+    let forward: Allowlist = addrs.iter().collect();
+    let reversed: Allowlist = addrs.iter().rev().collect();
+    let json = serde_json::to_string(&forward).unwrap();
+    assert_eq!(json, serde_json::to_string(&reversed).unwrap());
+
+    let v: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let listed: Vec<&str> = v["allowlist"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a.as_str().unwrap())
+        .collect();
+    assert!(listed.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(serde_json::from_str::<Allowlist>(&json).unwrap(), forward);
+}
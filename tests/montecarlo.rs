@@ -0,0 +1,131 @@
+use bcurve::curves::{Geometric, Grid, GridSpacing};
+use bcurve::dlmm::{Allowlist, DlmmFeeParams, LaunchPhasePolicy, TauDecay};
+use bcurve::montecarlo::{simulate, Distribution, MonteCarloConfig};
+
+fn curve() -> Geometric {
+    Geometric {
+        grid: Grid {
+            p0: 0.01,
+            bin_step_bps: 25.0,
+            spacing: GridSpacing::Price,
+        },
+        theta: 0.6,
+        r0_quote: 100.0,
+    }
+}
+
+fn fees() -> DlmmFeeParams {
+    DlmmFeeParams {
+        base_factor: 1.0,
+        bin_step_bps: 25.0,
+        variable_fee_control: 0.0,
+        max_fee_rate: 0.10,
+        min_fee_rate: 0.0,
+        cap_softness: 0.0,
+        distance_fee_delta: 0.0,
+        protocol_share: 0.0,
+    }
+}
+
+fn policy() -> LaunchPhasePolicy {
+    LaunchPhasePolicy {
+        allowlist: ["team", "mm"].into_iter().collect(),
+        tau_start_pct: 50.0,
+        tau_end_pct: 5.0,
+        ramp_secs: 120.0,
+        tau_decay: TauDecay::Linear,
+    }
+}
+
+fn config() -> MonteCarloConfig {
+    MonteCarloConfig {
+        runs: 400,
+        horizon_secs: 120.0,
+        arrival_rate: 0.5,
+        median_quote: 100.0,
+        size_sigma: 1.0,
+        allowlisted_share: 0.0,
+        va: 0.0,
+    }
+}
+
+#[test]
+fn same_seed_reproduces_the_same_statistics() {
+    let (c, f, p) = (curve(), fees(), policy());
+    let a = simulate(&c, 2_000, &f, &p, &config(), 42).unwrap();
+    let b = simulate(&c, 2_000, &f, &p, &config(), 42).unwrap();
+    assert_eq!(a, b);
+    let other = simulate(&c, 2_000, &f, &p, &config(), 43).unwrap();
+    assert_ne!(a.fee_revenue, other.fee_revenue);
+
+    // Poisson arrivals average rate × horizon buys per launch
+    assert!(
+        (a.mean_trades / 60.0 - 1.0).abs() < 0.05,
+        "{}",
+        a.mean_trades
+    );
+    let d = a.fee_revenue;
+    assert!(d.min <= d.p5 && d.p5 <= d.p50 && d.p50 <= d.p95 && d.p95 <= d.max);
+    assert!(a.surcharge_revenue.p50 > a.fee_revenue.p50);
+    assert!(a.final_active_bin.p50 > 0.0);
+}
+
+#[test]
+fn allowlisted_flow_pays_no_surcharge() {
+    let config = MonteCarloConfig {
+        allowlisted_share: 1.0,
+        ..config()
+    };
+    let stats = simulate(&curve(), 2_000, &fees(), &policy(), &config, 7).unwrap();
+    assert_eq!(stats.surcharge_revenue.max, 0.0);
+    assert!(stats.fee_revenue.p50 > 0.0);
+
+    // Without an allowlist everyone pays
+    let public = LaunchPhasePolicy {
+        allowlist: Allowlist::default(),
+        ..policy()
+    };
+    let stats = simulate(&curve(), 2_000, &fees(), &public, &config, 7).unwrap();
+    assert!(stats.surcharge_revenue.min > 0.0);
+}
+
+#[test]
+fn rejects_degenerate_configs() {
+    let (c, f, p) = (curve(), fees(), policy());
+    for bad in [
+        MonteCarloConfig {
+            runs: 0,
+            ..config()
+        },
+        MonteCarloConfig {
+            horizon_secs: 0.0,
+            ..config()
+        },
+        MonteCarloConfig {
+            arrival_rate: f64::NAN,
+            ..config()
+        },
+        MonteCarloConfig {
+            median_quote: -1.0,
+            ..config()
+        },
+        MonteCarloConfig {
+            allowlisted_share: 1.5,
+            ..config()
+        },
+    ] {
+        assert!(simulate(&c, 100, &f, &p, &bad, 1).is_err(), "{bad:?}");
+    }
+}
+
+#[test]
+fn distribution_uses_nearest_rank_percentiles() {
+    let mut samples: Vec<f64> = (1..=100).rev().map(f64::from).collect();
+    let d = Distribution::from_samples(&mut samples);
+    assert_eq!(d.mean, 50.5);
+    assert_eq!(
+        (d.min, d.p5, d.p50, d.p95, d.max),
+        (1.0, 5.0, 50.0, 95.0, 100.0)
+    );
+    assert_eq!(Distribution::from_samples(&mut []).p95, 0.0);
+}
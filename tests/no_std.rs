@@ -0,0 +1,14 @@
+use std::process::Command;
+
+#[test]
+fn core_math_builds_without_std() {
+    let output = Command::new("cargo")
+        .args(["check", "--lib", "--no-default-features"])
+        .output()
+        .expect("run cargo check");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
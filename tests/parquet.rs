@@ -0,0 +1,176 @@
+#![cfg(feature = "parquet")]
+
+use arrow_array::{Array, Float64Array, Int64Array, UInt32Array};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::fs::{self, File};
+use std::process::Command;
+
+fn bcurve(out: &str, format: &str) {
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--features",
+            "parquet",
+            "--",
+            "--theta",
+            "0.55",
+            "--bins",
+            "200000",
+            "--r0",
+            "100.0",
+            "--account-surcharge",
+            "--format",
+            format,
+            "--out-dir",
+            out,
+            "--no-draw",
+        ])
+        .status()
+        .expect("run bcurve");
+    assert!(status.success());
+}
+
+#[test]
+fn parquet_schedule_matches_the_csv() {
+    let out = "out_parquet_test";
+    bcurve(out, "parquet");
+    bcurve(out, "csv");
+
+    let file = File::open(format!("{out}/schedule.parquet")).unwrap();
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+    let kv = builder
+        .metadata()
+        .file_metadata()
+        .key_value_metadata()
+        .unwrap()
+        .clone();
+    let meta = |key: &str| {
+        kv.iter()
+            .find(|e| e.key == key)
+            .and_then(|e| e.value.clone())
+            .unwrap_or_else(|| panic!("no {key} metadata"))
+    };
+    assert_eq!(meta("mode"), "DLMM-Geometric(θ)");
+    assert_eq!(meta("bins"), "200000");
+    assert_eq!(meta("tau_start_pct"), "50.0");
+    assert!(meta("header_lines").contains("Growth factor g="));
+
+    let batches: Vec<_> = builder.build().unwrap().collect::<Result<_, _>>().unwrap();
+    let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+    assert_eq!(rows, 200_000);
+
+    let csv = fs::read_to_string(format!("{out}/schedule.csv")).unwrap();
+    let mut data = csv.lines().filter(|l| !l.starts_with('#') && !l.is_empty());
+    let header: Vec<&str> = data.next().unwrap().split(',').collect();
+    let schema = batches[0].schema();
+    let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+    assert_eq!(names, header);
+    let csv_rows: Vec<&str> = data.collect();
+
+    // First and last bins carry the same f64 bits as the CSV's shortest round-trip text
+    for (batch, index, line) in [
+        (&batches[0], 0, csv_rows[0]),
+        (
+            batches.last().unwrap(),
+            batches.last().unwrap().num_rows() - 1,
+            *csv_rows.last().unwrap(),
+        ),
+    ] {
+        let cells: Vec<&str> = line.split(',').collect();
+        let bin = batch
+            .column_by_name("bin")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap()
+            .value(index);
+        assert_eq!(bin.to_string(), cells[0]);
+        for (k, name) in header.iter().enumerate() {
+            if let Some(col) = batch
+                .column_by_name(name)
+                .unwrap()
+                .as_any()
+                .downcast_ref::<Float64Array>()
+            {
+                let csv_value: f64 = cells[k].parse().unwrap();
+                assert_eq!(col.value(index).to_bits(), csv_value.to_bits(), "{name}");
+                assert!(!col.is_null(index));
+            }
+        }
+    }
+    let _ = fs::remove_dir_all(out);
+}
+
+#[test]
+fn every_optional_column_has_a_parquet_mapping() {
+    let out = "out_parquet_columns_test";
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--features",
+            "parquet",
+            "--",
+            "--bins",
+            "50",
+            "--r0",
+            "100.0",
+            "--protocol-share",
+            "0.2",
+            "--invert-prices",
+            "--surcharge-at-secs",
+            "30",
+            "--account-surcharge",
+            "--log-columns",
+            "--bin-id-base",
+            "8388608",
+            "--format",
+            "parquet",
+            "--out-dir",
+            out,
+            "--no-draw",
+        ])
+        .status()
+        .expect("run bcurve");
+    assert!(status.success());
+
+    let file = File::open(format!("{out}/schedule.parquet")).unwrap();
+    let batches: Vec<_> = ParquetRecordBatchReaderBuilder::try_new(file)
+        .unwrap()
+        .build()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let batch = &batches[0];
+    let schema = batch.schema();
+    assert_eq!(schema.fields().len(), 20);
+    for (field, col) in schema.fields().iter().zip(batch.columns()) {
+        let any = col.as_any();
+        let values: Vec<f64> = if let Some(a) = any.downcast_ref::<Float64Array>() {
+            a.iter().flatten().collect()
+        } else if let Some(a) = any.downcast_ref::<Int64Array>() {
+            a.iter().flatten().map(|v| v as f64).collect()
+        } else {
+            let a = any.downcast_ref::<UInt32Array>().unwrap();
+            a.iter().flatten().map(f64::from).collect()
+        };
+        // Only nullable columns may leave cells empty, and no cell is NaN
+        assert!(
+            field.is_nullable() || col.null_count() == 0,
+            "{}",
+            field.name()
+        );
+        assert!(values.len() + col.null_count() == 50, "{}", field.name());
+        assert!(values.iter().all(|v| !v.is_nan()), "{}", field.name());
+    }
+    let bin_id = batch
+        .column_by_name("bin_id")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap()
+        .value(49);
+    assert_eq!(bin_id, 8_388_608 + 49);
+    let _ = fs::remove_dir_all(out);
+}
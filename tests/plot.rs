@@ -0,0 +1,171 @@
+use bcurve::curves::{sample_bins, Geometric, Grid, GridSpacing, TwoSided};
+use bcurve::dlmm::{depth_chart, depth_chart_sampled, DlmmFeeParams};
+use bcurve::plot::{
+    plot_fee_components, plot_price_vs_supply_with, plot_tokens_per_bin, PriceChartOptions,
+};
+use std::fs;
+
+fn geometric(p0: f64) -> Geometric {
+    Geometric {
+        grid: Grid {
+            p0,
+            bin_step_bps: 25.0,
+            spacing: GridSpacing::Price,
+        },
+        theta: 0.6,
+        r0_quote: 100.0,
+    }
+}
+
+#[test]
+fn log_price_chart_is_a_valid_png() {
+    let dir = "out_log_price_test";
+    fs::create_dir_all(dir).unwrap();
+    let log = PriceChartOptions {
+        log_price: true,
+        ..Default::default()
+    };
+    // 2000 bins at 25 bps span over four orders of magnitude
+    let path = format!("{dir}/price_vs_supply.png");
+    plot_price_vs_supply_with(&geometric(0.01), 2000, &path, &log).unwrap();
+    let png = fs::read(&path).unwrap();
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+    assert!(png.len() > 1000);
+
+    let path = format!("{dir}/inverse.svg");
+    let inverted = PriceChartOptions {
+        invert_price: true,
+        ..log
+    };
+    plot_price_vs_supply_with(&geometric(0.01), 2000, &path, &inverted).unwrap();
+    assert!(fs::read_to_string(&path)
+        .unwrap()
+        .contains("1/P (log scale)"));
+
+    // Non-positive prices have no logarithm
+    let path = format!("{dir}/negative.png");
+    let err = plot_price_vs_supply_with(&geometric(-0.01), 10, &path, &log).unwrap_err();
+    assert!(err.to_string().contains("prices > 0"), "{err}");
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn fee_chart_shows_cap_and_components() {
+    let dir = "out_fee_chart_test";
+    fs::create_dir_all(dir).unwrap();
+    let mut fees = DlmmFeeParams::new(1.0, 25.0, 5.0, 0.05).unwrap();
+    // Raw fee B·s + A·(va·s)² passes 5% near va = 39, inside the plotted range
+    assert!(fees.total_fee_rate(50.0) == 0.05);
+
+    let path = format!("{dir}/fee_vs_volatility.svg");
+    plot_fee_components(&fees, &path).unwrap();
+    let svg = fs::read_to_string(&path).unwrap();
+    for label in [
+        "base",
+        "variable",
+        "total (capped)",
+        "max_fee_rate = 0.05",
+        "capped",
+    ] {
+        assert!(svg.contains(label), "missing {label}");
+    }
+
+    // A cap the fee never reaches leaves nothing shaded
+    fees.max_fee_rate = 1.0;
+    plot_fee_components(&fees, &path).unwrap();
+    let svg = fs::read_to_string(&path).unwrap();
+    assert!(!svg.contains(">capped<"));
+
+    let path = format!("{dir}/fee_vs_volatility.png");
+    plot_fee_components(&fees, &path).unwrap();
+    assert!(fs::read(&path).unwrap().starts_with(b"\x89PNG"));
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn sample_bins_keeps_first_and_last_within_the_cap() {
+    // Full resolution while the range fits
+    assert_eq!(
+        sample_bins(-3..5, 8).collect::<Vec<_>>(),
+        (-3..5).collect::<Vec<_>>()
+    );
+    assert_eq!(sample_bins(0..0, 8).count(), 0);
+    for (n, max_points) in [(10_000_000_000, 1000), (10, 3), (101, 11), (7, 0)] {
+        let bins: Vec<i64> = sample_bins(0..n, max_points).collect();
+        assert!(
+            bins.len() <= max_points.max(2),
+            "{n} {max_points}: {}",
+            bins.len()
+        );
+        assert_eq!((bins[0], *bins.last().unwrap()), (0, n - 1));
+        assert!(bins.windows(2).all(|w| w[0] < w[1]));
+    }
+}
+
+/// Number of x,y pairs in the longest `<polyline>` of an SVG chart
+fn max_polyline_points(svg: &str) -> usize {
+    svg.split("<polyline")
+        .skip(1)
+        .map(|el| {
+            let points = el.split("points=\"").nth(1).unwrap();
+            points[..points.find('"').unwrap()]
+                .split_whitespace()
+                .count()
+        })
+        .max()
+        .unwrap()
+}
+
+#[test]
+fn huge_bin_count_plots_within_the_point_cap() {
+    let dir = "out_downsample_test";
+    fs::create_dir_all(dir).unwrap();
+    // A fine grid keeps 2,000,000 bins' prices finite
+    let mut c = geometric(1.0);
+    c.grid.bin_step_bps = 0.001;
+    let bins = 2_000_000;
+    let max_points = 500;
+
+    let path = format!("{dir}/price_vs_supply.svg");
+    let opts = PriceChartOptions {
+        max_points,
+        ..Default::default()
+    };
+    plot_price_vs_supply_with(&c, bins, &path, &opts).unwrap();
+    let n = max_polyline_points(&fs::read_to_string(&path).unwrap());
+    assert!(n > max_points && n <= 2 * max_points, "{n}");
+
+    let path = format!("{dir}/tokens_per_bin.svg");
+    plot_tokens_per_bin(&c, bins, max_points, &path).unwrap();
+    let n = max_polyline_points(&fs::read_to_string(&path).unwrap());
+    assert!(n > max_points && n <= 2 * max_points, "{n}");
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn sampled_depth_keeps_exact_points_within_the_cap() {
+    let two_sided = TwoSided {
+        core: geometric(0.01),
+        below_bins: 50,
+    };
+    let full = depth_chart(&two_sided, 0, 100);
+    let sampled = depth_chart_sampled(&two_sided, 0, 100, 20);
+    assert!(sampled.len() <= 20, "{}", sampled.len());
+    // Every kept point is a point of the full chart, ends included on both sides
+    assert!(sampled.iter().all(|p| full.contains(p)));
+    for p in [full[0], full[49], full[50], full[149]] {
+        assert!(sampled.contains(&p), "{p:?}");
+    }
+    assert_eq!(depth_chart_sampled(&two_sided, 0, 100, 1_000), full);
+
+    let mut c = geometric(1.0);
+    c.grid.bin_step_bps = 0.001;
+    let sampled = depth_chart_sampled(&c, 0, 2_000_000, 500);
+    assert_eq!(sampled.len(), 250);
+    assert!(sampled
+        .windows(2)
+        .all(|w| w[0].0 < w[1].0 && w[0].1 < w[1].1));
+}
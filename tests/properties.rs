@@ -1,8 +1,15 @@
 use approx::assert_relative_eq;
-use bcurve::curves::{Curve, Geometric, Grid, LogisticS};
+use bcurve::curves::{
+    CompensatedSum, Curve, Exponential, Geometric, Grid, GridSpacing, LogisticS, MaskedCurve,
+    TwoSided,
+};
 use bcurve::dlmm::DlmmFeeParams;
 use proptest::prelude::*;
 
+fn spacing() -> impl Strategy<Value = GridSpacing> {
+    prop_oneof![Just(GridSpacing::Price), Just(GridSpacing::SqrtPrice)]
+}
+
 proptest! {
     #[test]
     fn geometric_closed_form_matches_sum(
@@ -12,7 +19,7 @@ proptest! {
         r0 in 1e-6f64..1e6,
         n in 1i64..2000
     ) {
-        let grid = Grid { p0, bin_step_bps: step_bps };
+        let grid = Grid { p0, bin_step_bps: step_bps, spacing: GridSpacing::Price };
         let g = Geometric { grid, theta, r0_quote: r0 };
         let mut s_num = 0.0;
         for i in 0..n { s_num += g.delta_x_of_bin(i); }
@@ -26,20 +33,85 @@ proptest! {
         }
     }
 
+    #[test]
+    fn exponential_closed_form_matches_sum(
+        a in 1e-3f64..1e6,
+        b in prop_oneof![-1e-12f64..1e-12, -0.01f64..0.01],
+        n in 1i64..2000
+    ) {
+        let grid = Grid { p0: 0.01, bin_step_bps: 10.0, spacing: GridSpacing::Price };
+        let c = Exponential { grid, a, b };
+        let mut s_num = CompensatedSum::default();
+        for i in 0..n { s_num.add(c.delta_x_of_bin(i)); }
+        assert_relative_eq!(c.cumulative_supply(n), s_num.value(), max_relative = 1e-9);
+        let solved = Exponential { a: c.solve_a_from_supply(1e8, n), ..c };
+        assert_relative_eq!(solved.cumulative_supply(n), 1e8, max_relative = 1e-12);
+        prop_assert_eq!(c.price_of_bin(n), grid.price_of_bin(n));
+    }
+
+    #[test]
+    fn masked_curve_drops_exactly_excluded_supply(
+        theta in 0.1f64..0.99,
+        r0 in 1e-3f64..1e6,
+        n in 2i64..2000,
+        a in 0i64..2000,
+        len in 0i64..500,
+    ) {
+        let grid = Grid { p0: 0.01, bin_step_bps: 10.0, spacing: GridSpacing::Price };
+        let g = Geometric { grid, theta, r0_quote: r0 };
+        let masked = MaskedCurve { inner: g, excluded: a..a + len };
+        let removed: f64 = (a..(a + len).min(n)).map(|i| g.delta_x_of_bin(i)).sum();
+        // Absolute slack scales with the unmasked total, which can cancel to ~0
+        let total = g.cumulative_supply(n);
+        assert_relative_eq!(
+            masked.cumulative_supply(n),
+            total - removed,
+            max_relative = 1e-9,
+            epsilon = 1e-12 * total
+        );
+        for i in 0..n {
+            prop_assert_eq!(masked.price_of_bin(i), g.price_of_bin(i));
+        }
+    }
+
+    #[test]
+    fn curve_configs_round_trip_through_json(
+        p0 in 1e-6f64..1e1,
+        step_bps in 1.0f64..100.0,
+        theta in -2.0f64..2.0,
+        r0 in 1e-6f64..1e6,
+        k in 1e-10f64..1e-5,
+        n in 2i64..2000
+    ) {
+        let grid = Grid { p0, bin_step_bps: step_bps, spacing: GridSpacing::Price };
+        let g = Geometric { grid, theta, r0_quote: r0 };
+        let back: Geometric = serde_json::from_str(&serde_json::to_string(&g).unwrap()).unwrap();
+        prop_assert_eq!(back, g);
+        let l = LogisticS { grid, p_min: p0 / 2.0, p_max: p0 * 2.0, k, s_mid: 0.0, bins: n };
+        let back: LogisticS = serde_json::from_str(&serde_json::to_string(&l).unwrap()).unwrap();
+        prop_assert_eq!(back, l);
+    }
+
     #[test]
     fn fees_are_monotone_in_va(
         step_bps in 1.0f64..100.0,
         base in 0.0f64..1.0,
         varc in 0.0f64..1.0,
         cap in 0.001f64..0.50,  // 0.1%..50% cap
+        floor_frac in 0.0f64..=1.0,
         va1 in 0.0f64..50.0,
         va2 in 0.0f64..50.0,
     ) {
+        let floor = cap * floor_frac;
         let f = DlmmFeeParams {
             base_factor: base,
             bin_step_bps: step_bps,
             variable_fee_control: varc,
             max_fee_rate: cap,
+            min_fee_rate: floor,
+            cap_softness: 0.0,
+            distance_fee_delta: 0.0,
+            protocol_share: 0.0,
         };
         let t1 = f.total_fee_rate(va1);
         let t2 = f.total_fee_rate(va2);
@@ -54,6 +126,9 @@ proptest! {
         // total fee never exceeds cap
         prop_assert!(t1 <= cap + 1e-15);
         prop_assert!(t2 <= cap + 1e-15);
+        // ...nor drops below the floor
+        prop_assert!(t1 >= floor);
+        prop_assert!(t2 >= floor);
     }
 
     #[test]
@@ -66,7 +141,7 @@ proptest! {
         n in 2i64..2000
     ) {
         prop_assume!(pmin < p0 && p0 < pmax);
-        let grid = Grid { p0, bin_step_bps: step_bps };
+        let grid = Grid { p0, bin_step_bps: step_bps, spacing: GridSpacing::Price };
         // choose s_mid so S(P0)=0
         let s_mid = ((pmax - p0)/(p0 - pmin)).ln() / k;
         let cur = LogisticS { grid, p_min: pmin, p_max: pmax, k, s_mid, bins: n };
@@ -76,6 +151,30 @@ proptest! {
         }
     }
 
+    #[test]
+    fn logistic_inflection_sits_at_mid_price(
+        pmin in 0.0f64..1e-3,
+        pmax in 1e-2f64..1e1,
+        k in 1e-10f64..1e-5,
+        s_mid in -1e9f64..1e9,
+    ) {
+        let grid = Grid { p0: 5e-3, bin_step_bps: 10.0, spacing: GridSpacing::Price };
+        let cur = LogisticS { grid, p_min: pmin, p_max: pmax, k, s_mid, bins: 10 };
+        let (s, p) = cur.inflection();
+        prop_assert_eq!(s, s_mid);
+        assert_relative_eq!(p, 0.5 * (pmin + pmax), max_relative = 1e-12);
+    }
+
+    #[test]
+    fn bins_per_decade_spans_ten_x(
+        p0 in 1e-6f64..1e1,
+        n in 1u32..2000,
+    ) {
+        let grid = Grid::from_bins_per_decade(p0, n as f64);
+        let i = n as i64;
+        assert_relative_eq!(grid.price_of_bin(i) / grid.price_of_bin(0), 10.0, max_relative = 1e-9);
+    }
+
     #[test]
     fn bin_inversion_respects_end_price(
         p0 in 1e-6f64..1e1,
@@ -89,4 +188,217 @@ proptest! {
         let p_n = p0 * q.powi(n as i32);
         prop_assert!(p_n + 1e-15 >= p_end, "p_n={} < p_end={}", p_n, p_end);
     }
+
+    #[test]
+    fn two_sided_mirrors_upside(
+        theta in 0.1f64..0.95,
+        step_bps in 1.0f64..100.0,
+        below in 0i64..200,
+        above in 1i64..200,
+    ) {
+        let core = Geometric { grid: Grid { p0: 0.01, bin_step_bps: step_bps, spacing: GridSpacing::Price }, theta, r0_quote: 100.0 };
+        let c = TwoSided { core, below_bins: below };
+        prop_assert_eq!(c.first_bin(), -below);
+        prop_assert!(c.price_of_bin(-1) < c.price_of_bin(0));
+        let lower: f64 = (-below..0).map(|i| c.delta_x_of_bin(i)).sum();
+        let upper: f64 = (0..above).map(|i| c.delta_x_of_bin(i)).sum();
+        assert_relative_eq!(lower, c.supply_below(), max_relative = 1e-9);
+        assert_relative_eq!(upper, c.supply_above(above), max_relative = 1e-9);
+        prop_assert_eq!(c.delta_x_of_bin(-below - 1), 0.0);
+    }
+
+    #[test]
+    fn bins_for_price_range_is_first_bin_at_target(
+        p0 in 1e-6f64..1e1,
+        step_bps in 1.0f64..100.0,
+        ratio in 1.0f64..1e3,
+        spacing in spacing(),
+    ) {
+        let grid = Grid { p0, bin_step_bps: step_bps, spacing };
+        let target = p0 * ratio;
+        let n = grid.bins_for_price_range(target);
+        prop_assert!(grid.price_of_bin(n) >= target);
+        prop_assert!(n == 0 || grid.price_of_bin(n - 1) < target);
+    }
+
+    #[test]
+    fn bin_of_price_inverts_price_of_bin(
+        p0 in 1e-6f64..1e1,
+        step_bps in 1.0f64..100.0,
+        i in -5_000i64..5_000,
+        frac in 0.0f64..1.0,
+        spacing in spacing(),
+    ) {
+        let grid = Grid { p0, bin_step_bps: step_bps, spacing };
+        prop_assert_eq!(grid.bin_of_price(grid.price_of_bin(i)).unwrap(), i);
+        // Anywhere inside the bin maps back to it
+        let inside = grid.price_of_bin(i) * (1.0 + frac * (grid.q() - 1.0));
+        if inside < grid.price_of_bin(i + 1) {
+            prop_assert_eq!(grid.bin_of_price(inside).unwrap(), i);
+        }
+    }
+
+    #[test]
+    fn soft_cap_is_continuous_and_monotone(
+        step_bps in 1.0f64..100.0,
+        base in 0.0f64..1.0,
+        varc in 0.0f64..10.0,
+        cap in 0.001f64..0.50,
+        softness in 0.01f64..0.99,
+        va in 0.0f64..200.0,
+    ) {
+        let f = DlmmFeeParams {
+            base_factor: base,
+            bin_step_bps: step_bps,
+            variable_fee_control: varc,
+            max_fee_rate: cap,
+            min_fee_rate: 0.0,
+            cap_softness: softness,
+            distance_fee_delta: 0.0,
+            protocol_share: 0.0,
+        };
+        let dva = 1e-6;
+        let (t0, t1) = (f.total_fee_rate(va), f.total_fee_rate(va + dva));
+        let raw_jump = f.variable_fee_rate(va + dva) - f.variable_fee_rate(va);
+        // Never jumps by more than the raw fee moved
+        prop_assert!(t1 - t0 <= raw_jump + 1e-15, "t0={} t1={} raw_jump={}", t0, t1, raw_jump);
+        prop_assert!(t1 >= t0);
+        prop_assert!(t1 <= cap + 1e-15);
+    }
+
+    #[test]
+    fn protocol_and_lp_fees_split_the_capped_total_exactly(
+        step_bps in 1.0f64..100.0,
+        base in 0.0f64..1.0,
+        varc in 0.0f64..10.0,
+        cap in 0.001f64..0.50,
+        softness in 0.0f64..0.99,
+        share in 0.0f64..=1.0,
+        va in 0.0f64..200.0,
+    ) {
+        let f = DlmmFeeParams {
+            base_factor: base,
+            bin_step_bps: step_bps,
+            variable_fee_control: varc,
+            max_fee_rate: cap,
+            min_fee_rate: 0.0,
+            cap_softness: softness,
+            distance_fee_delta: 0.0,
+            protocol_share: share,
+        };
+        let total = f.total_fee_rate(va);
+        let (protocol, lp) = (f.protocol_fee_rate(va), f.lp_fee_rate(va));
+        prop_assert_eq!(protocol + lp, total);
+        prop_assert!(protocol >= 0.0 && lp >= 0.0);
+        // Split after the cap: the protocol part is share·(capped total), not share·raw
+        prop_assert!((protocol - share * total).abs() <= 1e-15 * total.max(1.0));
+        prop_assert!(protocol <= cap * share + 1e-15);
+    }
+
+    #[test]
+    fn anchored_grid_passes_through_anchor(
+        price in 1e-6f64..1e3,
+        step_bps in 1.0f64..100.0,
+        bin in -2000i64..2000,
+        spacing in spacing(),
+    ) {
+        let grid = Grid { p0: 1.0, bin_step_bps: step_bps, spacing }.anchored_at(price, bin);
+        assert_relative_eq!(grid.price_of_bin(bin), price, max_relative = 1e-12);
+        prop_assert_eq!(grid.bin_step_bps, step_bps);
+        prop_assert_eq!(grid.spacing, spacing);
+    }
+
+    #[test]
+    fn price_guards_bracket_spot(
+        spot in 1e-9f64..1e9,
+        impact_bps in 0.0f64..9999.0,
+    ) {
+        let lo = DlmmFeeParams::min_price_sell_y_for_x(spot, impact_bps);
+        let hi = DlmmFeeParams::min_price_sell_x_for_y(spot, impact_bps);
+        prop_assert!(lo <= spot && spot <= hi, "{} <= {} <= {}", lo, spot, hi);
+        prop_assert!(lo >= 0.0 && hi.is_finite());
+        // Both bounds collapse onto spot as the impact vanishes, linearly in it
+        let tiny = impact_bps * 1e-9;
+        let lo = DlmmFeeParams::min_price_sell_y_for_x(spot, tiny);
+        let hi = DlmmFeeParams::min_price_sell_x_for_y(spot, tiny);
+        let slack = spot * (tiny / 10_000.0) * (1.0 + 1e-6) + spot * f64::EPSILON;
+        prop_assert!(spot - lo <= slack && hi - spot <= slack);
+    }
+}
+
+#[test]
+fn price_guards_at_full_impact_diverge_safely() {
+    let spot = 0.25;
+    assert_eq!(DlmmFeeParams::min_price_sell_x_for_y(spot, 0.0), spot);
+    assert_eq!(DlmmFeeParams::min_price_sell_y_for_x(spot, 0.0), spot);
+    let mut prev = spot;
+    for impact_bps in [9_000.0, 9_999.0, 9_999.999, 10_000.0 - 1e-9] {
+        let hi = DlmmFeeParams::min_price_sell_x_for_y(spot, impact_bps);
+        assert!(hi.is_finite() && hi > prev, "impact {impact_bps}: {hi}");
+        prev = hi;
+    }
+    assert!(prev > spot * 1e12);
+    // Exactly 10,000 bps divides by zero: +∞, not NaN or a panic
+    assert_eq!(
+        DlmmFeeParams::min_price_sell_x_for_y(spot, 10_000.0),
+        f64::INFINITY
+    );
+    assert_eq!(DlmmFeeParams::min_price_sell_y_for_x(spot, 10_000.0), 0.0);
+}
+
+/// Correctly rounded Σ `xs` (Shewchuk's exact partials, as in Python's `math.fsum`)
+fn exact_sum(xs: impl IntoIterator<Item = f64>) -> f64 {
+    let mut partials: Vec<f64> = Vec::new();
+    for mut x in xs {
+        let mut kept = 0;
+        for j in 0..partials.len() {
+            let mut y = partials[j];
+            if x.abs() < y.abs() {
+                std::mem::swap(&mut x, &mut y);
+            }
+            let hi = x + y;
+            let lo = y - (hi - x);
+            if lo != 0.0 {
+                partials[kept] = lo;
+                kept += 1;
+            }
+            x = hi;
+        }
+        partials.truncate(kept);
+        partials.push(x);
+    }
+    partials.iter().rev().fold(0.0, |acc, &p| acc + p)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn default_cumulative_supply_matches_compensated_schedule_total(
+        step_bps in 0.05f64..0.5,
+        k in 1e-7f64..1e-5,
+        s_mid in 1e5f64..1e7,
+        n in 20_000i64..200_000,
+    ) {
+        let c = LogisticS {
+            grid: Grid { p0: 0.01, bin_step_bps: step_bps, spacing: GridSpacing::Price },
+            p_min: 0.005,
+            p_max: 1e3,
+            k,
+            s_mid,
+            bins: n,
+        };
+        // schedule.csv accumulates supply_cum with the same compensated sum
+        let mut csv_total = CompensatedSum::default();
+        for i in 0..n {
+            csv_total.add(c.delta_x_of_bin(i));
+        }
+        let default = c.cumulative_supply(n);
+        prop_assert_eq!(default, csv_total.value());
+        let exact = exact_sum((0..n).map(|i| c.delta_x_of_bin(i)));
+        prop_assert!(
+            (default - exact).abs() <= 2.0 * f64::EPSILON * exact.abs(),
+            "n={}: default {} vs exact {}", n, default, exact
+        );
+    }
 }
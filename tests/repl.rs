@@ -0,0 +1,57 @@
+use bcurve::curves::{Curve, Geometric, Grid, GridSpacing};
+use bcurve::repl::run_repl;
+
+fn session(input: &str) -> Vec<String> {
+    let c = Geometric {
+        grid: Grid {
+            p0: 0.01,
+            bin_step_bps: 25.0,
+            spacing: GridSpacing::Price,
+        },
+        theta: 0.6,
+        r0_quote: 100.0,
+    };
+    let mut out = Vec::new();
+    run_repl(&c, 500, input.as_bytes(), &mut out).unwrap();
+    String::from_utf8(out)
+        .unwrap()
+        .lines()
+        .map(str::to_owned)
+        .collect()
+}
+
+#[test]
+fn repl_answers_queries_and_survives_bad_input() {
+    let c = Geometric {
+        grid: Grid {
+            p0: 0.01,
+            bin_step_bps: 25.0,
+            spacing: GridSpacing::Price,
+        },
+        theta: 0.6,
+        r0_quote: 100.0,
+    };
+    let lines = session("price 42\nbin-of 0.0111\nnonsense\nprice x\n\nsupply 10\n");
+    assert_eq!(lines.len(), 5);
+    assert_eq!(lines[0], format!("P_42 = {:.12}", c.price_of_bin(42)));
+    let i: i64 = lines[1]
+        .strip_prefix("bin ")
+        .and_then(|r| r.split(' ').next())
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(c.price_of_bin(i) <= 0.0111 && c.price_of_bin(i + 1) > 0.0111);
+    assert!(lines[2].starts_with("commands:"));
+    assert!(lines[3].starts_with("bad bin"));
+    assert_eq!(lines[4], format!("S_10 = {:.6}", c.cumulative_supply(10)));
+}
+
+#[test]
+fn repl_buys_advance_the_active_bin() {
+    // Bin 0 costs ΔX_0 · P_0 = r0_quote = 100 quote
+    let lines = session("buy 50\nbuy 50\nbuy 0\nquit\nprice 1\n");
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].ends_with("active_bin=0"));
+    assert!(lines[1].ends_with("active_bin=1"));
+    assert!(lines[2].starts_with("tokens=0.000000"));
+}
@@ -0,0 +1,100 @@
+use bcurve::curves::{Curve, Geometric, Grid, GridSpacing, TwoSided};
+use bcurve::dlmm::{DlmmFeeParams, LaunchPhasePolicy, TauDecay};
+use bcurve::sim::{execute_buy, simulate_trades, BuyCursor, TradeEvent};
+
+fn curve() -> Geometric {
+    Geometric {
+        grid: Grid {
+            p0: 0.01,
+            bin_step_bps: 25.0,
+            spacing: GridSpacing::Price,
+        },
+        theta: 0.6,
+        r0_quote: 100.0,
+    }
+}
+
+#[test]
+fn execute_buy_walks_bins_and_refunds_past_the_end() {
+    let c = curve();
+    let mut cursor = BuyCursor::default();
+    // Bin 0 holds exactly r0_quote = 100 quote of tokens
+    let fill = execute_buy(&c, 3, &mut cursor, 150.0);
+    assert_eq!(cursor.bin, 1);
+    assert!((fill.tokens - (c.delta_x_of_bin(0) + 50.0 / c.price_of_bin(1))).abs() < 1e-9);
+    assert_eq!(fill.quote_unspent, 0.0);
+
+    let fill = execute_buy(&c, 3, &mut cursor, 1e9);
+    assert_eq!(cursor.bin, 3);
+    assert!(fill.quote_unspent > 0.0);
+    assert!((fill.quote_spent + fill.quote_unspent - 1e9).abs() < 1e-3);
+}
+
+#[test]
+fn simulate_trades_applies_surcharge_fee_and_allowlist() {
+    let c = curve();
+    let fees = DlmmFeeParams {
+        base_factor: 1.0,
+        bin_step_bps: 25.0,
+        variable_fee_control: 0.0,
+        max_fee_rate: 0.10,
+        min_fee_rate: 0.0,
+        cap_softness: 0.0,
+        distance_fee_delta: 0.0,
+        protocol_share: 0.0,
+    };
+    let policy = LaunchPhasePolicy {
+        allowlist: ["team"].into_iter().collect(),
+        tau_start_pct: 50.0,
+        tau_end_pct: 0.0,
+        ramp_secs: 100.0,
+        tau_decay: TauDecay::Linear,
+    };
+    let trade = |timestamp: f64, address: &str| TradeEvent {
+        timestamp,
+        address: address.to_string(),
+        quote_in: 100.0,
+    };
+    let out = simulate_trades(
+        &c,
+        500,
+        &fees,
+        0.0,
+        &policy,
+        &[trade(0.0, "team"), trade(0.0, "bot"), trade(50.0, "bot")],
+    );
+    assert_eq!(out[0].surcharge_pct, 0.0);
+    assert_eq!(out[1].surcharge_pct, 50.0);
+    assert_eq!(out[2].surcharge_pct, 25.0);
+    assert_eq!(out[1].surcharge_paid, 50.0);
+    // 0.25% base fee on what remains after the surcharge
+    assert!((out[1].fee_paid - 50.0 * 0.0025).abs() < 1e-12);
+    assert!(out[1].tokens_out < out[0].tokens_out);
+    assert!(out.windows(2).all(|w| w[0].active_bin <= w[1].active_bin));
+}
+
+#[test]
+fn simulate_trades_starts_at_the_first_bin() {
+    let c = TwoSided {
+        core: curve(),
+        below_bins: 5,
+    };
+    let fees = DlmmFeeParams {
+        base_factor: 0.0,
+        bin_step_bps: 25.0,
+        variable_fee_control: 0.0,
+        max_fee_rate: 0.10,
+        min_fee_rate: 0.0,
+        cap_softness: 0.0,
+        distance_fee_delta: 0.0,
+        protocol_share: 0.0,
+    };
+    let trade = TradeEvent {
+        timestamp: 0.0,
+        address: "bot".to_string(),
+        quote_in: 1.0,
+    };
+    let out = simulate_trades(&c, 10, &fees, 0.0, &LaunchPhasePolicy::default(), &[trade]);
+    assert_eq!(out[0].active_bin, -5);
+    assert!((out[0].tokens_out - 1.0 / c.price_of_bin(-5)).abs() < 1e-9);
+}
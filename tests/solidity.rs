@@ -0,0 +1,67 @@
+use bcurve::curves::{Curve, Geometric, Grid, GridSpacing, TwoSided};
+use bcurve::solidity::{to_fixed_point, write_solidity, SolidityOptions, CONTRACT_SIZE_LIMIT};
+
+#[test]
+fn fixed_point_literals_are_exact_integers() {
+    assert_eq!(to_fixed_point(0.01, 18).unwrap(), "10000000000000000");
+    assert_eq!(to_fixed_point(1.5, 6).unwrap(), "1500000");
+    assert_eq!(
+        to_fixed_point(1e9, 18).unwrap(),
+        "1000000000000000000000000000"
+    );
+    assert_eq!(to_fixed_point(0.0, 18).unwrap(), "0");
+    assert!(to_fixed_point(-1.0, 18).is_err());
+    assert!(to_fixed_point(f64::NAN, 18).is_err());
+    assert!(to_fixed_point(1e60, 18).is_err());
+}
+
+#[test]
+fn schedule_is_chunked_into_libraries_with_grid_constants() {
+    let geo = Geometric {
+        grid: Grid {
+            p0: 0.01,
+            bin_step_bps: 25.0,
+            spacing: GridSpacing::Price,
+        },
+        theta: 0.6,
+        r0_quote: 100.0,
+    };
+    let c = TwoSided {
+        core: geo,
+        below_bins: 5,
+    };
+    let opts = SolidityOptions {
+        decimals: 18,
+        chunk_bins: 40,
+    };
+    let mut out = Vec::new();
+    let report = write_solidity(&mut out, &c, 95, 25.0, &opts).unwrap();
+    let src = String::from_utf8(out).unwrap();
+
+    assert_eq!(report.chunks, vec![(-5, 35), (35, 75), (75, 95)]);
+    assert!(report.chunk_bytes.iter().all(|&b| b < CONTRACT_SIZE_LIMIT));
+    assert!((report.total_supply / (c.supply_below() + c.supply_above(95)) - 1.0).abs() < 1e-12);
+
+    assert!(src.contains("uint256 internal constant P0 = 10000000000000000;"));
+    assert!(src.contains("uint256 internal constant BIN_STEP_BPS = 25000000000000000000;"));
+    assert!(src.contains("int256 internal constant FIRST_BIN = -5;"));
+    assert!(src.contains("uint256 internal constant BIN_COUNT = 100;"));
+    assert!(src.contains("library ScheduleChunk2 {"));
+    assert!(!src.contains("ScheduleChunk3"));
+    assert!(src.contains("function prices() public pure returns (uint256[20] memory)"));
+    // Arrays start at the first mirrored bin
+    let first_allocation = to_fixed_point(c.delta_x_of_bin(-5), 18).unwrap();
+    assert!(src.contains(&format!("uint256({})", first_allocation)));
+    assert_eq!(src.matches("        return [").count(), 6);
+}
+
+#[test]
+fn fixed_point_rounds_half_up_on_the_decimal_digits() {
+    assert_eq!(to_fixed_point(0.0125, 3).unwrap(), "13");
+    assert_eq!(to_fixed_point(0.0124, 3).unwrap(), "12");
+    assert_eq!(to_fixed_point(0.9996, 3).unwrap(), "1000");
+    assert_eq!(to_fixed_point(0.0004, 3).unwrap(), "0");
+    assert_eq!(to_fixed_point(0.0005, 3).unwrap(), "1");
+    assert_eq!(to_fixed_point(123.456, 0).unwrap(), "123");
+    assert_eq!(to_fixed_point(1.15e59, 18).unwrap().len(), 78);
+}
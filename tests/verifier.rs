@@ -0,0 +1,484 @@
+use bcurve::curves::{
+    AdaptiveLogistic, Curve, Geometric, Grid, GridSpacing, Linear, LogisticS, TwoSided,
+};
+use bcurve::verifier::{
+    accuracy_sweep, allocation_ratio, allocation_stats, binned_integration_error,
+    check_allocation_shape, check_asymptote, check_monotone, check_revenue_monotone,
+    count_dust_bins, min_price_gap, reconcile_schedule, verify_curve, verify_geometric,
+    verify_linear, verify_logistic, AllocationShape, Welford, DEFAULT_MAX_ALLOCATION_RATIO,
+};
+
+fn geometric(p0: f64, bin_step_bps: f64) -> Geometric {
+    Geometric {
+        grid: Grid {
+            p0,
+            bin_step_bps,
+            spacing: GridSpacing::Price,
+        },
+        theta: 0.6,
+        r0_quote: 100.0,
+    }
+}
+
+#[test]
+fn min_price_gap_separates_fine_from_colliding_grids() {
+    // 0.001 bps at a large price is fine: a relative step of 1e-7 is ~4.5e8 ULP
+    let fine = min_price_gap(&geometric(1e9, 0.001), 1000).unwrap();
+    assert!(fine.gap > 0.0);
+    assert!(!fine.is_degenerate(), "{fine:?}");
+
+    // Below f64 resolution q rounds to 1.0 and adjacent prices collide
+    let collided = min_price_gap(&geometric(1e9, 1e-13), 1000).unwrap();
+    assert!(collided.is_degenerate(), "{collided:?}");
+    assert_eq!(collided.gap, 0.0);
+}
+
+#[test]
+fn min_price_gap_needs_two_bins() {
+    assert!(min_price_gap(&geometric(0.01, 10.0), 1).is_none());
+}
+
+#[test]
+fn gap_and_dust_scans_cover_bins_below_p0() {
+    let c = TwoSided {
+        core: geometric(0.01, 10.0),
+        below_bins: 3,
+    };
+    // One bin above P_0 plus three below still has adjacent prices to compare
+    let gap = min_price_gap(&c, 1).unwrap();
+    assert!((-3..0).contains(&gap.bin));
+    assert_eq!(count_dust_bins(&c, 1, f64::INFINITY), 4);
+    assert_eq!(count_dust_bins(&c, 0, f64::INFINITY), 3);
+}
+
+#[test]
+fn count_dust_bins_flags_logistic_tail() {
+    let grid = Grid {
+        p0: 0.0015,
+        bin_step_bps: 10.0,
+        spacing: GridSpacing::Price,
+    };
+    let p_max = 0.05;
+    let k = 8e-8;
+    let s_mid = ((p_max - grid.p0) / (grid.p0 - 0.001)).ln() / k;
+    let cur = LogisticS {
+        grid,
+        p_min: 0.001,
+        p_max,
+        k,
+        s_mid,
+        bins: 5000,
+    };
+    // Past p_max the logistic clamps and the tail allocates nothing
+    let dust = count_dust_bins(&cur, 5000, 1.0);
+    assert!(dust > 0);
+    assert!(cur.delta_x_of_bin(4998) < 1.0);
+    assert_eq!(count_dust_bins(&cur, 5000, 0.0), 0);
+
+    let geo = geometric(0.01, 10.0);
+    assert_eq!(count_dust_bins(&geo, 100, 1e-6), 0);
+    assert_eq!(count_dust_bins(&geo, 100, f64::INFINITY), 100);
+}
+
+#[test]
+fn reconcile_schedule_catches_corrupted_totals() {
+    let c = geometric(0.01, 25.0);
+    let dir = std::env::temp_dir().join(format!("bcurve_reconcile_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("schedule.csv");
+    let path_str = path.to_str().unwrap();
+
+    let mut body = String::from("# metadata\n\nbin,price,delta_x,revenue_cum\n");
+    let mut r = 0.0;
+    for i in 0..3 {
+        r += c.price_of_bin(i) * c.delta_x_of_bin(i);
+        body += &format!(
+            "{},{},{},{}\n",
+            i,
+            c.price_of_bin(i),
+            c.delta_x_of_bin(i),
+            r
+        );
+    }
+    std::fs::write(&path, &body).unwrap();
+    assert!(reconcile_schedule(&c, 3, path_str).unwrap() < 1e-12);
+    assert!(reconcile_schedule(&c, 4, path_str).is_err());
+
+    // Simulate precision loss in the written total
+    std::fs::write(&path, body.replace(&r.to_string(), &format!("{:.4}", r))).unwrap();
+    assert!(reconcile_schedule(&c, 3, path_str).is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn accuracy_sweep_error_shrinks_with_finer_bins() {
+    let c = LogisticS {
+        grid: Grid {
+            p0: 0.01,
+            bin_step_bps: 25.0,
+            spacing: GridSpacing::Price,
+        },
+        p_min: 0.005,
+        p_max: 0.05,
+        k: 1e-5,
+        s_mid: 2e5,
+        bins: 400,
+    };
+    let sweep = accuracy_sweep(&c, 400, &[1.0, 10.0, 100.0]);
+    assert_eq!(sweep.len(), 3);
+    assert!(
+        sweep[0].1 < sweep[1].1 && sweep[1].1 < sweep[2].1,
+        "{sweep:?}"
+    );
+    // Left Riemann sum error is O(step): 1 bp ≈ 0.005% of revenue
+    assert!(sweep[0].1 < 1e-3, "{sweep:?}");
+}
+
+#[test]
+fn colliding_prices_are_non_strict_not_decreasing() {
+    // q = 1 + 1e-16 rounds to 1.0: every price equals P_0
+    let flat = geometric(1e10, 1e-12);
+    let rep = verify_geometric(&flat, 50).unwrap();
+    assert!(rep.monotone_ok);
+    assert_eq!(rep.non_strict_steps, 49);
+
+    let fine = verify_geometric(&geometric(0.01, 25.0), 50).unwrap();
+    assert!(fine.monotone_ok);
+    assert_eq!(fine.non_strict_steps, 0);
+
+    // A genuinely decreasing grid still fails
+    let falling = geometric(0.01, -25.0);
+    assert_eq!(check_monotone(&falling, 50), (false, 0));
+}
+
+#[test]
+fn revenue_monotonicity_catches_negative_prices() {
+    let ok = verify_geometric(&geometric(0.01, 25.0), 50).unwrap();
+    assert!(ok.revenue_monotone_ok);
+
+    // Negative p0 and R_0 keep ΔX positive but make every P_i·ΔX_i negative
+    let mut bad = geometric(-0.01, 25.0);
+    bad.r0_quote = -100.0;
+    let rep = verify_geometric(&bad, 50).unwrap();
+    assert!(!rep.revenue_monotone_ok);
+    assert!(!check_revenue_monotone(&bad, 50));
+}
+
+#[test]
+fn adaptive_bins_beat_the_uniform_grid_over_the_same_range() {
+    let n = 200;
+    let (p_start, p_end) = (0.01, 0.049);
+    let uniform = LogisticS {
+        grid: Grid {
+            p0: p_start,
+            bin_step_bps: ((p_end / p_start).powf(1.0 / (n - 1) as f64) - 1.0) * 10_000.0,
+            spacing: GridSpacing::Price,
+        },
+        p_min: 0.005,
+        p_max: 0.05,
+        k: 1e-5,
+        s_mid: 2e5,
+        bins: n,
+    };
+    let adaptive = AdaptiveLogistic::new(uniform, p_start, p_end, n).unwrap();
+    assert_eq!(adaptive.price_of_bin(0), p_start);
+    assert_eq!(adaptive.price_of_bin(n - 1), p_end);
+    assert_eq!(adaptive.delta_x_of_bin(n - 1), 0.0);
+    assert_eq!(check_monotone(&adaptive, n), (true, 0));
+    // Both ladders telescope to the same supply span
+    let span = |c: &dyn Curve| (0..n).map(|i| c.delta_x_of_bin(i)).sum::<f64>();
+    assert!((span(&adaptive) / span(&uniform) - 1.0).abs() < 1e-9);
+
+    let err_adaptive = binned_integration_error(&uniform, &adaptive, n);
+    let err_uniform = binned_integration_error(&uniform, &uniform, n);
+    assert!(
+        err_adaptive < err_uniform,
+        "{err_adaptive} vs {err_uniform}"
+    );
+
+    assert!(AdaptiveLogistic::new(uniform, p_start, 0.06, n).is_err());
+    assert!(AdaptiveLogistic::new(uniform, p_start, p_end, 1).is_err());
+}
+
+#[test]
+fn adaptive_ladder_rejects_bins_outside_it() {
+    let target = LogisticS {
+        grid: Grid::new(0.01, 10.0),
+        p_min: 0.005,
+        p_max: 0.05,
+        k: 1e-5,
+        s_mid: 2e5,
+        bins: 10,
+    };
+    let adaptive = AdaptiveLogistic::new(target, 0.01, 0.04, 10).unwrap();
+    assert_eq!(adaptive.checked_price_of_bin(9).unwrap(), 0.04);
+    for i in [-1, 10, i64::MIN] {
+        let err = adaptive.checked_price_of_bin(i).unwrap_err().to_string();
+        assert!(err.contains("outside the ladder [0, 10)"), "{err}");
+        assert!(adaptive.price_of_bin(i).is_nan());
+    }
+}
+
+#[test]
+fn welford_allocation_stats_match_a_two_pass_computation() {
+    let c = Geometric {
+        grid: Grid {
+            p0: 0.01,
+            bin_step_bps: 25.0,
+            spacing: GridSpacing::Price,
+        },
+        theta: 0.6,
+        r0_quote: 100.0,
+    };
+    let bins = 5_000;
+    let xs: Vec<f64> = (0..bins).map(|i| c.delta_x_of_bin(i)).collect();
+    let mean = xs.iter().sum::<f64>() / xs.len() as f64;
+    let var = xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / xs.len() as f64;
+
+    let stats = allocation_stats(&c, bins);
+    assert!((stats.mean / mean - 1.0).abs() < 1e-12);
+    assert!((stats.variance / var - 1.0).abs() < 1e-9);
+    assert!((stats.cv.unwrap() - var.sqrt() / mean).abs() < 1e-9);
+    assert_eq!(verify_geometric(&c, bins).unwrap().allocation, stats);
+
+    // A flat allocation has no spread; an empty one has no CV
+    let mut w = Welford::default();
+    for _ in 0..10 {
+        w.push(3.0);
+    }
+    assert_eq!(
+        (w.count(), w.mean(), w.variance(), w.cv()),
+        (10, 3.0, 0.0, Some(0.0))
+    );
+    assert_eq!(Welford::default().cv(), None);
+
+    // Large offsets do not cancel the way Σx² - n·mean² would
+    let mut w = Welford::default();
+    for x in [1e9 + 4.0, 1e9 + 7.0, 1e9 + 13.0, 1e9 + 16.0] {
+        w.push(x);
+    }
+    assert!((w.variance() - 22.5).abs() < 1e-6);
+}
+
+#[test]
+fn logistic_tail_approaches_p_max_unless_the_grid_overshoots() {
+    let logistic = |bins: i64| LogisticS {
+        grid: Grid {
+            p0: 0.01,
+            bin_step_bps: 100.0,
+            spacing: GridSpacing::Price,
+        },
+        p_min: 0.005,
+        p_max: 0.02,
+        k: 1e-5,
+        s_mid: 1e5,
+        bins,
+    };
+    // 1.01^69 ≈ 1.987 stays under p_max / p0 = 2; 1.01^70 ≈ 2.007 does not
+    let fits = logistic(70);
+    let check = check_asymptote(&fits, 70);
+    assert!(check.approaches_asymptote_ok);
+    assert_eq!(check.first_overshoot_bin, None);
+    // ΔX grows toward p_max while the price per token shrinks
+    assert!(fits.delta_x_of_bin(68) > fits.delta_x_of_bin(60));
+
+    let overshoots = logistic(100);
+    let check = check_asymptote(&overshoots, 100);
+    assert!(!check.approaches_asymptote_ok);
+    assert_eq!(check.first_overshoot_bin, Some(70));
+    assert!(overshoots.price_of_bin(70) >= overshoots.p_max);
+    // The clamp flattens the tail: nothing is allocated past the asymptote
+    assert!((70..100).all(|i| overshoots.delta_x_of_bin(i) == 0.0));
+}
+
+#[test]
+fn allocation_ratio_is_r_to_the_minus_n_minus_one() {
+    let bins = 3000;
+    let c = Geometric {
+        theta: 0.2,
+        ..geometric(0.01, 100.0)
+    };
+    let expected = c.r().powi(-(bins as i32 - 1));
+    let ratio = allocation_ratio(&c, bins);
+    assert!(
+        (ratio / expected - 1.0).abs() < 1e-9,
+        "{ratio} vs {expected}"
+    );
+    assert!(ratio > DEFAULT_MAX_ALLOCATION_RATIO);
+    assert_eq!(
+        verify_geometric(&c, bins).unwrap().allocation_ratio,
+        Some(ratio)
+    );
+
+    // θ = 1 spreads supply evenly; θ near 1 stays well under the default
+    let flat = Geometric {
+        theta: 1.0,
+        ..geometric(0.01, 100.0)
+    };
+    assert_eq!(allocation_ratio(&flat, bins), 1.0);
+    let gentle = Geometric {
+        theta: 0.9,
+        ..geometric(0.01, 100.0)
+    };
+    assert!(allocation_ratio(&gentle, bins) < 1e2);
+}
+
+#[test]
+fn verify_linear_matches_the_arithmetic_series() {
+    let grid = Grid {
+        p0: 0.01,
+        bin_step_bps: 25.0,
+        spacing: GridSpacing::Price,
+    };
+    let c = Linear {
+        grid,
+        base: 1000.0,
+        slope: -2.5,
+    };
+    let rep = verify_linear(&c, 400).unwrap();
+    assert_eq!(
+        rep.supply_closed,
+        Some(400.0 * 1000.0 - 2.5 * 400.0 * 399.0 / 2.0)
+    );
+    assert!(rep.rel_err_supply.unwrap() < 1e-15, "{rep:?}");
+    assert!(rep.monotone_ok && rep.revenue_monotone_ok);
+    assert_eq!(c.delta_x_of_bin(399), 2.5);
+    // Past bin 400 the allocation turns negative
+    assert!(verify_linear(&c, 402).is_err());
+}
+
+#[test]
+fn verify_geometric_cross_checks_revenue_against_its_closed_form() {
+    for theta in [0.0, 0.6, 1.0 - 1e-12, 1.0, 1.5] {
+        let c = Geometric {
+            theta,
+            ..geometric(0.01, 25.0)
+        };
+        let rep = verify_geometric(&c, 5_000).unwrap();
+        let closed = rep.revenue_closed.unwrap();
+        assert_eq!(closed, c.revenue_closed(5_000));
+        assert!(rep.rel_err_revenue.unwrap() < 1e-12, "θ={theta}: {rep:?}");
+    }
+    // g = q^0 = 1: every bin raises exactly R_0
+    assert_eq!(
+        Geometric {
+            theta: 0.0,
+            ..geometric(0.01, 25.0)
+        }
+        .revenue_closed(300),
+        30_000.0
+    );
+    assert_eq!(
+        verify_linear(
+            &Linear {
+                grid: Grid {
+                    p0: 0.01,
+                    bin_step_bps: 25.0,
+                    spacing: GridSpacing::Price,
+                },
+                base: 1.0,
+                slope: 0.0,
+            },
+            10
+        )
+        .unwrap()
+        .revenue_closed,
+        None
+    );
+}
+
+#[test]
+fn verify_logistic_telescopes_and_stays_finite_past_p_max() {
+    let logistic = |bins: i64, k: f64| LogisticS {
+        grid: Grid {
+            p0: 0.01,
+            bin_step_bps: 100.0,
+            spacing: GridSpacing::Price,
+        },
+        p_min: 0.005,
+        p_max: 0.02,
+        k,
+        s_mid: 1e5,
+        bins,
+    };
+    let rep = verify_logistic(&logistic(70, 1e-5), 70).unwrap();
+    assert!(rep.rel_err_supply.unwrap() < 1e-12, "{rep:?}");
+    assert!(rep.monotone_ok);
+    assert!(rep.asymptote.unwrap().approaches_asymptote_ok);
+
+    // Past p_max the clamp keeps S(P) finite and the overshooting bins empty
+    let rep = verify_logistic(&logistic(100, 1e-5), 100).unwrap();
+    assert!(rep.supply_sum.is_finite());
+    assert!(rep.rel_err_supply.unwrap() < 1e-12, "{rep:?}");
+    assert_eq!(rep.asymptote.unwrap().first_overshoot_bin, Some(70));
+
+    // k = 0 has no finite S(P)
+    assert!(verify_logistic(&logistic(70, 0.0), 70).is_err());
+    assert!(verify_logistic(&logistic(1, 1e-5), 1).is_err());
+}
+
+#[test]
+fn allocation_shape_follows_the_geometric_theta_regime() {
+    let shape = |theta: f64| {
+        let c = Geometric {
+            theta,
+            ..geometric(0.01, 25.0)
+        };
+        let rep = verify_curve(&c, 2_000).unwrap();
+        assert_eq!(
+            (rep.convex_ok, rep.concave_ok),
+            check_allocation_shape(&c, 2_000)
+        );
+        assert_eq!(verify_geometric(&c, 2_000).unwrap().shape, rep.shape);
+        rep.shape
+    };
+    // r = q^(θ-1): θ < 1 decays, θ > 1 grows, θ = 1 is flat
+    assert_eq!(shape(0.6), AllocationShape::Decaying);
+    assert_eq!(shape(1.0 - 1e-6), AllocationShape::Decaying);
+    assert_eq!(shape(1.0), AllocationShape::Flat);
+    assert_eq!(shape(1.5), AllocationShape::Growing);
+}
+
+#[test]
+fn verify_curve_labels_linear_and_logistic_shapes() {
+    let grid = Grid {
+        p0: 0.01,
+        bin_step_bps: 25.0,
+        spacing: GridSpacing::Price,
+    };
+    let falling = Linear {
+        grid,
+        base: 1000.0,
+        slope: -2.5,
+    };
+    let rep = verify_curve(&falling, 400).unwrap();
+    assert_eq!(rep.shape, AllocationShape::Decaying);
+    assert_eq!(rep.supply_closed, None);
+    assert_eq!(rep.supply_sum, falling.cumulative_supply(400));
+    assert_eq!(verify_linear(&falling, 400).unwrap().shape, rep.shape);
+    assert!(verify_curve(&falling, 402).is_err());
+
+    // ΔX grows toward p_max on a geometric grid up to the empty last bin; past p_max
+    // the clamp empties the tail
+    let logistic = |bins: i64| LogisticS {
+        grid: Grid {
+            p0: 0.01,
+            bin_step_bps: 100.0,
+            spacing: GridSpacing::Price,
+        },
+        p_min: 0.005,
+        p_max: 0.02,
+        k: 1e-5,
+        s_mid: 1e5,
+        bins,
+    };
+    assert_eq!(
+        verify_curve(&logistic(70), 69).unwrap().shape,
+        AllocationShape::Growing
+    );
+    assert_eq!(
+        verify_logistic(&logistic(100), 100).unwrap().shape,
+        AllocationShape::Mixed
+    );
+}